@@ -0,0 +1,114 @@
+//! Repeated hot queries against a seeded 5,000-task database.
+//!
+//! `cached` goes through the app's query functions, which reuse a prepared
+//! statement; `unprepared` runs the same SQL with `persistent(false)`, so SQLite
+//! parses it every time. Run with `cargo bench --bench task_queries`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use sqlx::SqlitePool;
+use tada_desktop_lib::bench;
+use tokio::runtime::Runtime;
+
+const TASKS: usize = 5_000;
+const LISTS: usize = 10;
+const DAY_MS: i64 = 86_400_000;
+const NOW: i64 = 1_700_000_000_000;
+
+async fn seed(pool: &SqlitePool) {
+    let mut tx = pool.begin().await.unwrap();
+    for list in 0..LISTS {
+        sqlx::query(r#"INSERT OR IGNORE INTO lists (id, name, "order") VALUES (?, ?, ?)"#)
+            .bind(format!("list-{list}"))
+            .bind(format!("List {list}"))
+            .bind(list as i64 + 2)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+    }
+    for i in 0..TASKS {
+        let list = i % LISTS;
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (id, title, completed, due_date, list_id, list_name, "order",
+                               created_at, updated_at, tags, group_category)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, '[]', 'nodate')
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(format!("Task {i}"))
+        .bind(i % 4 == 0)
+        // Spread due dates over two months around NOW, leaving some undated
+        .bind((i % 7 != 0).then(|| NOW + (i as i64 % 60 - 30) * DAY_MS))
+        .bind(format!("list-{list}"))
+        .bind(format!("List {list}"))
+        .bind(i as i64)
+        .bind(NOW)
+        .bind(NOW)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    }
+    tx.commit().await.unwrap();
+}
+
+fn task_queries(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let pool = runtime.block_on(async {
+        let pool = bench::open(&dir.path().join("bench.db")).await.unwrap();
+        bench::migrate(&pool).await.unwrap();
+        seed(&pool).await;
+        pool
+    });
+    // Shared by reference so the `async move` blocks below only copy the reference
+    let pool = &pool;
+
+    let mut group = c.benchmark_group("tasks_in_list");
+    // Switching between lists, as the UI does
+    let lists: Vec<String> = (0..LISTS).map(|l| format!("list-{l}")).collect();
+    group.bench_function(BenchmarkId::new("cached", TASKS), |b| {
+        let mut next = lists.iter().cycle();
+        b.to_async(&runtime).iter(|| {
+            let list = next.next().unwrap();
+            async move { bench::tasks_in_list(pool, list).await.unwrap() }
+        });
+    });
+    group.bench_function(BenchmarkId::new("unprepared", TASKS), |b| {
+        let mut next = lists.iter().cycle();
+        b.to_async(&runtime).iter(|| {
+            let list = next.next().unwrap();
+            async move {
+                sqlx::query(bench::TASKS_IN_LIST_SQL)
+                    .persistent(false)
+                    .bind(list)
+                    .fetch_all(pool)
+                    .await
+                    .unwrap()
+            }
+        });
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("count_due_between");
+    group.bench_function(BenchmarkId::new("cached", TASKS), |b| {
+        b.to_async(&runtime)
+            .iter(|| async { bench::count_due_between(pool, NOW, NOW + DAY_MS).await.unwrap() });
+    });
+    group.bench_function(BenchmarkId::new("unprepared", TASKS), |b| {
+        b.to_async(&runtime).iter(|| async {
+            sqlx::query_scalar::<_, i64>(bench::COUNT_DUE_BETWEEN_SQL)
+                .persistent(false)
+                .bind(NOW)
+                .bind(NOW + DAY_MS)
+                .fetch_one(pool)
+                .await
+                .unwrap()
+        });
+    });
+    group.finish();
+
+    runtime.block_on(pool.close());
+}
+
+criterion_group!(benches, task_queries);
+criterion_main!(benches);
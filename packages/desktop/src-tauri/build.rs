@@ -1,3 +1,5 @@
 fn main() {
+    // For `app_info`; the target triple is only known to build scripts
+    println!("cargo:rustc-env=TADA_TARGET={}", std::env::var("TARGET").unwrap_or_default());
     tauri_build::build()
-}
\ No newline at end of file
+}
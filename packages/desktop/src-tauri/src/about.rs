@@ -0,0 +1,82 @@
+//! Build and platform details for the About dialog and support requests.
+//!
+//! The SQLite version is asked of the linked library with `sqlite_version()`,
+//! since the bundled and system builds differ in what SQL they accept.
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::error::Result;
+use crate::{migrations, AppState};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppInfo {
+    pub app_version: String,
+    pub tauri_version: &'static str,
+    /// `None` where the webview can't be asked.
+    pub webview_version: Option<String>,
+    /// The triple this build was compiled for, like `aarch64-apple-darwin`.
+    pub target: &'static str,
+    pub os: &'static str,
+    /// `None` where it can't be read.
+    pub os_version: Option<String>,
+    pub sqlite_version: String,
+    /// The schema the database is at (`PRAGMA user_version`).
+    pub schema_version: i64,
+    /// The newest schema this build knows.
+    pub latest_schema_version: i64,
+}
+
+#[cfg(target_os = "linux")]
+fn read_os_version() -> Option<String> {
+    // PRETTY_NAME="Ubuntu 24.04.1 LTS"
+    let release = std::fs::read_to_string("/etc/os-release").ok()?;
+    let name = release.lines().find_map(|line| line.strip_prefix("PRETTY_NAME="))?;
+    Some(name.trim_matches('"').to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn read_os_version() -> Option<String> {
+    let output = std::process::Command::new("sw_vers").arg("-productVersion").output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then(|| format!("macOS {version}"))
+}
+
+#[cfg(windows)]
+fn read_os_version() -> Option<String> {
+    use windows_sys::Wdk::System::SystemServices::RtlGetVersion;
+    use windows_sys::Win32::System::SystemInformation::OSVERSIONINFOW;
+
+    // GetVersionEx reports what the manifest claims compatibility with; this doesn't
+    // SAFETY: a zeroed OSVERSIONINFOW with its size set is valid, and the call only writes into it
+    let mut info: OSVERSIONINFOW = unsafe { std::mem::zeroed() };
+    info.dwOSVersionInfoSize = size_of::<OSVERSIONINFOW>() as u32;
+    if unsafe { RtlGetVersion(&mut info) } != 0 {
+        return None;
+    }
+    Some(format!("Windows {}.{}.{}", info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn read_os_version() -> Option<String> {
+    None
+}
+
+/// Versions of the app and what it runs on, for the About dialog and bug reports.
+#[tauri::command]
+pub async fn app_info(app: AppHandle, state: State<'_, AppState>) -> Result<AppInfo> {
+    let pool = state.db();
+    Ok(AppInfo {
+        app_version: app.package_info().version.to_string(),
+        tauri_version: tauri::VERSION,
+        webview_version: tauri::webview_version().ok(),
+        target: env!("TADA_TARGET"),
+        os: std::env::consts::OS,
+        // `sw_vers` is a process to wait on
+        os_version: tauri::async_runtime::spawn_blocking(read_os_version).await.ok().flatten(),
+        sqlite_version: sqlx::query_scalar("SELECT sqlite_version()").fetch_one(&pool).await?,
+        schema_version: sqlx::query_scalar("PRAGMA user_version").fetch_one(&pool).await?,
+        latest_schema_version: migrations::latest_version(),
+    })
+}
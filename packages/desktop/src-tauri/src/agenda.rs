@@ -0,0 +1,71 @@
+//! One dated agenda across lists, with subtasks that have their own due date
+//! listed next to the tasks.
+//!
+//! Each item sorts by its own due date, so a subtask due before its parent
+//! comes first. Subtasks carry their parent's id, title, list and priority.
+
+use serde::Serialize;
+use sqlx::FromRow;
+use tauri::State;
+
+use crate::error::{Error, Result};
+use crate::AppState;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AgendaItem {
+    /// `"task"` or `"subtask"`.
+    pub kind: String,
+    pub id: String,
+    pub title: String,
+    pub due_date: i64,
+    pub completed: bool,
+    pub list_id: Option<String>,
+    pub list_name: String,
+    /// The task a subtask belongs to; `None` for tasks.
+    pub parent_id: Option<String>,
+    pub parent_title: Option<String>,
+    pub priority: Option<i64>,
+}
+
+/// Tasks and subtasks due in `[from_ms, to_ms)`, outside the trash, soonest first.
+/// Completed items, and subtasks of completed tasks, only with `include_completed`.
+#[tauri::command]
+pub async fn agenda(
+    state: State<'_, AppState>,
+    from_ms: i64,
+    to_ms: i64,
+    include_completed: Option<bool>,
+) -> Result<Vec<AgendaItem>> {
+    if to_ms <= from_ms {
+        return Err(Error::InvalidInput("The agenda range must end after it starts".into()));
+    }
+    // Ties on the due date keep a task ahead of subtasks, then go by priority and manual order
+    Ok(sqlx::query_as(
+        r#"
+        SELECT kind, id, title, due_date, completed, list_id, list_name, parent_id, parent_title, priority
+        FROM (
+            SELECT 'task' AS kind, t.id, t.title, t.due_date, t.completed, t.list_id, t.list_name,
+                   NULL AS parent_id, NULL AS parent_title, t.priority, t."order" AS sort_order
+            FROM tasks t
+            WHERE t.deleted_at IS NULL AND t.list_name != 'Trash'
+              AND t.due_date >= ?1 AND t.due_date < ?2
+              AND (?3 OR t.completed = 0)
+            UNION ALL
+            SELECT 'subtask', s.id, s.title, s.due_date, s.completed, t.list_id, t.list_name,
+                   t.id, t.title, t.priority, s."order"
+            FROM subtasks s
+            JOIN tasks t ON t.id = s.parent_id
+            WHERE t.deleted_at IS NULL AND t.list_name != 'Trash'
+              AND s.due_date >= ?1 AND s.due_date < ?2
+              AND (?3 OR (s.completed = 0 AND t.completed = 0))
+        )
+        ORDER BY due_date, kind = 'subtask', COALESCE(priority, 4), sort_order, id
+        "#,
+    )
+    .bind(from_ms)
+    .bind(to_ms)
+    .bind(include_completed.unwrap_or(false))
+    .fetch_all(&state.db())
+    .await?)
+}
@@ -0,0 +1,469 @@
+use std::time::Duration;
+
+use chrono::{Datelike, Duration as Days, Months, NaiveDate};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::dates::{local_date, local_day_bounds, start_of_local_day};
+use crate::db::{new_id, now_ms};
+use crate::models::{Summary, Task};
+use crate::settings;
+use crate::AppState;
+
+const MAX_RETRIES: u32 = 4;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// `test_ai_connection` only lists models, so it gives up much sooner.
+const TEST_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_SUMMARY_PROMPT: &str = "You are a professional reporting assistant. Write a concise, \
+    well-structured Markdown work summary from the tasks of the period and the upcoming tasks. \
+    Start directly with the report content.";
+
+/// The `ai` settings blob. Deliberately not `Debug`, so the key can't end up in logs.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiSettings {
+    #[serde(default)]
+    pub provider: String,
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub base_url: String,
+}
+
+/// Errors the UI can tell apart, serialized as `{ kind, message }`.
+#[derive(Debug, Serialize, thiserror::Error)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum AiError {
+    #[error("AI is not configured: {0}")]
+    NotConfigured(String),
+    #[error("The AI provider is rate limiting requests, please try again later")]
+    RateLimited,
+    #[error("The AI provider rejected the API key")]
+    Auth,
+    #[error("The AI provider returned an error ({0})")]
+    Http(String),
+    #[error("Could not reach the AI provider: {0}")]
+    Network(String),
+    #[error("The AI provider's URL is not valid: {0}")]
+    BadUrl(String),
+    #[error("The AI provider has no model named '{0}'")]
+    ModelNotFound(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl From<crate::error::Error> for AiError {
+    fn from(e: crate::error::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AiError {
+    fn from(e: reqwest::Error) -> Self {
+        // Some providers take the key as a query parameter, so never echo the URL
+        let message = e.without_url().to_string();
+        if e.is_builder() { Self::BadUrl(message) } else { Self::Network(message) }
+    }
+}
+
+fn openai_compatible_base(provider: &str) -> Option<&'static str> {
+    Some(match provider {
+        "openai" => "https://api.openai.com/v1",
+        "gemini" => "https://generativelanguage.googleapis.com/v1beta/openai",
+        "xai" => "https://api.x.ai/v1",
+        "groq" => "https://api.groq.com/openai/v1",
+        "openrouter" => "https://openrouter.ai/api/v1",
+        "siliconflow" => "https://api.siliconflow.cn/v1",
+        "302" => "https://api.302.ai/v1",
+        "moonshot" => "https://api.moonshot.cn/v1",
+        "deepseek" => "https://api.deepseek.com/v1",
+        "zhipu" => "https://open.bigmodel.cn/api/paas/v4",
+        "bytedance" => "https://ark.cn-beijing.volces.com/api/v3",
+        _ => return None,
+    })
+}
+
+impl AiSettings {
+    pub async fn load(pool: &SqlitePool) -> Result<Self, AiError> {
+        settings::get(pool, "ai")
+            .await?
+            .ok_or_else(|| AiError::NotConfigured("no AI settings saved".into()))
+    }
+
+    /// Base URL of the provider's OpenAI-compatible API, without a trailing slash.
+    pub fn api_base(&self) -> Result<String, AiError> {
+        let base_url = self.base_url.trim().trim_end_matches('/');
+        match self.provider.as_str() {
+            // Same path the frontend appends for these providers
+            "custom" | "ollama" if !base_url.is_empty() => {
+                let parsed = reqwest::Url::parse(base_url).map_err(|e| AiError::BadUrl(e.to_string()))?;
+                if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+                    return Err(AiError::BadUrl("expected an http:// or https:// address".into()));
+                }
+                Ok(format!("{base_url}/v1"))
+            }
+            "custom" | "ollama" => Err(AiError::NotConfigured("a base URL is required".into())),
+            provider => openai_compatible_base(provider).map(str::to_string).ok_or_else(|| {
+                AiError::NotConfigured(format!("provider '{provider}' has no OpenAI-compatible endpoint"))
+            }),
+        }
+    }
+
+    pub fn requires_api_key(&self) -> bool {
+        self.provider != "ollama"
+    }
+}
+
+pub fn http_client() -> Result<reqwest::Client, AiError> {
+    Ok(reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?)
+}
+
+/// POSTs JSON, retrying 429 responses with exponential backoff (honoring `Retry-After`).
+async fn post_with_backoff(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: &str,
+    body: &Value,
+) -> Result<reqwest::Response, AiError> {
+    let mut delay = Duration::from_secs(1);
+    for attempt in 0..=MAX_RETRIES {
+        let mut request = client.post(url).json(body);
+        if !api_key.is_empty() {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await?;
+        match response.status() {
+            status if status.is_success() => return Ok(response),
+            StatusCode::TOO_MANY_REQUESTS if attempt < MAX_RETRIES => {
+                let wait = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .map(Duration::from_secs)
+                    .unwrap_or(delay);
+                tokio::time::sleep(wait).await;
+                delay *= 2;
+            }
+            StatusCode::TOO_MANY_REQUESTS => return Err(AiError::RateLimited),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(AiError::Auth),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                return Err(AiError::Http(format!("{status}: {}", body.chars().take(300).collect::<String>())));
+            }
+        }
+    }
+    Err(AiError::RateLimited)
+}
+
+/// Streams a chat completion, calling `on_delta` for every content chunk, and returns the full text.
+pub async fn stream_chat(
+    settings: &AiSettings,
+    system_prompt: &str,
+    user_prompt: &str,
+    mut on_delta: impl FnMut(&str),
+) -> Result<String, AiError> {
+    if settings.model.trim().is_empty() {
+        return Err(AiError::NotConfigured("no model selected".into()));
+    }
+    if settings.requires_api_key() && settings.api_key.trim().is_empty() {
+        return Err(AiError::NotConfigured("an API key is required".into()));
+    }
+
+    let url = format!("{}/chat/completions", settings.api_base()?);
+    let body = json!({
+        "model": settings.model,
+        "stream": true,
+        "messages": [
+            { "role": "system", "content": system_prompt },
+            { "role": "user", "content": user_prompt },
+        ],
+    });
+    let mut response = post_with_backoff(&http_client()?, &url, settings.api_key.trim(), &body).await?;
+
+    // Server-sent events; lines can straddle chunks, so buffer bytes until a newline
+    let mut pending: Vec<u8> = Vec::new();
+    let mut text = String::new();
+    'stream: while let Some(chunk) = response.chunk().await? {
+        pending.extend_from_slice(&chunk);
+        while let Some(pos) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                break 'stream;
+            }
+            let Ok(event) = serde_json::from_str::<Value>(data) else {
+                continue;
+            };
+            if let Some(delta) = event["choices"][0]["delta"]["content"].as_str() {
+                text.push_str(delta);
+                on_delta(delta);
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Outcome of `test_ai_connection`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiTestResult {
+    /// Model ids the provider offers, sorted, for the `availableModels` setting.
+    pub models: Vec<String>,
+    pub latency_ms: i64,
+}
+
+/// Checks the saved `ai` settings by listing the provider's models, without
+/// spending any tokens. Fails with the same error kinds as `generate_summary`,
+/// plus `badUrl` for an address that can't be used and `modelNotFound` when
+/// the selected model isn't on the list.
+#[tauri::command]
+pub async fn test_ai_connection(state: State<'_, AppState>) -> Result<AiTestResult, AiError> {
+    let settings = AiSettings::load(&state.db()).await?;
+    if settings.requires_api_key() && settings.api_key.trim().is_empty() {
+        return Err(AiError::NotConfigured("an API key is required".into()));
+    }
+    let base = settings.api_base()?;
+    log::info!("Testing the AI connection to {} ({base})", settings.provider);
+
+    let client = reqwest::Client::builder().timeout(TEST_TIMEOUT).build()?;
+    let mut request = client.get(format!("{base}/models"));
+    if !settings.api_key.trim().is_empty() {
+        request = request.bearer_auth(settings.api_key.trim());
+    }
+    let started = now_ms();
+    let response = request.send().await?;
+    let latency_ms = now_ms() - started;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(AiError::Auth),
+        StatusCode::TOO_MANY_REQUESTS => return Err(AiError::RateLimited),
+        // Nothing answers at the models path, so the base URL points somewhere else
+        StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED => {
+            return Err(AiError::BadUrl(format!("{base} is not an OpenAI-compatible API")));
+        }
+        status => return Err(AiError::Http(status.to_string())),
+    }
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|_| AiError::BadUrl(format!("{base} did not answer with a model list")))?;
+    let mut models: Vec<String> = body["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| model["id"].as_str())
+        .map(str::to_string)
+        .collect();
+    models.sort();
+    models.dedup();
+
+    let model = settings.model.trim();
+    if !model.is_empty() && !models.is_empty() && !models.iter().any(|m| m == model) {
+        return Err(AiError::ModelNotFound(model.to_string()));
+    }
+    log::info!("AI connection OK: {} models in {latency_ms} ms", models.len());
+    Ok(AiTestResult { models, latency_ms })
+}
+
+fn end_of_day(date: NaiveDate) -> i64 {
+    local_day_bounds(start_of_local_day(date)).1 - 1
+}
+
+/// Inclusive `[start, end]` bounds for the summary period keys the frontend uses.
+/// Weeks start on Sunday, matching date-fns defaults in the UI.
+pub fn period_bounds(period_key: &str, now: i64) -> Option<(i64, i64)> {
+    let today = local_date(now);
+    let week_start = today - Days::days(today.weekday().num_days_from_sunday().into());
+    let month_start = today.with_day(1)?;
+    let (start, end) = match period_key {
+        "today" => (today, today),
+        "yesterday" => (today.pred_opt()?, today.pred_opt()?),
+        "thisWeek" => (week_start, week_start + Days::days(6)),
+        "lastWeek" => (week_start - Days::days(7), week_start - Days::days(1)),
+        "thisMonth" => (month_start, month_start.checked_add_months(Months::new(1))?.pred_opt()?),
+        "lastMonth" => (month_start.checked_sub_months(Months::new(1))?, month_start.pred_opt()?),
+        custom => {
+            let mut parts = custom.strip_prefix("custom_")?.split('_');
+            let start: i64 = parts.next()?.parse().ok()?;
+            let end: i64 = parts.next()?.parse().ok()?;
+            (local_date(start), local_date(end))
+        }
+    };
+    Some((start_of_local_day(start), end_of_day(end)))
+}
+
+fn strip_base64_images(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("![") {
+        let candidate = &rest[start..];
+        let parsed = candidate.find("](data:").and_then(|alt_end| {
+            let url_end = candidate[alt_end..].find(')')? + alt_end;
+            Some((&candidate[2..alt_end], url_end))
+        });
+        match parsed {
+            Some((alt, url_end)) if !alt.contains(']') => {
+                out.push_str(&rest[..start]);
+                out.push_str(&if alt.is_empty() { "[Image]".to_string() } else { format!("[Image: {alt}]") });
+                rest = &candidate[url_end + 1..];
+            }
+            _ => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn build_prompt(tasks: &[Task], future: &[Task]) -> String {
+    let mut prompt = if tasks.is_empty() {
+        "No tasks were selected for the primary summary period.".to_string()
+    } else {
+        let lines: Vec<String> = tasks
+            .iter()
+            .map(|t| {
+                let status = if t.completed { "Completed" } else { "Incomplete" };
+                let progress = t
+                    .complete_percentage
+                    .filter(|p| *p > 0)
+                    .map(|p| format!(", {p}% done"))
+                    .unwrap_or_default();
+                let notes = strip_base64_images(t.content.as_deref().unwrap_or("N/A"));
+                format!("- Task: \"{}\" (Status: {status}{progress})\n  Notes: {notes}", t.title)
+            })
+            .collect();
+        format!("## Tasks from the summary period:\n{}", lines.join("\n"))
+    };
+
+    if future.is_empty() {
+        prompt.push_str("\n\nNo specific upcoming tasks were provided for context.");
+    } else {
+        let lines: Vec<String> = future
+            .iter()
+            .map(|t| {
+                let due = t.due_date.map(|d| local_date(d).to_string()).unwrap_or_else(|| "N/A".into());
+                format!("- Task: \"{}\" (Due: {due})", t.title)
+            })
+            .collect();
+        prompt.push_str(&format!("\n\n## Upcoming tasks for future planning context:\n{}", lines.join("\n")));
+    }
+    prompt
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SummaryProgress<'a> {
+    period_key: &'a str,
+    list_key: &'a str,
+    delta: &'a str,
+}
+
+/// Generates an AI summary for a period and list entirely on the Rust side, so
+/// the API key never reaches the webview. Text streams out via `summary-progress`.
+#[tauri::command]
+pub async fn generate_summary(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    period_key: String,
+    list_key: String,
+    system_prompt: Option<String>,
+) -> Result<Summary, AiError> {
+    log::info!("Generating summary for {period_key} / {list_key}");
+    let pool = &state.db();
+    let settings = AiSettings::load(pool).await?;
+    let (start, end) = period_bounds(&period_key, now_ms())
+        .ok_or_else(|| AiError::Internal(format!("Unknown summary period '{period_key}'")))?;
+
+    // Same selection as the summary view: completion time for done tasks, due date for open ones
+    let tasks: Vec<Task> = sqlx::query_as(
+        r#"
+        SELECT * FROM tasks
+        WHERE list_name != 'Trash' AND deleted_at IS NULL AND (?1 = 'all' OR list_name = ?1)
+          AND CASE
+                WHEN completed = 1 AND completed_at IS NOT NULL THEN completed_at
+                WHEN completed = 0 AND due_date IS NOT NULL THEN due_date
+                ELSE updated_at
+              END BETWEEN ?2 AND ?3
+        ORDER BY due_date IS NULL, due_date, "order", created_at
+        "#,
+    )
+    .bind(&list_key)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(crate::error::Error::from)?;
+
+    let future: Vec<Task> = sqlx::query_as(
+        r#"
+        SELECT * FROM tasks
+        WHERE list_name != 'Trash' AND deleted_at IS NULL AND (?1 = 'all' OR list_name = ?1)
+          AND completed = 0 AND due_date > ?2
+        ORDER BY due_date
+        "#,
+    )
+    .bind(&list_key)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(crate::error::Error::from)?;
+
+    if tasks.is_empty() && future.is_empty() {
+        return Err(AiError::Internal("No tasks were found for this summary.".into()));
+    }
+
+    let system_prompt = system_prompt.unwrap_or_else(|| DEFAULT_SUMMARY_PROMPT.to_string());
+    let text = stream_chat(&settings, &system_prompt, &build_prompt(&tasks, &future), |delta| {
+        let _ = app.emit(
+            "summary-progress",
+            SummaryProgress {
+                period_key: &period_key,
+                list_key: &list_key,
+                delta,
+            },
+        );
+    })
+    .await?;
+
+    let now = now_ms();
+    let summary = Summary {
+        id: new_id(),
+        created_at: now,
+        updated_at: now,
+        period_key: period_key.clone(),
+        list_key: list_key.clone(),
+        task_ids: tasks.into_iter().map(|t| t.id).collect(),
+        summary_text: text,
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO summaries (id, created_at, updated_at, period_key, list_key, task_ids, summary_text)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&summary.id)
+    .bind(summary.created_at)
+    .bind(summary.updated_at)
+    .bind(&summary.period_key)
+    .bind(&summary.list_key)
+    .bind(serde_json::to_string(&summary.task_ids).unwrap_or_else(|_| "[]".into()))
+    .bind(&summary.summary_text)
+    .execute(pool)
+    .await
+    .map_err(crate::error::Error::from)?;
+
+    Ok(summary)
+}
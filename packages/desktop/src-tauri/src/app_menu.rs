@@ -0,0 +1,68 @@
+//! The macOS application menu.
+//!
+//! The stock Quit item asks AppKit to terminate, which reaches Tauri in ways
+//! that don't always count as a real quit, so Cmd+Q sometimes only hid the
+//! window. This menu's Quit is an ordinary item that takes the same path as the
+//! tray's Quit. Cmd+W closes the window, which the close handler hides to the tray.
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Wry};
+
+use crate::close_behavior;
+
+const QUIT_ID: &str = "app-quit";
+
+pub fn build(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let separator = || PredefinedMenuItem::separator(app);
+    let quit = MenuItem::with_id(app, QUIT_ID, "Quit Tada", true, Some("CmdOrCtrl+Q"))?;
+    let app_menu = Submenu::with_items(
+        app,
+        "Tada",
+        true,
+        &[
+            &PredefinedMenuItem::about(app, None, None)?,
+            &separator()?,
+            &PredefinedMenuItem::services(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::hide(app, None)?,
+            &PredefinedMenuItem::hide_others(app, None)?,
+            &PredefinedMenuItem::show_all(app, None)?,
+            &separator()?,
+            &quit,
+        ],
+    )?;
+    // Without an Edit menu the webview's text fields lose copy and paste shortcuts
+    let edit_menu = Submenu::with_items(
+        app,
+        "Edit",
+        true,
+        &[
+            &PredefinedMenuItem::undo(app, None)?,
+            &PredefinedMenuItem::redo(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::cut(app, None)?,
+            &PredefinedMenuItem::copy(app, None)?,
+            &PredefinedMenuItem::paste(app, None)?,
+            &PredefinedMenuItem::select_all(app, None)?,
+        ],
+    )?;
+    let window_menu = Submenu::with_items(
+        app,
+        "Window",
+        true,
+        &[
+            &PredefinedMenuItem::minimize(app, None)?,
+            &PredefinedMenuItem::maximize(app, None)?,
+            &PredefinedMenuItem::fullscreen(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::close_window(app, None)?,
+        ],
+    )?;
+    Menu::with_items(app, &[&app_menu, &edit_menu, &window_menu])
+}
+
+pub fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    if event.id() == QUIT_ID {
+        close_behavior::quit(app);
+    }
+}
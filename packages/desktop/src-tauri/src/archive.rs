@@ -0,0 +1,140 @@
+//! Moves old completed tasks out of `tasks` into `archived_tasks`, and back.
+//!
+//! Rows keep their ids and timestamps, so an unarchived task is identical to the
+//! one that left. Leaving `tasks` records a tombstone like any deletion, so other
+//! synced devices drop the task too. Tasks with attachments stay put: the
+//! attachment rows would cascade away and their files be purged as orphans.
+
+use sqlx::{QueryBuilder, Sqlite, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::commands::fetch_task;
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::Task;
+use crate::query::{order_by, push_conditions, TaskFilter};
+use crate::AppState;
+
+const DAY_MS: i64 = 86_400_000;
+
+/// Columns shared by `tasks` and `archived_tasks`, spelled out so the copy
+/// can't misalign if either table gains a column.
+const TASK_COLUMNS: &str = r#"id, title, completed, completed_at, complete_percentage, due_date, list_id,
+    list_name, content, "order", created_at, updated_at, tags, priority, group_category,
+    recurrence_rule, color, start_date, pinned, estimated_minutes, actual_minutes, status, board_order,
+    reminder_offset_minutes"#;
+const SUBTASK_COLUMNS: &str = r#"id, parent_id, title, completed, completed_at, due_date, "order",
+    created_at, updated_at"#;
+
+/// Moves the tasks in `ids` (a JSON array) and their subtasks into the archive.
+async fn move_to_archive(tx: &mut Transaction<'_, Sqlite>, ids: &str) -> Result<u64> {
+    sqlx::query(&format!(
+        "INSERT INTO archived_tasks ({TASK_COLUMNS}, archived_at)
+         SELECT {TASK_COLUMNS}, ? FROM tasks WHERE id IN (SELECT value FROM json_each(?))"
+    ))
+    .bind(now_ms())
+    .bind(ids)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO archived_subtasks ({SUBTASK_COLUMNS})
+         SELECT {SUBTASK_COLUMNS} FROM subtasks WHERE parent_id IN (SELECT value FROM json_each(?))"
+    ))
+    .bind(ids)
+    .execute(&mut **tx)
+    .await?;
+    // Subtasks are deleted explicitly so their tombstones are recorded too
+    sqlx::query("DELETE FROM subtasks WHERE parent_id IN (SELECT value FROM json_each(?))")
+        .bind(ids)
+        .execute(&mut **tx)
+        .await?;
+    Ok(sqlx::query("DELETE FROM tasks WHERE id IN (SELECT value FROM json_each(?))")
+        .bind(ids)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected())
+}
+
+/// Archives tasks completed more than `older_than_days` ago, returning how many moved.
+#[tauri::command]
+pub async fn archive_old_completed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    older_than_days: i64,
+) -> Result<usize> {
+    if older_than_days < 0 {
+        return Err(Error::InvalidInput("older_than_days can't be negative".into()));
+    }
+    let cutoff = now_ms() - older_than_days.saturating_mul(DAY_MS);
+    let mut tx = state.db().begin().await?;
+    let ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM tasks t
+        WHERE completed = 1 AND completed_at IS NOT NULL AND completed_at < ? AND deleted_at IS NULL
+          AND NOT EXISTS (SELECT 1 FROM attachments a WHERE a.task_id = t.id)
+        "#,
+    )
+    .bind(cutoff)
+    .fetch_all(&mut *tx)
+    .await?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let moved = move_to_archive(&mut tx, &serde_json::to_string(&ids)?).await?;
+    tx.commit().await?;
+    log::info!("Archived {moved} completed tasks older than {older_than_days} days");
+    events::tasks_changed(&app);
+    Ok(moved as usize)
+}
+
+/// Lists archived tasks with the same filter as `query_tasks`.
+#[tauri::command]
+pub async fn query_archive(state: State<'_, AppState>, filter: TaskFilter) -> Result<Vec<Task>> {
+    let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM archived_tasks");
+    push_conditions(&mut query, &filter);
+    query.push(order_by(&filter));
+    if let Some(limit) = filter.limit {
+        query.push(" LIMIT ").push_bind(limit.max(0));
+    }
+    Ok(query.build_query_as::<Task>().fetch_all(&state.db()).await?)
+}
+
+/// Moves an archived task and its subtasks back into `tasks` unchanged.
+#[tauri::command]
+pub async fn unarchive(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<Task> {
+    let mut tx = state.db().begin().await?;
+    let restored = sqlx::query(&format!(
+        "INSERT INTO tasks ({TASK_COLUMNS}) SELECT {TASK_COLUMNS} FROM archived_tasks WHERE id = ?"
+    ))
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+    if restored.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("Archived task {id}")));
+    }
+    sqlx::query(&format!(
+        "INSERT INTO subtasks ({SUBTASK_COLUMNS}) SELECT {SUBTASK_COLUMNS} FROM archived_subtasks WHERE parent_id = ?"
+    ))
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+    // Cascades to archived_subtasks
+    sqlx::query("DELETE FROM archived_tasks WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+
+    // The list may have been deleted while the task was archived
+    sqlx::query(
+        "UPDATE tasks SET list_id = NULL WHERE id = ? AND list_id NOT IN (SELECT id FROM lists WHERE deleted_at IS NULL)",
+    )
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    let task = fetch_task(&mut tx, &id).await?;
+    tx.commit().await?;
+    log::debug!("Unarchived task {id}");
+    events::task_created(&app, &task);
+    Ok(task)
+}
@@ -0,0 +1,519 @@
+//! Files attached to tasks, copied into `attachments/<workspace>/` under the
+//! app data dir, one folder per workspace after its database file. Files from
+//! before the split sit in `attachments/` itself and are still found there.
+//!
+//! Files up to the `attachmentBlobMaxBytes` setting are kept in the database
+//! instead, in `attachment_blobs` with an empty `stored_path`, so they travel
+//! with the database file. The webview reads either kind through the
+//! `tada-attachment` protocol, e.g. `<img src="tada-attachment://localhost/<id>">`
+//! (`http://tada-attachment.localhost/<id>` on Windows). Voice notes recorded
+//! in the webview are stored the same way and play back through the protocol,
+//! which answers the range requests `<audio>` makes while seeking.
+
+use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use sqlx::SqlitePool;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, State, UriSchemeContext, UriSchemeResponder, Wry};
+
+use crate::db::{new_id, now_ms};
+use crate::error::{Error, Result};
+use crate::models::Attachment;
+use crate::paths;
+use crate::settings;
+use crate::workspaces;
+use crate::AppState;
+
+const ATTACHMENTS_DIR: &str = "attachments";
+/// Settings key for the largest file `attach_file` accepts, in bytes.
+const MAX_SIZE_KEY: &str = "attachmentMaxBytes";
+const DEFAULT_MAX_SIZE: u64 = 50 * 1024 * 1024;
+/// Settings key for the largest file stored in the database rather than on disk; 0 turns that off.
+const BLOB_MAX_SIZE_KEY: &str = "attachmentBlobMaxBytes";
+const DEFAULT_BLOB_MAX_SIZE: u64 = 256 * 1024;
+pub const PROTOCOL: &str = "tada-attachment";
+/// Most bytes one range request of the protocol gets back, so seeking through a
+/// large video never reads it whole.
+const RANGE_CHUNK: u64 = 1024 * 1024;
+
+/// The folder all workspaces shared before each got its own.
+fn shared_dir(app: &AppHandle) -> Result<PathBuf> {
+    Ok(paths::data_dir(app)?.join(ATTACHMENTS_DIR))
+}
+
+/// The active workspace's attachments folder, where new files go.
+pub fn attachments_dir(app: &AppHandle) -> Result<PathBuf> {
+    Ok(shared_dir(app)?.join(workspaces::active_folder(&paths::config_dir(app)?)))
+}
+
+/// Reduces a file name to a single safe path component: no separators,
+/// no `..`, no control characters, and never empty.
+fn sanitize_filename(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.').trim_end_matches(['.', ' ']);
+    if cleaned.is_empty() { "file".to_string() } else { cleaned.chars().take(120).collect() }
+}
+
+/// Resolves a stored path, refusing anything that would land outside the attachments folder.
+fn resolve_stored(dir: &Path, stored_path: &str) -> Result<PathBuf> {
+    let relative = Path::new(stored_path);
+    let mut components = relative.components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(_)), None) => Ok(dir.join(relative)),
+        _ => Err(Error::InvalidInput(format!("Invalid attachment path '{stored_path}'"))),
+    }
+}
+
+/// A stored file's path: in the workspace's folder, or in the shared one when
+/// it was attached before the split.
+fn stored_file(app: &AppHandle, stored_path: &str) -> Result<PathBuf> {
+    let path = resolve_stored(&attachments_dir(app)?, stored_path)?;
+    if path.exists() {
+        return Ok(path);
+    }
+    let shared = resolve_stored(&shared_dir(app)?, stored_path)?;
+    Ok(if shared.exists() { shared } else { path })
+}
+
+fn mime_for(filename: &str) -> &'static str {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "heic" => "image/heic",
+        "pdf" => "application/pdf",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "ogg" | "oga" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Deletes stored files, logging rather than failing: the rows are already gone.
+pub fn remove_files(app: &AppHandle, stored_paths: &[String]) {
+    // Attachments kept in the database have no file
+    for stored_path in stored_paths.iter().filter(|p| !p.is_empty()) {
+        let result = stored_file(app, stored_path).and_then(|path| Ok(std::fs::remove_file(path)?));
+        match result {
+            Ok(()) => {}
+            Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Failed to remove attachment file {stored_path}: {e}"),
+        }
+    }
+}
+
+/// Stored paths of a task's attachments, read before the task is deleted.
+pub async fn stored_paths_for_task<'e, E>(executor: E, task_id: &str) -> Result<Vec<String>>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    Ok(sqlx::query_scalar("SELECT stored_path FROM attachments WHERE task_id = ?")
+        .bind(task_id)
+        .fetch_all(executor)
+        .await?)
+}
+
+/// Files in `dir` that no row in `known` refers to.
+fn orphans_in(dir: &Path, known: &HashSet<String>) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| entry.file_name().to_str().is_some_and(|name| !known.contains(name)))
+        .map(|entry| entry.path())
+        .collect()
+}
+
+/// Removes files whose row is gone, e.g. after the frontend deleted a task directly.
+/// The shared folder from before the split may hold any workspace's files, so
+/// it's only cleaned while there's a single workspace.
+pub async fn purge_orphans(app: &AppHandle, pool: &SqlitePool) -> Result<usize> {
+    let known: HashSet<String> = sqlx::query_scalar("SELECT stored_path FROM attachments")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+    let mut orphans = orphans_in(&attachments_dir(app)?, &known);
+    if !workspaces::has_several(&paths::config_dir(app)?) {
+        orphans.extend(orphans_in(&shared_dir(app)?, &known));
+    }
+    for path in &orphans {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove attachment file {}: {e}", path.display());
+        }
+    }
+    Ok(orphans.len())
+}
+
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = handle.state::<AppState>();
+        let _permit = state.job_permit().await;
+        match purge_orphans(&handle, &state.db()).await {
+            Ok(0) => {}
+            Ok(n) => log::info!("Removed {n} orphaned attachment files"),
+            Err(e) => log::error!("Failed to clean up attachments: {e}"),
+        }
+    });
+}
+
+/// Copies a file into the app's attachments folder and links it to a task.
+#[tauri::command]
+pub async fn attach_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    source_path: String,
+) -> Result<Attachment> {
+    attach(&app, &state.db(), task_id, &source_path).await
+}
+
+async fn ensure_task(pool: &SqlitePool, task_id: &str) -> Result<()> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tasks WHERE id = ? AND deleted_at IS NULL)")
+        .bind(task_id)
+        .fetch_one(pool)
+        .await?;
+    if !exists {
+        return Err(Error::NotFound(format!("Task {task_id}")));
+    }
+    Ok(())
+}
+
+/// Checks `len` against the size limit and says whether it belongs in the database.
+async fn in_database(pool: &SqlitePool, len: u64) -> Result<bool> {
+    let max_size = settings::get::<u64>(pool, MAX_SIZE_KEY).await?.unwrap_or(DEFAULT_MAX_SIZE);
+    if len > max_size {
+        return Err(Error::InvalidInput(format!(
+            "Attachments can be at most {} MB",
+            max_size / (1024 * 1024)
+        )));
+    }
+    let blob_max_size = settings::get::<u64>(pool, BLOB_MAX_SIZE_KEY).await?.unwrap_or(DEFAULT_BLOB_MAX_SIZE);
+    Ok(blob_max_size > 0 && len <= blob_max_size)
+}
+
+/// `attach_file` for Rust callers such as dropped files.
+pub async fn attach(app: &AppHandle, pool: &SqlitePool, task_id: String, source_path: &str) -> Result<Attachment> {
+    ensure_task(pool, &task_id).await?;
+
+    let source = PathBuf::from(source_path);
+    let metadata = std::fs::metadata(&source)?;
+    if !metadata.is_file() {
+        return Err(Error::InvalidInput(format!("{source_path} is not a file")));
+    }
+    let in_database = in_database(pool, metadata.len()).await?;
+
+    let filename = sanitize_filename(&source.file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+    let id = new_id();
+    let mut attachment = Attachment {
+        stored_path: if in_database { String::new() } else { format!("{id}-{filename}") },
+        id,
+        task_id,
+        mime: mime_for(&filename).to_string(),
+        filename,
+        size: 0,
+        created_at: now_ms(),
+    };
+
+    if in_database {
+        let data = std::fs::read(&source)?;
+        attachment.size = data.len() as i64;
+        insert_with_blob(pool, &attachment, data).await?;
+    } else {
+        let dir = attachments_dir(app)?;
+        std::fs::create_dir_all(&dir)?;
+        let target = resolve_stored(&dir, &attachment.stored_path)?;
+        attachment.size = std::fs::copy(&source, &target)? as i64;
+        insert_with_file(pool, &attachment, &target).await?;
+    }
+    log::debug!(
+        "Attached {} to task {}{}",
+        attachment.filename,
+        attachment.task_id,
+        if in_database { " in the database" } else { "" }
+    );
+    Ok(attachment)
+}
+
+/// Formats a voice note may be recorded in, as the webview's `MediaRecorder` reports them.
+const VOICE_NOTE_MIMES: &[(&str, &str)] = &[
+    ("audio/webm", "webm"),
+    ("audio/ogg", "ogg"),
+    ("audio/mp4", "m4a"),
+    ("audio/aac", "aac"),
+    ("audio/mpeg", "mp3"),
+    ("audio/wav", "wav"),
+    ("audio/x-wav", "wav"),
+];
+
+/// Stores a recording made in the webview as an attachment of the task.
+///
+/// `mime` may carry parameters such as `;codecs=opus`; only the type itself
+/// is checked and stored. The bytes are moved into the blob or written
+/// straight to the file, never copied.
+#[tauri::command]
+pub async fn save_voice_note(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    bytes: Vec<u8>,
+    mime: String,
+) -> Result<Attachment> {
+    let pool = state.db();
+    ensure_task(&pool, &task_id).await?;
+
+    let essence = mime.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    let Some(&(mime, extension)) = VOICE_NOTE_MIMES.iter().find(|(m, _)| *m == essence) else {
+        return Err(Error::InvalidInput(format!("Unsupported voice note format '{mime}'")));
+    };
+    if bytes.is_empty() {
+        return Err(Error::InvalidInput("The recording is empty".into()));
+    }
+    let in_database = in_database(&pool, bytes.len() as u64).await?;
+
+    let filename = format!("Voice note {}.{extension}", Local::now().format("%Y-%m-%d %H.%M.%S"));
+    let id = new_id();
+    let attachment = Attachment {
+        stored_path: if in_database { String::new() } else { format!("{id}-{filename}") },
+        id,
+        task_id,
+        mime: mime.to_string(),
+        filename,
+        size: bytes.len() as i64,
+        created_at: now_ms(),
+    };
+
+    if in_database {
+        insert_with_blob(&pool, &attachment, bytes).await?;
+    } else {
+        let dir = attachments_dir(&app)?;
+        std::fs::create_dir_all(&dir)?;
+        let target = resolve_stored(&dir, &attachment.stored_path)?;
+        let mut file = std::fs::File::create(&target)?;
+        if let Err(e) = file.write_all(&bytes).and_then(|()| file.sync_all()) {
+            let _ = std::fs::remove_file(&target);
+            return Err(e.into());
+        }
+        insert_with_file(&pool, &attachment, &target).await?;
+    }
+    log::debug!("Saved a voice note of {} bytes to task {}", attachment.size, attachment.task_id);
+    Ok(attachment)
+}
+
+async fn insert_with_blob(pool: &SqlitePool, attachment: &Attachment, data: Vec<u8>) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    insert_row(&mut *tx, attachment).await?;
+    sqlx::query("INSERT INTO attachment_blobs (attachment_id, data) VALUES (?, ?)")
+        .bind(&attachment.id)
+        .bind(data)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn insert_with_file(pool: &SqlitePool, attachment: &Attachment, target: &Path) -> Result<()> {
+    if let Err(e) = insert_row(pool, attachment).await {
+        // Don't leave a copy behind that no row points to
+        let _ = std::fs::remove_file(target);
+        return Err(e);
+    }
+    Ok(())
+}
+
+async fn insert_row<'e, E>(executor: E, attachment: &Attachment) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO attachments (id, task_id, filename, stored_path, mime, size, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&attachment.id)
+    .bind(&attachment.task_id)
+    .bind(&attachment.filename)
+    .bind(&attachment.stored_path)
+    .bind(&attachment.mime)
+    .bind(attachment.size)
+    .bind(attachment.created_at)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Where an attachment's bytes are.
+enum Contents {
+    Blob,
+    File(PathBuf),
+}
+
+async fn locate(app: &AppHandle, pool: &SqlitePool, id: &str) -> Result<(Attachment, Contents)> {
+    let attachment: Attachment = sqlx::query_as("SELECT * FROM attachments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Attachment {id}")))?;
+    let contents = if attachment.stored_path.is_empty() {
+        Contents::Blob
+    } else {
+        Contents::File(stored_file(app, &attachment.stored_path)?)
+    };
+    Ok((attachment, contents))
+}
+
+/// Byte length of the contents, which the `size` column only records.
+async fn length(pool: &SqlitePool, id: &str, contents: &Contents) -> Result<u64> {
+    match contents {
+        Contents::Blob => {
+            let length: Option<i64> =
+                sqlx::query_scalar("SELECT length(data) FROM attachment_blobs WHERE attachment_id = ?")
+                    .bind(id)
+                    .fetch_optional(pool)
+                    .await?;
+            length.map(|n| n as u64).ok_or_else(|| Error::NotFound(format!("Contents of attachment {id}")))
+        }
+        Contents::File(path) => Ok(std::fs::metadata(path)?.len()),
+    }
+}
+
+/// Reads `len` bytes from `start`, without loading the rest.
+async fn read_range(pool: &SqlitePool, id: &str, contents: &Contents, start: u64, len: u64) -> Result<Vec<u8>> {
+    match contents {
+        Contents::Blob => {
+            let data: Option<Vec<u8>> =
+                sqlx::query_scalar("SELECT substr(data, ?, ?) FROM attachment_blobs WHERE attachment_id = ?")
+                    .bind(start as i64 + 1)
+                    .bind(len as i64)
+                    .bind(id)
+                    .fetch_optional(pool)
+                    .await?;
+            data.ok_or_else(|| Error::NotFound(format!("Contents of attachment {id}")))
+        }
+        Contents::File(path) => {
+            let mut file = std::fs::File::open(path)?;
+            file.seek(SeekFrom::Start(start))?;
+            let mut data = Vec::with_capacity(len as usize);
+            file.take(len).read_to_end(&mut data)?;
+            Ok(data)
+        }
+    }
+}
+
+/// An attachment's whole contents, from the database or its file.
+#[tauri::command]
+pub async fn read_attachment(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<tauri::ipc::Response> {
+    let pool = state.db();
+    let (_, contents) = locate(&app, &pool, &id).await?;
+    let length = length(&pool, &id, &contents).await?;
+    Ok(tauri::ipc::Response::new(read_range(&pool, &id, &contents, 0, length).await?))
+}
+
+/// A single `bytes=` range as `(start, end)` with `end` exclusive, capped at
+/// `RANGE_CHUNK`. `None` for anything else, which gets the whole file.
+fn parse_range(value: &str, length: u64) -> Option<(u64, u64)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => (length.saturating_sub(suffix.parse().ok()?), length),
+        (start, "") => (start.parse().ok()?, length),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.saturating_add(1)),
+    };
+    let end = end.min(length).min(start.saturating_add(RANGE_CHUNK));
+    (start < end).then_some((start, end))
+}
+
+async fn respond(app: &AppHandle, request: &Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+    let id = request.uri().path().trim_matches('/');
+    let pool = app.state::<AppState>().db();
+    let (attachment, contents) = locate(app, &pool, id).await?;
+    let length = length(&pool, id, &contents).await?;
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_range(value, length));
+    let (start, end) = range.unwrap_or((0, length));
+    let data = read_range(&pool, id, &contents, start, end - start).await?;
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, &attachment.mime)
+        .header(header::ACCEPT_RANGES, "bytes")
+        // An id's contents never change
+        .header(header::CACHE_CONTROL, "private, max-age=31536000, immutable");
+    if range.is_some() {
+        response = response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {start}-{}/{length}", end - 1));
+    }
+    response.body(data).map_err(|e| Error::InvalidInput(e.to_string()))
+}
+
+/// Handler for the `tada-attachment` protocol, registered on the builder.
+pub fn serve(ctx: UriSchemeContext<'_, Wry>, request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let response = respond(&app, &request).await.unwrap_or_else(|e| {
+            let status = match e {
+                Error::NotFound(_) => StatusCode::NOT_FOUND,
+                Error::InvalidInput(_) => StatusCode::BAD_REQUEST,
+                e => {
+                    log::error!("Failed to serve attachment {}: {e}", request.uri());
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            };
+            let mut response = Response::new(Vec::new());
+            *response.status_mut() = status;
+            response
+        });
+        responder.respond(response);
+    });
+}
+
+#[tauri::command]
+pub async fn remove_attachment(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<()> {
+    let stored_path: Option<String> = sqlx::query_scalar("DELETE FROM attachments WHERE id = ? RETURNING stored_path")
+        .bind(&id)
+        .fetch_optional(&state.db())
+        .await?;
+    let Some(stored_path) = stored_path else {
+        return Err(Error::NotFound(format!("Attachment {id}")));
+    };
+    remove_files(&app, &[stored_path]);
+    Ok(())
+}
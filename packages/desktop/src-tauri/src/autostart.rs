@@ -0,0 +1,63 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::Result;
+use crate::settings;
+use crate::AppState;
+
+const SETTINGS_KEY: &str = "autostart";
+
+#[cfg(desktop)]
+fn apply(app: &AppHandle, enabled: bool) -> Result<()> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    let launcher = app.autolaunch();
+    // Only touch the OS registration when it differs, so disabling really removes it
+    if launcher.is_enabled()? != enabled {
+        if enabled {
+            launcher.enable()?;
+        } else {
+            launcher.disable()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(desktop))]
+fn apply(_app: &AppHandle, _enabled: bool) -> Result<()> {
+    Ok(())
+}
+
+/// Re-applies the stored preference on launch, so a reinstall that dropped the
+/// OS registration picks it back up.
+pub fn sync_from_settings(app: &AppHandle) {
+    let pool = app.state::<AppState>().db();
+    match tauri::async_runtime::block_on(settings::get::<bool>(&pool, SETTINGS_KEY)) {
+        Ok(Some(enabled)) => {
+            if let Err(e) = apply(app, enabled) {
+                log::error!("Failed to apply autostart setting: {e}");
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::error!("Failed to read autostart setting: {e}"),
+    }
+}
+
+#[tauri::command]
+pub async fn set_autostart(app: AppHandle, state: State<'_, AppState>, enabled: bool) -> Result<()> {
+    apply(&app, enabled)?;
+    settings::set(&state.db(), SETTINGS_KEY, &enabled).await
+}
+
+#[tauri::command]
+pub fn get_autostart(app: AppHandle) -> Result<bool> {
+    #[cfg(desktop)]
+    {
+        use tauri_plugin_autostart::ManagerExt;
+        Ok(app.autolaunch().is_enabled()?)
+    }
+    #[cfg(not(desktop))]
+    {
+        let _ = app;
+        Ok(false)
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use chrono::Local;
+use libsqlite3_sys as ffi;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, Sqlite, SqlitePool, Transaction};
+use tauri::{AppHandle, Manager, State};
+
+use crate::crypto;
+use crate::dates::format_local;
+use crate::db::{now_ms, row_to_json, table_columns};
+use crate::error::{Error, Result};
+use crate::events;
+use crate::migrations;
+use crate::models::Task;
+use crate::paths;
+use crate::settings;
+use crate::workspaces;
+use crate::AppState;
+
+/// Tables included in a full export, parents before children so inserts satisfy foreign keys.
+pub const EXPORT_TABLES: &[&str] = &[
+    "lists",
+    "tasks",
+    "subtasks",
+    "task_dependencies",
+    "summaries",
+    "settings",
+    "echo_reports",
+    "archived_tasks",
+    "archived_subtasks",
+    "templates",
+    "streaks",
+    // Attachment files stay in the attachments folder; the rows point at them
+    "attachments",
+    "attachment_blobs",
+];
+
+/// Rows hanging off tasks, with the column naming their parent and its table.
+/// A Replace from a backup made before they were exported keeps them for the
+/// parents that come back, rather than letting the cascade from `tasks` drop
+/// them (and the orphan purge their files after).
+const KEPT_IF_MISSING: &[(&str, &str, &str)] = &[
+    ("attachments", "task_id", "tasks"),
+    ("attachment_blobs", "attachment_id", "attachments"),
+];
+
+/// A full snapshot of the user's data. Rows are kept as column maps so the format
+/// follows the schema without a struct per table.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseExport {
+    pub schema_version: i64,
+    pub exported_at: i64,
+    pub tables: BTreeMap<String, Vec<Map<String, Value>>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum ImportMode {
+    /// Wipe the exported tables and load the file as-is.
+    Replace,
+    /// Upsert by primary key, keeping whichever side has the newer `updated_at`.
+    Merge,
+}
+
+fn primary_key(table: &str) -> &'static str {
+    match table {
+        "settings" => "key",
+        "attachment_blobs" => "attachment_id",
+        _ => "id",
+    }
+}
+
+pub async fn snapshot(pool: &SqlitePool) -> Result<DatabaseExport> {
+    let mut tables = BTreeMap::new();
+    for table in EXPORT_TABLES {
+        let rows = sqlx::query(&format!("SELECT * FROM \"{table}\""))
+            .fetch_all(pool)
+            .await?;
+        tables.insert(table.to_string(), rows.iter().map(row_to_json).collect());
+    }
+    Ok(DatabaseExport {
+        schema_version: migrations::latest_version(),
+        exported_at: now_ms(),
+        tables,
+    })
+}
+
+pub(crate) fn bind_value<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    value: &Value,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(i64::from(*b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        Value::Array(items) if items.iter().all(Value::is_u64) => {
+            query.bind(items.iter().filter_map(Value::as_u64).map(|b| b as u8).collect::<Vec<u8>>())
+        }
+        other => query.bind(other.to_string()),
+    }
+}
+
+/// Writes an export into the database inside the caller's transaction,
+/// returning the number of rows written per table.
+pub async fn apply(
+    tx: &mut Transaction<'_, Sqlite>,
+    export: &DatabaseExport,
+    mode: ImportMode,
+) -> Result<BTreeMap<String, u64>> {
+    if export.schema_version > migrations::latest_version() {
+        return Err(Error::InvalidInput(
+            "This backup was created by a newer version of Tada. Please update the app first.".into(),
+        ));
+    }
+
+    let mut kept = Vec::new();
+    if let ImportMode::Replace = mode {
+        for (table, parent_column, parent_table) in KEPT_IF_MISSING {
+            if !export.tables.contains_key(*table) {
+                let rows = sqlx::query(&format!("SELECT * FROM \"{table}\"")).fetch_all(&mut **tx).await?;
+                kept.push((*table, *parent_column, *parent_table, rows.iter().map(row_to_json).collect::<Vec<_>>()));
+            }
+        }
+        for table in EXPORT_TABLES.iter().rev() {
+            sqlx::query(&format!("DELETE FROM \"{table}\"")).execute(&mut **tx).await?;
+        }
+    }
+
+    let mut written = BTreeMap::new();
+    for table in EXPORT_TABLES {
+        let Some(rows) = export.tables.get(*table) else {
+            continue;
+        };
+        written.insert(table.to_string(), write_rows(tx, table, rows, mode).await?);
+    }
+
+    for (table, parent_column, parent_table, rows) in kept {
+        let parents: HashSet<String> = sqlx::query_scalar(&format!("SELECT id FROM \"{parent_table}\""))
+            .fetch_all(&mut **tx)
+            .await?
+            .into_iter()
+            .collect();
+        let (rows, dropped): (Vec<_>, Vec<_>) = rows
+            .into_iter()
+            .partition(|row| row.get(parent_column).and_then(Value::as_str).is_some_and(|id| parents.contains(id)));
+        if !dropped.is_empty() {
+            log::warn!("Dropped {} {table} rows whose parent isn't in the backup", dropped.len());
+        }
+        write_rows(tx, table, &rows, mode).await?;
+    }
+    Ok(written)
+}
+
+/// Inserts (or for a Merge, upserts) one table's rows, returning how many were written.
+async fn write_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    rows: &[Map<String, Value>],
+    mode: ImportMode,
+) -> Result<u64> {
+    let known = table_columns(&mut **tx, table).await?;
+    let key = primary_key(table);
+    let mut count = 0;
+
+    for row in rows {
+        // Only columns this schema has; the names come from the database, never from the file
+        let columns: Vec<&String> = known.iter().filter(|c| row.contains_key(*c)).collect();
+        if !columns.iter().any(|c| c.as_str() == key) {
+            continue;
+        }
+        let column_list = columns.iter().map(|c| format!("\"{c}\"")).collect::<Vec<_>>().join(", ");
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut sql = format!("INSERT INTO \"{table}\" ({column_list}) VALUES ({placeholders})");
+
+        if let ImportMode::Merge = mode {
+            let updates = columns
+                .iter()
+                .filter(|c| c.as_str() != key)
+                .map(|c| format!("\"{c}\" = excluded.\"{c}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            if updates.is_empty() || !known.iter().any(|c| c == "updated_at") {
+                sql.push_str(&format!(" ON CONFLICT(\"{key}\") DO NOTHING"));
+            } else {
+                sql.push_str(&format!(
+                    " ON CONFLICT(\"{key}\") DO UPDATE SET {updates} \
+                     WHERE excluded.updated_at > \"{table}\".updated_at"
+                ));
+            }
+        }
+
+        let mut query = sqlx::query(&sql);
+        for column in &columns {
+            query = bind_value(query, &row[column.as_str()]);
+        }
+        count += query.execute(&mut **tx).await?.rows_affected();
+    }
+    Ok(count)
+}
+
+/// Writes every exported table as JSON, encrypted with `encrypt_with` if given.
+#[tauri::command]
+pub async fn export_all(state: State<'_, AppState>, path: String, encrypt_with: Option<String>) -> Result<()> {
+    let export = snapshot(&state.db()).await?;
+    let json = serde_json::to_vec_pretty(&export)?;
+    match encrypt_with.filter(|passphrase| !passphrase.is_empty()) {
+        Some(passphrase) => std::fs::write(&path, crypto::seal(&passphrase, &json)?)?,
+        None => std::fs::write(&path, json)?,
+    }
+    log::info!("Exported database to {path}");
+    Ok(())
+}
+
+/// Imports a file produced by `export_all`. Any failure rolls the whole import back.
+/// An encrypted file without `passphrase` fails with `PasswordRequired`, for the
+/// UI to ask for one and call again.
+#[tauri::command]
+pub async fn import_all(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    mode: ImportMode,
+    passphrase: Option<String>,
+) -> Result<BTreeMap<String, u64>> {
+    let bytes = std::fs::read(path)?;
+    let bytes = if crypto::is_sealed(&bytes) {
+        let passphrase = passphrase.ok_or(Error::PasswordRequired)?;
+        crypto::open(&passphrase, &bytes)?
+    } else {
+        bytes
+    };
+    let export: DatabaseExport = serde_json::from_slice(&bytes)?;
+    let mut tx = state.db().begin().await?;
+    let written = apply(&mut tx, &export, mode).await?;
+    tx.commit().await?;
+    log::info!("Imported backup ({mode:?}): {written:?}");
+    events::tasks_changed(&app);
+    events::list_updated(&app, None);
+    Ok(written)
+}
+
+/// Writes tasks as CSV, optionally limited to one list. Timestamps are local time.
+#[tauri::command]
+pub async fn export_tasks_csv(state: State<'_, AppState>, path: String, list_id: Option<String>) -> Result<()> {
+    let tasks: Vec<Task> = sqlx::query_as(
+        r#"SELECT * FROM tasks WHERE deleted_at IS NULL AND (?1 IS NULL OR list_id = ?1) ORDER BY list_name, "order""#,
+    )
+    .bind(&list_id)
+    .fetch_all(&state.db())
+    .await?;
+
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record([
+        "id",
+        "title",
+        "completed",
+        "due_date",
+        "list_name",
+        "priority",
+        "tags",
+        "created_at",
+        "completed_at",
+    ])?;
+    for task in tasks {
+        writer.write_record([
+            task.id,
+            task.title,
+            task.completed.to_string(),
+            task.due_date.map(format_local).unwrap_or_default(),
+            task.list_name,
+            task.priority.map(|p| p.to_string()).unwrap_or_default(),
+            task.tags.join(";"),
+            format_local(task.created_at),
+            task.completed_at.map(format_local).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+const BACKUPS_DIR: &str = "backups";
+/// Settings key for how many startup backups to keep.
+const KEEP_KEY: &str = "backupKeep";
+const DEFAULT_KEEP: usize = 7;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    pub created_at: i64,
+    pub size: u64,
+}
+
+/// The active workspace's backups, in `backups/<workspace>/` after its database
+/// file, so one workspace never rotates away or restores another's.
+pub(crate) fn backups_dir(app: &AppHandle) -> Result<PathBuf> {
+    backups_dir_for(app, &workspaces::active_db_path(&paths::config_dir(app)?))
+}
+
+/// The backups of the workspace whose database is `db_path`.
+pub(crate) fn backups_dir_for(app: &AppHandle, db_path: &Path) -> Result<PathBuf> {
+    let folder = db_path.file_stem().ok_or_else(|| Error::Backup(format!("No file name in {}", db_path.display())))?;
+    Ok(paths::data_dir(app)?.join(BACKUPS_DIR).join(folder))
+}
+
+/// Moves backups from the shared folder all workspaces used before each got
+/// its own into the workspace's folder. Only done while there's a single
+/// workspace, as they can't be told apart otherwise; then they stay put,
+/// neither listed nor rotated.
+pub(crate) fn adopt_shared_backups(app: &AppHandle) -> Result<()> {
+    let shared = paths::data_dir(app)?.join(BACKUPS_DIR);
+    let backups = list(&shared)?;
+    if backups.is_empty() || workspaces::has_several(&paths::config_dir(app)?) {
+        return Ok(());
+    }
+    let dir = backups_dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    for backup in &backups {
+        std::fs::rename(&backup.path, dir.join(&backup.file_name))?;
+    }
+    log::info!("Moved {} backups into {}", backups.len(), dir.display());
+    Ok(())
+}
+
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with("tada-") && name.ends_with(".db")
+}
+
+/// Message for the last error on a connection.
+fn sqlite_error(db: *mut ffi::sqlite3) -> Error {
+    // SAFETY: `db` is a live connection; the message is copied before anything else touches it
+    let message = unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(db)) };
+    Error::Backup(message.to_string_lossy().into_owned())
+}
+
+/// Copies the `main` database of `src` into `dest` with SQLite's online backup API,
+/// which yields a consistent copy even while other connections write.
+fn copy_pages(dest: *mut ffi::sqlite3, src: *mut ffi::sqlite3) -> Result<()> {
+    // SAFETY: both handles are open connections that stay valid for this call, and
+    // the backup object is finished before returning
+    unsafe {
+        let backup = ffi::sqlite3_backup_init(dest, c"main".as_ptr(), src, c"main".as_ptr());
+        if backup.is_null() {
+            return Err(sqlite_error(dest));
+        }
+        loop {
+            match ffi::sqlite3_backup_step(backup, -1) {
+                ffi::SQLITE_DONE => break,
+                ffi::SQLITE_OK => {}
+                // Another connection holds a lock; give it a moment
+                ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED => {
+                    ffi::sqlite3_sleep(50);
+                }
+                _ => break,
+            }
+        }
+        if ffi::sqlite3_backup_finish(backup) != ffi::SQLITE_OK {
+            return Err(sqlite_error(dest));
+        }
+    }
+    Ok(())
+}
+
+/// Runs `f` with a raw handle to a separate database file, closing it afterwards.
+fn with_file_db<T>(path: &Path, flags: i32, f: impl FnOnce(*mut ffi::sqlite3) -> Result<T>) -> Result<T> {
+    let c_path = CString::new(path.to_string_lossy().as_bytes())
+        .map_err(|_| Error::InvalidInput("Backup path contains a NUL byte".into()))?;
+    let mut db = ptr::null_mut();
+    // SAFETY: `db` receives a handle that's closed below on every path
+    let rc = unsafe { ffi::sqlite3_open_v2(c_path.as_ptr(), &mut db, flags, ptr::null()) };
+    let result = if rc == ffi::SQLITE_OK { f(db) } else { Err(sqlite_error(db)) };
+    // SAFETY: closing a handle from sqlite3_open_v2, even a failed one, is allowed
+    unsafe { ffi::sqlite3_close(db) };
+    result
+}
+
+/// Writes a backup of the live database to `backups/<workspace>/tada-YYYYMMDD-HHMMSS.db`.
+pub async fn create_backup(app: &AppHandle, pool: &SqlitePool) -> Result<PathBuf> {
+    let dir = backups_dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("tada-{}.db", Local::now().format("%Y%m%d-%H%M%S")));
+
+    let mut conn = pool.acquire().await?;
+    let mut handle = conn.lock_handle().await?;
+    let live = handle.as_raw_handle().as_ptr();
+    with_file_db(&path, ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE, |backup| {
+        copy_pages(backup, live)
+    })?;
+    Ok(path)
+}
+
+/// Backups in `dir`, newest first.
+pub(crate) fn list(dir: &Path) -> Result<Vec<BackupInfo>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok().filter(|n| is_backup_name(n))?;
+            let metadata = entry.metadata().ok()?;
+            let created_at = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or_default();
+            Some(BackupInfo {
+                path: entry.path().to_string_lossy().into_owned(),
+                file_name,
+                created_at,
+                size: metadata.len(),
+            })
+        })
+        .collect();
+    // The timestamped names sort chronologically; newest first
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(backups)
+}
+
+/// Deletes all but the newest `keep` backups.
+fn rotate(dir: &Path, keep: usize) -> Result<()> {
+    for old in list(dir)?.into_iter().skip(keep) {
+        std::fs::remove_file(&old.path)?;
+    }
+    Ok(())
+}
+
+/// Backs the database up once per launch and prunes old copies.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = handle.state::<AppState>();
+        let _permit = state.job_permit().await;
+        let pool = state.db();
+        let result = async {
+            let keep = settings::get::<usize>(&pool, KEEP_KEY).await?.unwrap_or(DEFAULT_KEEP).max(1);
+            adopt_shared_backups(&handle)?;
+            let path = create_backup(&handle, &pool).await?;
+            rotate(&backups_dir(&handle)?, keep)?;
+            Ok::<_, Error>(path)
+        }
+        .await;
+        match result {
+            Ok(path) => log::info!("Backed up database to {}", path.display()),
+            Err(e) => log::error!("Startup backup failed: {e}"),
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>> {
+    list(&backups_dir(&app)?)
+}
+
+/// Replaces the live database with one of the startup backups.
+///
+/// The backup API writes the pages straight into the open database, so the
+/// SQL plugin's connections see the restored data without being reopened.
+/// The current state is backed up first, so a restore can itself be undone.
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, state: State<'_, AppState>, path: String) -> Result<()> {
+    let dir = backups_dir(&app)?.canonicalize()?;
+    let source = PathBuf::from(&path).canonicalize()?;
+    let is_backup = source.parent() == Some(dir.as_path())
+        && source.file_name().and_then(|n| n.to_str()).is_some_and(is_backup_name);
+    if !is_backup {
+        return Err(Error::InvalidInput("Only backups from the backups folder can be restored".into()));
+    }
+    // A backup a newer build made would be copied in only to fail migrating
+    let mut backup = SqliteConnectOptions::new().filename(&source).read_only(true).connect().await?;
+    let checked = migrations::ensure_supported(&mut backup).await;
+    backup.close().await?;
+    checked?;
+
+    let safety = create_backup(&app, &state.db()).await?;
+    log::info!("Restoring {path}; previous state saved to {}", safety.display());
+    {
+        let mut conn = state.db().acquire().await?;
+        let mut handle = conn.lock_handle().await?;
+        let live = handle.as_raw_handle().as_ptr();
+        with_file_db(&source, ffi::SQLITE_OPEN_READONLY, |backup| copy_pages(live, backup))?;
+    }
+
+    // An older backup may predate some migrations
+    migrations::run(&state.db()).await?;
+    migrations::sync_user_version(&state.db()).await?;
+    events::tasks_changed(&app);
+    events::list_updated(&app, None);
+    Ok(())
+}
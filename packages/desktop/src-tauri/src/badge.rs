@@ -0,0 +1,60 @@
+//! Dock badge with the number of overdue tasks. macOS only; a no-op elsewhere
+//! until the Windows taskbar overlay is wired up.
+
+use tauri::AppHandle;
+
+#[cfg(target_os = "macos")]
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+#[cfg(target_os = "macos")]
+pub fn init(app: &AppHandle) {
+    use tauri::Manager;
+
+    crate::events::on_tasks_changed(app, |handle| {
+        tauri::async_runtime::spawn(async move {
+            let state = handle.state::<crate::AppState>();
+            let _permit = state.job_permit().await;
+            if let Err(e) = refresh(&handle).await {
+                log::error!("Failed to update dock badge: {e}");
+            }
+        });
+    });
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = handle.state::<crate::AppState>();
+            let _permit = state.job_permit().await;
+            if let Err(e) = refresh(&handle).await {
+                log::error!("Failed to update dock badge: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn init(_app: &AppHandle) {}
+
+#[cfg(target_os = "macos")]
+pub async fn refresh(app: &AppHandle) -> crate::error::Result<()> {
+    use tauri::Manager;
+
+    let pool = app.state::<crate::AppState>().db();
+    let overdue: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM tasks
+        WHERE completed = 0 AND list_name != 'Trash' AND deleted_at IS NULL AND due_date IS NOT NULL AND due_date < ?
+        "#,
+    )
+    .bind(crate::db::now_ms())
+    .fetch_one(&pool)
+    .await?;
+
+    if let Some(window) = app.get_webview_window("main") {
+        // An empty label removes the badge entirely
+        window.set_badge_label((overdue > 0).then(|| overdue.to_string()))?;
+    }
+    Ok(())
+}
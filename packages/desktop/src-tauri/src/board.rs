@@ -0,0 +1,139 @@
+//! A kanban board of a list's tasks: Backlog, In Progress and Done.
+//!
+//! Done is whether the task is completed, so completing a task anywhere moves
+//! it there and dragging it into Done completes it (a recurring task creates
+//! its next occurrence as usual). `status` only tells Backlog and In Progress
+//! apart; tasks that never had one, like everything from before the board,
+//! are in Backlog. Cards are ordered by `board_order`, apart from the list's
+//! manual `order`; cards never placed on the board come after the rest, oldest first.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqliteConnection, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::commands::{announce_completed, complete_unblocked_in, fetch_task, reopen_in};
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::events;
+use crate::models::Task;
+use crate::AppState;
+
+/// The column a task is in, derived from `completed` and `status`.
+const EFFECTIVE_STATUS_SQL: &str =
+    "CASE WHEN completed = 1 THEN 'done' WHEN status = 'in_progress' THEN 'in_progress' ELSE 'backlog' END";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardStatus {
+    Backlog,
+    InProgress,
+    Done,
+}
+
+impl BoardStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Backlog => "backlog",
+            Self::InProgress => "in_progress",
+            Self::Done => "done",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardColumns {
+    pub backlog: Vec<Task>,
+    pub in_progress: Vec<Task>,
+    pub done: Vec<Task>,
+}
+
+async fn column(conn: &mut SqliteConnection, list_id: &str, status: BoardStatus) -> Result<Vec<Task>> {
+    Ok(sqlx::query_as(&format!(
+        r#"
+        SELECT * FROM tasks
+        WHERE list_id = ? AND deleted_at IS NULL AND {EFFECTIVE_STATUS_SQL} = ?
+        ORDER BY board_order IS NULL, board_order, created_at, id
+        "#
+    ))
+    .bind(list_id)
+    .bind(status.as_str())
+    .fetch_all(conn)
+    .await?)
+}
+
+/// A list's tasks by column, each in board order.
+#[tauri::command]
+pub async fn board(state: State<'_, AppState>, list_id: String) -> Result<BoardColumns> {
+    let mut conn = state.db().acquire().await?;
+    Ok(BoardColumns {
+        backlog: column(&mut conn, &list_id, BoardStatus::Backlog).await?,
+        in_progress: column(&mut conn, &list_id, BoardStatus::InProgress).await?,
+        done: column(&mut conn, &list_id, BoardStatus::Done).await?,
+    })
+}
+
+/// Numbers a column's cards from 0, touching only those whose position changed.
+async fn write_board_order(tx: &mut Transaction<'_, Sqlite>, ids: &[String]) -> Result<()> {
+    let now = now_ms();
+    for (index, id) in ids.iter().enumerate() {
+        sqlx::query("UPDATE tasks SET board_order = ?1, updated_at = ?2 WHERE id = ?3 AND board_order IS NOT ?1")
+            .bind(index as i64)
+            .bind(now)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Moves a card to `new_index` in a column of its list, completing or
+/// reopening the task when it enters or leaves Done, and returns the board.
+#[tauri::command]
+pub async fn move_task_status(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    status: BoardStatus,
+    new_index: usize,
+) -> Result<BoardColumns> {
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let task = fetch_task(&mut tx, &task_id).await?;
+    let list_id = task.list_id.clone().unwrap_or_default();
+
+    let mut completed = None;
+    if status == BoardStatus::Done && !task.completed {
+        completed = Some(complete_unblocked_in(&mut tx, &task_id, false).await?);
+    } else if status != BoardStatus::Done && task.completed {
+        reopen_in(&mut tx, &task).await?;
+    }
+    sqlx::query("UPDATE tasks SET status = ?, updated_at = ? WHERE id = ?")
+        .bind(status.as_str())
+        .bind(now_ms())
+        .bind(&task_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut ids: Vec<String> = column(&mut tx, &list_id, status)
+        .await?
+        .into_iter()
+        .map(|t| t.id)
+        .filter(|id| *id != task_id)
+        .collect();
+    ids.insert(new_index.min(ids.len()), task_id.clone());
+    write_board_order(&mut tx, &ids).await?;
+
+    let columns = BoardColumns {
+        backlog: column(&mut tx, &list_id, BoardStatus::Backlog).await?,
+        in_progress: column(&mut tx, &list_id, BoardStatus::InProgress).await?,
+        done: column(&mut tx, &list_id, BoardStatus::Done).await?,
+    };
+    tx.commit().await?;
+    log::debug!("Moved task {task_id} to {} at {new_index}", status.as_str());
+    if let Some(completed) = &completed {
+        announce_completed(&app, &pool, completed).await;
+    }
+    events::tasks_changed(&app);
+    Ok(columns)
+}
@@ -0,0 +1,207 @@
+//! Quick capture parsing: "Call plumber #home !high @Errands tomorrow 2pm".
+//!
+//! `#tag`, `!priority` and `@list` are whole words and may appear anywhere, and
+//! the date is found in what's left, so the order of the parts doesn't matter.
+//! A backslash keeps a word literal: `\#1` stays in the title as `#1`.
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::error::Result;
+use crate::nlp_date::{self, utf16_offset};
+use crate::AppState;
+
+const MARKERS: [char; 3] = ['#', '!', '@'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureKind {
+    Tag,
+    Priority,
+    List,
+    Date,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureSpan {
+    pub kind: CaptureKind,
+    /// `[start, end)` in UTF-16 code units, like `parse_due_date`.
+    pub span: [usize; 2],
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureList {
+    /// The existing list's name when one matched, otherwise as typed.
+    pub name: String,
+    pub id: Option<String>,
+    /// No list has this name yet; adding the task would create it.
+    pub is_new: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedCapture {
+    /// The input without the matched parts and escape backslashes.
+    pub title: String,
+    pub tags: Vec<String>,
+    pub priority: Option<i64>,
+    pub list: Option<CaptureList>,
+    pub due_date: Option<i64>,
+    pub has_time: bool,
+    pub spans: Vec<CaptureSpan>,
+}
+
+/// What the text alone says, before the list name is looked up.
+struct Scan {
+    title: String,
+    tags: Vec<String>,
+    priority: Option<i64>,
+    list: Option<String>,
+    due_date: Option<i64>,
+    has_time: bool,
+    spans: Vec<CaptureSpan>,
+}
+
+fn priority(word: &str) -> Option<i64> {
+    Some(match word.to_lowercase().as_str() {
+        "high" | "h" | "1" => 1,
+        "medium" | "med" | "m" | "2" => 2,
+        "low" | "l" | "3" => 3,
+        _ => return None,
+    })
+}
+
+/// Byte ranges of the whitespace-separated words of `input`.
+fn words(input: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in input.char_indices().chain(std::iter::once((input.len(), ' '))) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                words.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn scan(input: &str, now: i64) -> Scan {
+    let mut tags: Vec<String> = Vec::new();
+    let mut priority_found = None;
+    let mut list = None;
+    // Byte ranges cut from the title, matched parts and escape backslashes alike
+    let mut removed = Vec::new();
+    let mut matched = Vec::new();
+
+    for (start, end) in words(input) {
+        let word = &input[start..end];
+        let mut chars = word.chars();
+        let (Some(first), Some(second)) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        if first == '\\' && MARKERS.contains(&second) {
+            removed.push((start, start + 1));
+            continue;
+        }
+        if !MARKERS.contains(&first) {
+            continue;
+        }
+        // Trailing punctuation belongs to the sentence, not the tag or list name
+        let value = word[1..].trim_end_matches([',', '.', ';', '!', '?']);
+        if value.is_empty() || value.starts_with(MARKERS) {
+            continue;
+        }
+        let range = (start, start + 1 + value.len());
+        let kind = match first {
+            '#' => {
+                if !tags.iter().any(|t| t.eq_ignore_ascii_case(value)) {
+                    tags.push(value.to_string());
+                }
+                CaptureKind::Tag
+            }
+            '!' if priority_found.is_none() => match priority(value) {
+                Some(p) => {
+                    priority_found = Some(p);
+                    CaptureKind::Priority
+                }
+                None => continue,
+            },
+            '@' if list.is_none() => {
+                list = Some(value.to_string());
+                CaptureKind::List
+            }
+            _ => continue,
+        };
+        removed.push(range);
+        matched.push((kind, range));
+    }
+
+    // Blank out the markers so a tag like #today can't also be read as a date
+    let mut masked = input.to_string();
+    for &(_, (start, end)) in &matched {
+        masked.replace_range(start..end, &" ".repeat(end - start));
+    }
+    let date = nlp_date::find(&masked, now);
+    if let Some(date) = &date {
+        removed.push((date.start, date.end));
+        matched.push((CaptureKind::Date, (date.start, date.end)));
+    }
+
+    let title: String = input
+        .char_indices()
+        .filter(|(i, _)| !removed.iter().any(|&(start, end)| (start..end).contains(i)))
+        .map(|(_, c)| c)
+        .collect();
+    matched.sort_by_key(|&(_, (start, _))| start);
+    Scan {
+        title: title.split_whitespace().collect::<Vec<_>>().join(" "),
+        tags,
+        priority: priority_found,
+        list,
+        due_date: date.as_ref().map(|d| d.due_date),
+        has_time: date.is_some_and(|d| d.has_time),
+        spans: matched
+            .into_iter()
+            .map(|(kind, (start, end))| CaptureSpan {
+                kind,
+                span: [utf16_offset(input, start), utf16_offset(input, end)],
+            })
+            .collect(),
+    }
+}
+
+/// Splits quick-add text into a title and task fields, matching `@list` against existing lists.
+#[tauri::command]
+pub async fn parse_quick_capture(state: State<'_, AppState>, input: String, now_ms: i64) -> Result<ParsedCapture> {
+    let scan = scan(&input, now_ms);
+    let list = match scan.list {
+        Some(typed) => {
+            let existing: Option<(String, String)> =
+                sqlx::query_as(
+                    "SELECT id, name FROM lists WHERE name = ? COLLATE NOCASE AND name != 'Trash' AND deleted_at IS NULL",
+                )
+                    .bind(&typed)
+                    .fetch_optional(&state.db())
+                    .await?;
+            Some(match existing {
+                Some((id, name)) => CaptureList { name, id: Some(id), is_new: false },
+                None => CaptureList { name: typed, id: None, is_new: true },
+            })
+        }
+        None => None,
+    };
+    Ok(ParsedCapture {
+        title: scan.title,
+        tags: scan.tags,
+        priority: scan.priority,
+        list,
+        due_date: scan.due_date,
+        has_time: scan.has_time,
+        spans: scan.spans,
+    })
+}
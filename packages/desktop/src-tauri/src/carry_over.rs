@@ -0,0 +1,196 @@
+//! Bullet-journal migration: unfinished tasks due before today move to today.
+//!
+//! `carry_over_overdue` does it on request. With `carryOverOverdue` on, it also
+//! runs by itself at launch and after each local midnight; it's off by default.
+//! A moved task keeps its time of day (due yesterday at 15:00 becomes today at
+//! 15:00) unless `carryOverKeepTime` is off, in which case it takes the target's
+//! exact time. A deferred task keeps the same lead time before its due date.
+//!
+//! A recurring task moves once however many occurrences it missed. When its
+//! rule has an occurrence on the target day it lands on that, with the COUNT
+//! used up by the skipped ones, so the series stays on schedule and completing
+//! it spawns the next occurrence rather than one for today again.
+
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::group_category;
+use crate::dates::{local_date, local_day_bounds, resolve_local, start_of_local_day};
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::models::Task;
+use crate::recurrence::RRule;
+use crate::{events, settings, AppState};
+
+const ENABLED_KEY: &str = "carryOverOverdue";
+const KEEP_TIME_KEY: &str = "carryOverKeepTime";
+/// Slack after midnight, so the run can't land a hair before it.
+const AFTER_MIDNIGHT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarryOver {
+    /// How many tasks moved, or would move in a dry run.
+    pub count: usize,
+    pub task_ids: Vec<String>,
+    pub dry_run: bool,
+}
+
+/// Where one task goes.
+struct Move {
+    id: String,
+    due_date: i64,
+    start_date: Option<i64>,
+    recurrence_rule: Option<String>,
+}
+
+/// `to_ms`'s local date at the local time of day of `due`.
+fn same_time_on(due: i64, to_ms: i64) -> Option<i64> {
+    let time = Local.timestamp_millis_opt(due).single()?.time();
+    Some(resolve_local(local_date(to_ms).and_time(time))?.timestamp_millis())
+}
+
+/// The recurring task's occurrence on the target day `[day_start, day_end)`,
+/// with its rule advanced past the skipped ones; `None` if the rule has none that day.
+fn occurrence_on(rule: &RRule, due: i64, day_start: i64, day_end: i64) -> Option<(i64, RRule)> {
+    let (mut rule, mut current) = (rule.clone(), due);
+    while let Some(next) = rule.next_after(current).filter(|next| *next < day_end) {
+        rule = rule.advanced();
+        current = next;
+        if current >= day_start {
+            return Some((current, rule));
+        }
+    }
+    None
+}
+
+fn plan(task: &Task, to_ms: i64, keep_time: bool) -> Option<Move> {
+    let due = task.due_date?;
+    let (day_start, day_end) = local_day_bounds(to_ms);
+    let on_schedule = task
+        .recurrence_rule
+        .as_deref()
+        .and_then(|raw| raw.parse::<RRule>().ok())
+        .and_then(|rule| occurrence_on(&rule, due, day_start, day_end));
+    let (due_date, recurrence_rule) = match on_schedule {
+        Some((occurrence, rule)) => (occurrence, Some(rule.to_string())),
+        None if keep_time => (same_time_on(due, to_ms).unwrap_or(to_ms), task.recurrence_rule.clone()),
+        None => (to_ms, task.recurrence_rule.clone()),
+    };
+    Some(Move {
+        id: task.id.clone(),
+        due_date,
+        start_date: task.start_date.map(|start| due_date - (due - start)),
+        recurrence_rule,
+    })
+}
+
+/// Unfinished tasks due before `to_ms`'s local day, and where each one goes.
+async fn overdue(pool: &SqlitePool, to_ms: i64, keep_time: bool) -> Result<Vec<Move>> {
+    let (day_start, _) = local_day_bounds(to_ms);
+    let tasks: Vec<Task> = sqlx::query_as(
+        r#"
+        SELECT * FROM tasks
+        WHERE completed = 0 AND deleted_at IS NULL AND list_name != 'Trash'
+          AND due_date IS NOT NULL AND due_date < ?
+        ORDER BY due_date, id
+        "#,
+    )
+    .bind(day_start)
+    .fetch_all(pool)
+    .await?;
+    Ok(tasks.iter().filter_map(|task| plan(task, to_ms, keep_time)).collect())
+}
+
+async fn apply(tx: &mut Transaction<'_, Sqlite>, moves: &[Move]) -> Result<()> {
+    let now = now_ms();
+    for planned in moves {
+        sqlx::query(
+            r#"
+            UPDATE tasks
+            SET due_date = ?, start_date = ?, recurrence_rule = ?, group_category = ?, updated_at = ?
+            WHERE id = ? AND completed = 0
+            "#,
+        )
+        .bind(planned.due_date)
+        .bind(planned.start_date)
+        .bind(&planned.recurrence_rule)
+        .bind(group_category(false, Some(planned.due_date), planned.start_date))
+        .bind(now)
+        .bind(&planned.id)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn keep_time_default(pool: &SqlitePool) -> Result<bool> {
+    Ok(settings::get::<bool>(pool, KEEP_TIME_KEY).await?.unwrap_or(true))
+}
+
+async fn carry_over(app: &AppHandle, pool: &SqlitePool, to_ms: i64, dry_run: bool, keep_time: bool) -> Result<CarryOver> {
+    let moves = overdue(pool, to_ms, keep_time).await?;
+    if !dry_run && !moves.is_empty() {
+        let mut tx = pool.begin().await?;
+        apply(&mut tx, &moves).await?;
+        tx.commit().await?;
+        log::info!("Carried {} overdue task(s) over to {}", moves.len(), local_date(to_ms));
+        events::tasks_changed(app);
+    }
+    Ok(CarryOver {
+        count: moves.len(),
+        task_ids: moves.into_iter().map(|planned| planned.id).collect(),
+        dry_run,
+    })
+}
+
+/// Runs the carry-over at launch and after each local midnight, while `carryOverOverdue` is on.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let state = handle.state::<AppState>();
+                let _permit = state.job_permit().await;
+                let pool = state.db();
+                let run = async {
+                    if settings::get::<bool>(&pool, ENABLED_KEY).await? != Some(true) {
+                        return Ok(());
+                    }
+                    let today = start_of_local_day(local_date(now_ms()));
+                    carry_over(&handle, &pool, today, false, keep_time_default(&pool).await?).await.map(|_| ())
+                };
+                if let Err(e) = run.await {
+                    log::error!("Failed to carry over overdue tasks: {e}");
+                }
+            }
+            let now = now_ms();
+            let (_, midnight) = local_day_bounds(now);
+            let wait = Duration::from_millis(u64::try_from(midnight - now).unwrap_or_default());
+            tokio::time::sleep(wait + AFTER_MIDNIGHT).await;
+        }
+    });
+}
+
+/// Moves unfinished tasks due before `to_ms`'s local day to that day. With
+/// `dry_run` nothing changes and the ids that would move come back; `keep_time`
+/// overrides the `carryOverKeepTime` setting.
+#[tauri::command]
+pub async fn carry_over_overdue(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    to_ms: i64,
+    dry_run: Option<bool>,
+    keep_time: Option<bool>,
+) -> Result<CarryOver> {
+    let pool = state.db();
+    let keep_time = match keep_time {
+        Some(keep_time) => keep_time,
+        None => keep_time_default(&pool).await?,
+    };
+    carry_over(&app, &pool, to_ms, dry_run.unwrap_or(false), keep_time).await
+}
@@ -0,0 +1,175 @@
+//! Headless `tada add` / `tada list`, run against the database without opening a window.
+//!
+//! Anything else on the command line (deep links, autostart flags) falls through to the GUI.
+//! A running app picks up CLI changes on its next reload, since no events reach it from here.
+//! The outbound webhook still hears about added tasks: `add` sends it directly before exiting.
+
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use sqlx::SqlitePool;
+
+use crate::commands::{self, TaskInput, INBOX_LIST_ID};
+use crate::dates::{format_local, local_day_bounds, start_of_local_day};
+use crate::db::{self, now_ms};
+use crate::error::{Error, Result};
+use crate::migrations;
+use crate::models::Task;
+use crate::nlp_date;
+use crate::webhook::{self, Event};
+
+#[derive(Parser)]
+#[command(name = "tada", version, about = "Tada task manager")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add a task
+    Add {
+        title: String,
+        /// List name; defaults to the inbox
+        #[arg(long)]
+        list: Option<String>,
+        /// "tomorrow", "next friday 5pm", "in 3 days", or YYYY-MM-DD
+        #[arg(long)]
+        due: Option<String>,
+        /// 1 (high) to 3 (low)
+        #[arg(long)]
+        priority: Option<i64>,
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Print open tasks
+    List {
+        /// Only tasks due today
+        #[arg(long)]
+        today: bool,
+        /// Only tasks in this list
+        #[arg(long)]
+        list: Option<String>,
+    },
+}
+
+const SUBCOMMANDS: &[&str] = &["add", "list", "help", "--help", "-h", "--version", "-V"];
+
+/// Runs a CLI subcommand if the arguments name one, returning the exit code.
+/// `None` means the GUI should start as usual.
+pub fn run() -> Option<i32> {
+    let first = std::env::args().nth(1)?;
+    if !SUBCOMMANDS.contains(&first.as_str()) {
+        return None;
+    }
+    #[cfg(windows)]
+    attach_console();
+
+    let cli = Cli::parse();
+    match tauri::async_runtime::block_on(execute(cli.command)) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("tada: {e}");
+            Some(1)
+        }
+    }
+}
+
+/// Release builds use the GUI subsystem on Windows, which has no console to print to.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // Fails harmlessly when there is no parent console or one is already attached
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+async fn open_db() -> Result<SqlitePool> {
+    let path = db::default_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let pool = db::open(&path).await?;
+    migrations::ensure_supported(&mut *pool.acquire().await?).await?;
+    // Same migrations the SQL plugin applies, in case the GUI has never run
+    migrations::run(&pool).await?;
+    Ok(pool)
+}
+
+async fn list_id_by_name(pool: &SqlitePool, name: &str) -> Result<String> {
+    sqlx::query_scalar("SELECT id FROM lists WHERE name = ? COLLATE NOCASE AND name != 'Trash' AND deleted_at IS NULL")
+        .bind(name)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("List '{name}'")))
+}
+
+/// The quick-add parser first, then a plain ISO date.
+fn parse_due(input: &str) -> Result<i64> {
+    if let Some(parsed) = nlp_date::parse(input, now_ms()) {
+        return Ok(parsed.due_date);
+    }
+    NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d")
+        .map(start_of_local_day)
+        .map_err(|_| Error::InvalidInput(format!("Couldn't understand the due date '{input}'")))
+}
+
+async fn execute(command: Command) -> Result<()> {
+    let pool = open_db().await?;
+    match command {
+        Command::Add { title, list, due, priority, tags } => {
+            let list_id = match &list {
+                Some(name) => list_id_by_name(&pool, name).await?,
+                None => INBOX_LIST_ID.to_string(),
+            };
+            let input = TaskInput {
+                title,
+                content: None,
+                // Left out, the list's defaults apply as they do in the app
+                due_date: due.as_deref().map(parse_due).transpose()?.map(Some),
+                list_id: Some(list_id),
+                tags,
+                priority: priority.map(Some),
+                recurrence_rule: None,
+                color: None,
+                start_date: None,
+            };
+            let mut tx = pool.begin().await?;
+            let task = commands::insert_task(&mut tx, &input).await?;
+            tx.commit().await?;
+            println!("Added \"{}\" to {}", task.title, task.list_name);
+            if let Err(e) = webhook::send(&pool, Event::Created, &task.id).await {
+                eprintln!("tada: couldn't send the webhook: {e}");
+            }
+        }
+        Command::List { today, list } => {
+            let list_id = match &list {
+                Some(name) => Some(list_id_by_name(&pool, name).await?),
+                None => None,
+            };
+            let (start, end) = local_day_bounds(now_ms());
+            let tasks: Vec<Task> = sqlx::query_as(
+                r#"
+                SELECT * FROM tasks
+                WHERE completed = 0 AND list_name != 'Trash' AND deleted_at IS NULL
+                  AND (? IS NULL OR list_id = ?)
+                  AND (? = 0 OR (due_date >= ? AND due_date < ?))
+                ORDER BY due_date IS NULL, due_date, "order"
+                "#,
+            )
+            .bind(&list_id)
+            .bind(&list_id)
+            .bind(today)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&pool)
+            .await?;
+            for task in &tasks {
+                let due = task.due_date.map(|d| format!("  (due {})", format_local(d))).unwrap_or_default();
+                println!("[ ] {}  [{}]{due}", task.title, task.list_name);
+            }
+        }
+    }
+    pool.close().await;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! What the window close button does: hide to the tray, quit, or ask the frontend.
+
+use std::sync::atomic::Ordering;
+
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+use crate::list_windows;
+use crate::settings;
+use crate::AppState;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum CloseBehavior {
+    #[default]
+    Tray,
+    Quit,
+    Ask,
+}
+
+/// Exits for real, past the close handler.
+pub(crate) fn quit(app: &AppHandle) {
+    app.state::<AppState>().is_quitting.store(true, Ordering::Relaxed);
+    list_windows::close_all(app);
+    app.exit(0);
+}
+
+/// Called after the close has been prevented. The setting is read on every close
+/// so changing it in the UI applies without a restart.
+pub(crate) fn on_close_requested(window: &Window) {
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = window.app_handle();
+        let state = app.state::<AppState>();
+        let behavior = match settings::get::<CloseBehavior>(&state.db(), "closeButtonBehavior").await {
+            Ok(behavior) => behavior.unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Failed to read closeButtonBehavior, hiding to tray: {e}");
+                CloseBehavior::Tray
+            }
+        };
+        match behavior {
+            CloseBehavior::Tray => {
+                let _ = window.hide();
+            }
+            CloseBehavior::Quit => quit(app),
+            CloseBehavior::Ask => {
+                let _ = window.emit("confirm-close", ());
+            }
+        }
+    });
+}
+
+/// The frontend's answer to `confirm-close` when the user chose to quit.
+#[tauri::command]
+pub fn confirm_quit(app: AppHandle) {
+    quit(&app);
+}
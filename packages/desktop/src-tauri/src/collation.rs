@@ -0,0 +1,56 @@
+//! Natural ordering for titles, so "Item 2" sorts before "Item 10".
+//!
+//! Runs of ASCII digits compare by value and everything else compares by
+//! lowercased characters, which covers non-Latin scripts too. Registered on
+//! every pool connection as the `NATURAL` collation; Rust-side sorts call
+//! `compare` directly so both agree.
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Name to use in `COLLATE` clauses.
+pub const NATURAL: &str = "NATURAL";
+
+fn take_digits(chars: &mut Peekable<Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+    digits
+}
+
+/// Case-insensitive, number-aware comparison. "7", "07" and "007" are the same
+/// number; only when the titles are otherwise equal does the one with fewer
+/// leading zeros go first. Strings equal in every way but case fall back to a
+/// plain comparison, so the order is total as SQLite requires.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let (mut a_chars, mut b_chars) = (a.chars().peekable(), b.chars().peekable());
+    let mut zeros = Ordering::Equal;
+    loop {
+        let (x, y) = match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => break,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => (*x, *y),
+        };
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let (x, y) = (take_digits(&mut a_chars), take_digits(&mut b_chars));
+            let (x_value, y_value) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+            // Without leading zeros, a longer run is a bigger number
+            let by_value = x_value.len().cmp(&y_value.len()).then_with(|| x_value.cmp(y_value));
+            if by_value != Ordering::Equal {
+                return by_value;
+            }
+            zeros = zeros.then(x.len().cmp(&y.len()));
+        } else {
+            let by_char = x.to_lowercase().cmp(y.to_lowercase());
+            if by_char != Ordering::Equal {
+                return by_char;
+            }
+            a_chars.next();
+            b_chars.next();
+        }
+    }
+    zeros.then_with(|| a.cmp(b))
+}
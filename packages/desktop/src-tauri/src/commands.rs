@@ -0,0 +1,1001 @@
+use std::collections::HashSet;
+
+use chrono::{Days, Local};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{Sqlite, SqliteConnection, SqlitePool, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::dates::start_of_local_day;
+use crate::db::{new_id, now_ms};
+use crate::dependencies;
+use crate::events;
+use crate::grouping;
+use crate::error::{Error, Result};
+use crate::models::{Subtask, Task};
+use crate::recurrence;
+use crate::settings;
+use crate::streaks;
+use crate::subtasks::update_percentage;
+use crate::undo::{self, UndoEntry};
+use crate::webhook::{self, Event};
+use crate::AppState;
+
+/// List new tasks land in when the input doesn't name one.
+pub const INBOX_LIST_ID: &str = "inbox-default";
+const COPY_SUFFIX_KEY: &str = "duplicateTitleSuffix";
+const DEFAULT_COPY_SUFFIX: &str = " (copy)";
+/// Longest estimate `set_estimate` accepts: a week.
+const MAX_ESTIMATE_MINUTES: u32 = 7 * 24 * 60;
+
+/// Keeps an explicit `null` apart from a missing field: `Some(None)` vs `None`.
+fn explicit<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Editable task fields accepted by `create_task` and `update_task`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskInput {
+    pub title: String,
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Left out, `create_task` applies the list's due offset; `null` means no due date.
+    #[serde(default, deserialize_with = "explicit")]
+    pub due_date: Option<Option<i64>>,
+    #[serde(default)]
+    pub list_id: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Left out, `create_task` applies the list's default priority; `null` means none.
+    #[serde(default, deserialize_with = "explicit")]
+    pub priority: Option<Option<i64>>,
+    /// RRULE string, e.g. `FREQ=WEEKLY;BYDAY=MO`.
+    #[serde(default)]
+    pub recurrence_rule: Option<String>,
+    /// Accent color as `#rgb` or `#rrggbb`.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Defers the task: it stays out of `hideFuture` queries until then.
+    #[serde(default)]
+    pub start_date: Option<i64>,
+}
+
+impl TaskInput {
+    fn validate(&self) -> Result<()> {
+        if self.title.trim().is_empty() {
+            return Err(Error::InvalidInput("Task title cannot be empty".into()));
+        }
+        if let Some(color) = &self.color {
+            let valid = color
+                .strip_prefix('#')
+                .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()));
+            if !valid {
+                return Err(Error::InvalidInput(format!("Invalid color '{color}'")));
+            }
+        }
+        if let (Some(start), Some(due)) = (self.start_date, self.due_date.flatten()) {
+            if start > due {
+                return Err(Error::InvalidInput("A task can't start after it's due".into()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The stored `group_category` of a task; see `grouping`. The UI computes its
+/// own grouping when it loads tasks.
+///
+/// The due date decides when there is one; an undated task deferred to the
+/// future is "scheduled" rather than "nodate".
+pub fn group_category(completed: bool, due_date: Option<i64>, start_date: Option<i64>) -> String {
+    if completed {
+        return "nodate".into();
+    }
+    if due_date.is_none() && start_date.is_some_and(|start| start > now_ms()) {
+        return "scheduled".into();
+    }
+    grouping::compute_group_category(due_date, now_ms(), grouping::week_start())
+}
+
+pub async fn fetch_task(conn: &mut SqliteConnection, id: &str) -> Result<Task> {
+    sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Task {id}")))
+}
+
+/// Resolves the list a task belongs to, returning its id and name.
+pub(crate) async fn resolve_list(conn: &mut SqliteConnection, list_id: Option<&str>) -> Result<(String, String)> {
+    let list_id = list_id.unwrap_or(INBOX_LIST_ID);
+    let name: Option<String> = sqlx::query_scalar("SELECT name FROM lists WHERE id = ? AND deleted_at IS NULL")
+        .bind(list_id)
+        .fetch_optional(conn)
+        .await?;
+    name.map(|name| (list_id.to_string(), name))
+        .ok_or_else(|| Error::InvalidInput(format!("List {list_id} does not exist")))
+}
+
+/// Next `order` value at the end of a list.
+pub(crate) async fn next_order(conn: &mut SqliteConnection, list_id: &str) -> Result<i64> {
+    let max: Option<i64> = sqlx::query_scalar(r#"SELECT MAX("order") FROM tasks WHERE list_id = ?"#)
+        .bind(list_id)
+        .fetch_one(conn)
+        .await?;
+    Ok(max.map_or(0, |m| m + 1))
+}
+
+/// Fills the fields a new task left out from its list's defaults. The offset
+/// lands on local midnight, like a date picked without a time.
+async fn apply_list_defaults(
+    conn: &mut SqliteConnection,
+    list_id: &str,
+    input: &TaskInput,
+) -> Result<(Option<i64>, Option<i64>)> {
+    let (default_priority, due_offset_days): (Option<i64>, Option<i64>) =
+        sqlx::query_as("SELECT default_priority, default_due_offset_days FROM lists WHERE id = ?")
+            .bind(list_id)
+            .fetch_one(conn)
+            .await?;
+    let due_date = input.due_date.unwrap_or_else(|| {
+        let days = u64::try_from(due_offset_days?).ok()?;
+        let today = Local::now().date_naive();
+        today.checked_add_days(Days::new(days)).map(start_of_local_day)
+    });
+    Ok((due_date, input.priority.unwrap_or(default_priority)))
+}
+
+pub async fn insert_task(tx: &mut Transaction<'_, Sqlite>, input: &TaskInput) -> Result<Task> {
+    input.validate()?;
+    let recurrence_rule = recurrence::normalize(input.recurrence_rule.as_deref())?;
+    let (list_id, list_name) = resolve_list(tx, input.list_id.as_deref()).await?;
+    let (due_date, priority) = apply_list_defaults(tx, &list_id, input).await?;
+    let order = next_order(tx, &list_id).await?;
+    let id = new_id();
+    let now = now_ms();
+
+    sqlx::query(
+        r#"
+        INSERT INTO tasks (id, title, completed, due_date, list_id, list_name, content, "order",
+                           created_at, updated_at, tags, priority, group_category, recurrence_rule, color,
+                           start_date)
+        VALUES (?, ?, 0, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(input.title.trim())
+    .bind(due_date)
+    .bind(&list_id)
+    .bind(&list_name)
+    .bind(&input.content)
+    .bind(order)
+    .bind(now)
+    .bind(now)
+    .bind(serde_json::to_string(&input.tags).unwrap_or_else(|_| "[]".into()))
+    .bind(priority)
+    .bind(group_category(false, due_date, input.start_date))
+    .bind(recurrence_rule)
+    .bind(&input.color)
+    .bind(input.start_date)
+    .execute(&mut **tx)
+    .await?;
+
+    fetch_task(tx, &id).await
+}
+
+#[tauri::command]
+pub async fn create_task(app: AppHandle, state: State<'_, AppState>, input: TaskInput) -> Result<Task> {
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let task = insert_task(&mut tx, &input).await?;
+    tx.commit().await?;
+    log::debug!("Created task {}", task.id);
+    announce_created(&app, &pool, std::slice::from_ref(&task)).await;
+    Ok(task)
+}
+
+/// Announces committed new tasks: `task-created` for one, a reload for several,
+/// and the webhook's `created` for each. Every in-app path that inserts tasks ends here;
+/// the CLI, with no app to announce to, sends the webhook itself.
+pub(crate) async fn announce_created(app: &AppHandle, pool: &SqlitePool, tasks: &[Task]) {
+    match tasks {
+        [] => return,
+        [task] => events::task_created(app, task),
+        _ => events::tasks_changed(app),
+    }
+    webhook::queue(app, pool, Event::Created, tasks.iter().map(|task| task.id.as_str())).await;
+}
+
+/// Copies a task and its subtasks as open items, placed right after the original.
+/// The title gets the `duplicateTitleSuffix` setting appended (" (copy)" unless
+/// set); tags, content and the rest carry over, the due and start dates only
+/// with `keep_dates`.
+#[tauri::command]
+pub async fn duplicate_task(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    keep_dates: Option<bool>,
+) -> Result<Task> {
+    let keep_dates = keep_dates.unwrap_or(false);
+    let pool = state.db();
+    let suffix = settings::get::<String>(&pool, COPY_SUFFIX_KEY)
+        .await?
+        .unwrap_or_else(|| DEFAULT_COPY_SUFFIX.to_string());
+    let mut tx = pool.begin().await?;
+    let original = fetch_task(&mut tx, &task_id).await?;
+    let (due_date, start_date) = if keep_dates { (original.due_date, original.start_date) } else { (None, None) };
+    let id = new_id();
+    let now = now_ms();
+
+    // Make room directly below the original
+    let shifted =
+        sqlx::query(r#"UPDATE tasks SET "order" = "order" + 1, updated_at = ? WHERE list_id IS ? AND "order" > ?"#)
+            .bind(now)
+            .bind(&original.list_id)
+            .bind(original.order)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    sqlx::query(
+        r#"
+        INSERT INTO tasks (id, title, completed, due_date, list_id, list_name, content, "order",
+                           created_at, updated_at, tags, priority, group_category, recurrence_rule, color,
+                           start_date, estimated_minutes, reminder_offset_minutes)
+        SELECT ?, title || ?, 0, ?, list_id, list_name, content, ?, ?, ?, tags, priority, ?, recurrence_rule, color, ?,
+               estimated_minutes, reminder_offset_minutes
+        FROM tasks WHERE id = ?
+        "#,
+    )
+    .bind(&id)
+    .bind(&suffix)
+    .bind(due_date)
+    .bind(original.order + 1)
+    .bind(now)
+    .bind(now)
+    .bind(group_category(false, due_date, start_date))
+    .bind(start_date)
+    .bind(&task_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let subtasks: Vec<Subtask> = sqlx::query_as(r#"SELECT * FROM subtasks WHERE parent_id = ? ORDER BY "order", id"#)
+        .bind(&task_id)
+        .fetch_all(&mut *tx)
+        .await?;
+    for subtask in &subtasks {
+        sqlx::query(
+            r#"
+            INSERT INTO subtasks (id, parent_id, title, completed, due_date, "order", created_at, updated_at)
+            VALUES (?, ?, ?, 0, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(new_id())
+        .bind(&id)
+        .bind(&subtask.title)
+        .bind(subtask.due_date.filter(|_| keep_dates))
+        .bind(subtask.order)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    update_percentage(&mut tx, &id).await?;
+    let task = fetch_task(&mut tx, &id).await?;
+    tx.commit().await?;
+
+    log::debug!("Duplicated task {task_id} as {id} with {} subtasks", subtasks.len());
+    announce_created(&app, &pool, std::slice::from_ref(&task)).await;
+    if shifted > 0 {
+        // The tasks below the original moved down one as well
+        events::tasks_changed(&app);
+    }
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn update_task(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    input: TaskInput,
+) -> Result<Task> {
+    input.validate()?;
+    let recurrence_rule = recurrence::normalize(input.recurrence_rule.as_deref())?;
+    let mut tx = state.db().begin().await?;
+    let existing = fetch_task(&mut tx, &id).await?;
+    let (list_id, list_name) = resolve_list(&mut tx, input.list_id.as_deref()).await?;
+    // Moving to another list appends to the end of it; otherwise the position is kept
+    let order = if existing.list_id.as_deref() == Some(list_id.as_str()) {
+        existing.order
+    } else {
+        next_order(&mut tx, &list_id).await?
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE tasks
+        SET title = ?, content = ?, due_date = ?, list_id = ?, list_name = ?, "order" = ?,
+            tags = ?, priority = ?, group_category = ?, recurrence_rule = ?, color = ?, start_date = ?,
+            updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(input.title.trim())
+    .bind(&input.content)
+    // An update replaces every field, so a missing due date or priority clears it; list defaults are for new tasks
+    .bind(input.due_date.flatten())
+    .bind(&list_id)
+    .bind(&list_name)
+    .bind(order)
+    .bind(serde_json::to_string(&input.tags).unwrap_or_else(|_| "[]".into()))
+    .bind(input.priority.flatten())
+    .bind(group_category(existing.completed, input.due_date.flatten(), input.start_date))
+    .bind(recurrence_rule)
+    .bind(&input.color)
+    .bind(input.start_date)
+    .bind(now_ms())
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+
+    let task = fetch_task(&mut tx, &id).await?;
+    tx.commit().await?;
+    log::debug!("Updated task {id}");
+    events::task_updated(&app, &task);
+    Ok(task)
+}
+
+/// Pins or unpins a task. Its `order` is untouched, so unpinning puts it back where it was.
+#[tauri::command]
+pub async fn toggle_pin(app: AppHandle, state: State<'_, AppState>, task_id: String) -> Result<Task> {
+    let mut tx = state.db().begin().await?;
+    let toggled = sqlx::query("UPDATE tasks SET pinned = NOT pinned, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(now_ms())
+        .bind(&task_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if toggled == 0 {
+        return Err(Error::NotFound(format!("Task {task_id}")));
+    }
+    let task = fetch_task(&mut tx, &task_id).await?;
+    tx.commit().await?;
+    log::debug!("{} task {task_id}", if task.pinned { "Pinned" } else { "Unpinned" });
+    events::task_updated(&app, &task);
+    Ok(task)
+}
+
+/// Sets how long a task should take, or clears the estimate with `None`.
+/// The tracked `actual_minutes` is left alone.
+#[tauri::command]
+pub async fn set_estimate(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    minutes: Option<u32>,
+) -> Result<Task> {
+    if minutes.is_some_and(|m| !(1..=MAX_ESTIMATE_MINUTES).contains(&m)) {
+        return Err(Error::InvalidInput(format!(
+            "Estimates must be between 1 and {MAX_ESTIMATE_MINUTES} minutes"
+        )));
+    }
+    let mut tx = state.db().begin().await?;
+    let updated = sqlx::query("UPDATE tasks SET estimated_minutes = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(minutes)
+        .bind(now_ms())
+        .bind(&task_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if updated == 0 {
+        return Err(Error::NotFound(format!("Task {task_id}")));
+    }
+    let task = fetch_task(&mut tx, &task_id).await?;
+    tx.commit().await?;
+    log::debug!("Set the estimate of task {task_id} to {minutes:?} minutes");
+    events::task_updated(&app, &task);
+    Ok(task)
+}
+
+/// Marks a task complete. Completing a recurring task also creates its next
+/// occurrence in the same transaction.
+#[tauri::command]
+pub async fn complete_task(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    force: Option<bool>,
+) -> Result<Task> {
+    complete(&app, &state.db(), &id, force.unwrap_or(false)).await
+}
+
+async fn ensure_unblocked(conn: &mut SqliteConnection, id: &str) -> Result<()> {
+    let blocking = dependencies::incomplete_dependencies(conn, id).await?;
+    if blocking.is_empty() {
+        return Ok(());
+    }
+    Err(Error::InvalidInput(match blocking.len() {
+        1 => "This task is blocked by 1 incomplete task".to_string(),
+        n => format!("This task is blocked by {n} incomplete tasks"),
+    }))
+}
+
+/// Completes an open task like `complete_task`, or reopens a completed one.
+///
+/// Reopening clears `completed_at` and puts `complete_percentage` back to 0,
+/// or to its subtasks' share when it has any. A recurring task's next
+/// occurrence was created when it was completed and stays: only completing
+/// creates occurrences, reopening never removes them.
+#[tauri::command]
+pub async fn toggle_complete(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    force: Option<bool>,
+) -> Result<Task> {
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let existing = fetch_task(&mut tx, &task_id).await?;
+    if !existing.completed {
+        let completed = complete_unblocked_in(&mut tx, &task_id, force.unwrap_or(false)).await?;
+        tx.commit().await?;
+        log::debug!("Completed task {task_id}");
+        announce_completed(&app, &pool, &completed).await;
+        undo::record_completed(&app, std::slice::from_ref(&completed));
+        return Ok(completed.task);
+    }
+
+    let task = reopen_in(&mut tx, &existing).await?;
+    tx.commit().await?;
+    log::debug!("Reopened task {task_id}");
+    events::task_updated(&app, &task);
+    Ok(task)
+}
+
+/// `complete_task` for Rust callers such as reminder actions.
+pub async fn complete(app: &AppHandle, pool: &SqlitePool, id: &str, force: bool) -> Result<Task> {
+    let mut tx = pool.begin().await?;
+    let completed = complete_unblocked_in(&mut tx, id, force).await?;
+    tx.commit().await?;
+    log::debug!("Completed task {id}");
+    announce_completed(app, pool, &completed).await;
+    undo::record_completed(app, std::slice::from_ref(&completed));
+    Ok(completed.task)
+}
+
+/// A task completed by `complete_in`.
+pub(crate) struct Completed {
+    pub task: Task,
+    /// The task as it was before, for undo.
+    pub previous: Task,
+    /// The next occurrence, when the task recurs.
+    pub next: Option<Task>,
+    /// The series' streak, when this completion took it to a milestone.
+    pub milestone: Option<streaks::Streak>,
+}
+
+/// Emits the events for a committed `complete_in`.
+pub(crate) async fn announce_completed(app: &AppHandle, pool: &SqlitePool, completed: &Completed) {
+    events::task_updated(app, &completed.task);
+    if !completed.previous.completed {
+        webhook::queue(app, pool, Event::Completed, [completed.task.id.as_str()]).await;
+    }
+    if let Some(next) = &completed.next {
+        events::task_created(app, next);
+    }
+    if let Some(streak) = &completed.milestone {
+        streaks::celebrate(app, streak);
+    }
+}
+
+/// `complete_in`, refused while another open task blocks this one; `force`
+/// completes it anyway. The check runs in the same transaction as the write.
+pub(crate) async fn complete_unblocked_in(tx: &mut Transaction<'_, Sqlite>, id: &str, force: bool) -> Result<Completed> {
+    if !force {
+        ensure_unblocked(tx, id).await?;
+    }
+    complete_in(tx, id).await
+}
+
+/// Completes a task inside the caller's transaction.
+pub(crate) async fn complete_in(tx: &mut Transaction<'_, Sqlite>, id: &str) -> Result<Completed> {
+    let existing = fetch_task(tx, id).await?;
+    let now = now_ms();
+
+    sqlx::query(
+        r#"
+        UPDATE tasks
+        SET completed = 1, completed_at = ?, complete_percentage = 100,
+            group_category = 'nodate', updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(now)
+    .bind(now)
+    .bind(id)
+    .execute(&mut **tx)
+    .await?;
+
+    // Completing an already completed task again neither spawns nor counts
+    let (next, milestone) = if existing.completed {
+        (None, None)
+    } else {
+        let next = recurrence::spawn_next(tx, &existing).await?;
+        let milestone = streaks::record(tx, &existing, next.as_ref()).await?;
+        (next, milestone)
+    };
+
+    Ok(Completed { task: fetch_task(tx, id).await?, previous: existing, next, milestone })
+}
+
+/// Reopens a completed task inside the caller's transaction. Its percentage
+/// goes back to 0, or to the share of its subtasks that are done.
+pub(crate) async fn reopen_in(tx: &mut Transaction<'_, Sqlite>, existing: &Task) -> Result<Task> {
+    sqlx::query(
+        r#"
+        UPDATE tasks
+        SET completed = 0, completed_at = NULL, group_category = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(group_category(false, existing.due_date, existing.start_date))
+    .bind(now_ms())
+    .bind(&existing.id)
+    .execute(&mut **tx)
+    .await?;
+    update_percentage(tx, &existing.id).await?;
+    fetch_task(tx, &existing.id).await
+}
+
+/// Moves a task to the trash inside the caller's transaction, returning the list
+/// it was in; `None` if it didn't exist or was already trashed. Subtasks and
+/// attachments stay with it until the trash is emptied.
+pub(crate) async fn delete_in(tx: &mut Transaction<'_, Sqlite>, id: &str) -> Result<Option<Option<String>>> {
+    let now = now_ms();
+    Ok(sqlx::query_scalar(
+        "UPDATE tasks SET deleted_at = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL RETURNING list_id",
+    )
+    .bind(now)
+    .bind(now)
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?)
+}
+
+/// Moves a task to the trash; `restore` brings it back.
+#[tauri::command]
+pub async fn delete_task(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<()> {
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let Some(list_id) = delete_in(&mut tx, &id).await? else {
+        return Err(Error::NotFound(format!("Task {id}")));
+    };
+    tx.commit().await?;
+    log::debug!("Moved task {id} to the trash");
+    events::task_deleted(&app, &id, list_id.as_deref());
+    webhook::queue(&app, &pool, Event::Deleted, [id.as_str()]).await;
+    undo::record(&app, UndoEntry::Delete(vec![id]));
+    Ok(())
+}
+
+/// Trashes tasks in one transaction and returns the ids that existed.
+async fn delete_all(app: &AppHandle, pool: &SqlitePool, ids: &[String]) -> Result<Vec<String>> {
+    let mut tx = pool.begin().await?;
+    let mut deleted_ids = Vec::new();
+    for id in ids {
+        if delete_in(&mut tx, id).await?.is_some() {
+            deleted_ids.push(id.clone());
+        }
+    }
+    tx.commit().await?;
+    if !deleted_ids.is_empty() {
+        log::debug!("Moved {} tasks to the trash", deleted_ids.len());
+        events::tasks_changed(app);
+        webhook::queue(app, pool, Event::Deleted, deleted_ids.iter().map(String::as_str)).await;
+        undo::record(app, UndoEntry::Delete(deleted_ids.clone()));
+    }
+    Ok(deleted_ids)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkCompleted {
+    pub completed: u64,
+    /// Open tasks left as they were because another open task blocks them.
+    pub blocked: Vec<String>,
+}
+
+/// Completes the given tasks atomically. Blocked ones are skipped and listed,
+/// unless `force` completes them too.
+#[tauri::command]
+pub async fn bulk_complete(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    ids: Vec<String>,
+    force: Option<bool>,
+) -> Result<BulkCompleted> {
+    let force = force.unwrap_or(false);
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let mut completed = Vec::with_capacity(ids.len());
+    let mut blocked = Vec::new();
+    for id in &ids {
+        let open = !fetch_task(&mut tx, id).await?.completed;
+        if open && !force && !dependencies::incomplete_dependencies(&mut tx, id).await?.is_empty() {
+            blocked.push(id.clone());
+            continue;
+        }
+        completed.push(complete_in(&mut tx, id).await?);
+    }
+    tx.commit().await?;
+    if !blocked.is_empty() {
+        log::debug!("Left {} blocked tasks open", blocked.len());
+    }
+    if !completed.is_empty() {
+        events::tasks_changed(&app);
+    }
+    for streak in completed.iter().filter_map(|c| c.milestone.as_ref()) {
+        streaks::celebrate(&app, streak);
+    }
+    let newly_completed = completed.iter().filter(|c| !c.previous.completed);
+    webhook::queue(&app, &pool, Event::Completed, newly_completed.map(|c| c.task.id.as_str())).await;
+    undo::record_completed(&app, &completed);
+    Ok(BulkCompleted { completed: completed.len() as u64, blocked })
+}
+
+/// Trashes every given task atomically, returning how many existed.
+#[tauri::command]
+pub async fn bulk_delete(app: AppHandle, state: State<'_, AppState>, ids: Vec<String>) -> Result<u64> {
+    Ok(delete_all(&app, &state.db(), &ids).await?.len() as u64)
+}
+
+/// Trashes completed tasks, optionally only in one list and only those completed
+/// before `older_than` (epoch millis). Returns the trashed ids.
+#[tauri::command]
+pub async fn purge_completed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    list_id: Option<String>,
+    older_than: Option<i64>,
+) -> Result<Vec<String>> {
+    let ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM tasks
+        WHERE completed = 1 AND deleted_at IS NULL
+          AND (?1 IS NULL OR list_id = ?1)
+          AND (?2 IS NULL OR COALESCE(completed_at, updated_at) < ?2)
+        "#,
+    )
+    .bind(&list_id)
+    .bind(older_than)
+    .fetch_all(&state.db())
+    .await?;
+    delete_all(&app, &state.db(), &ids).await
+}
+
+/// Full order for a list after the caller's ids: those first, as given, then
+/// any ids the caller left out in their current order. Every id must belong to the owner.
+fn merge_order(current: &[String], ordered_ids: &[String], owner: &str) -> Result<Vec<String>> {
+    let mut seen = HashSet::new();
+    for id in ordered_ids {
+        if !current.contains(id) {
+            return Err(Error::InvalidInput(format!("{id} does not belong to {owner}")));
+        }
+        if !seen.insert(id.as_str()) {
+            return Err(Error::InvalidInput(format!("{id} appears more than once")));
+        }
+    }
+    let mut order = ordered_ids.to_vec();
+    order.extend(current.iter().filter(|id| !seen.contains(id.as_str())).cloned());
+    Ok(order)
+}
+
+/// Writes `"order"` = position for each id, returning the ids whose value changed.
+async fn write_order(tx: &mut Transaction<'_, Sqlite>, table: &str, ids: &[String]) -> Result<Vec<String>> {
+    let now = now_ms();
+    let mut changed = Vec::new();
+    for (index, id) in ids.iter().enumerate() {
+        // `table` is a literal from this module
+        let affected = sqlx::query(&format!(
+            r#"UPDATE {table} SET "order" = ?1, updated_at = ?2 WHERE id = ?3 AND "order" IS NOT ?1"#
+        ))
+        .bind(index as i64)
+        .bind(now)
+        .bind(id)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+        if affected > 0 {
+            changed.push(id.clone());
+        }
+    }
+    Ok(changed)
+}
+
+async fn list_task_ids(conn: &mut SqliteConnection, list_id: &str) -> Result<Vec<String>> {
+    Ok(sqlx::query_scalar(
+        r#"SELECT id FROM tasks WHERE list_id = ? AND deleted_at IS NULL ORDER BY "order", created_at"#,
+    )
+        .bind(list_id)
+        .fetch_all(conn)
+        .await?)
+}
+
+async fn fetch_tasks(conn: &mut SqliteConnection, ids: &[String]) -> Result<Vec<Task>> {
+    let mut tasks = Vec::with_capacity(ids.len());
+    for id in ids {
+        tasks.push(fetch_task(conn, id).await?);
+    }
+    Ok(tasks)
+}
+
+/// Sets the order of a list's tasks in one transaction, returning the tasks that moved.
+#[tauri::command]
+pub async fn reorder_tasks(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    list_id: String,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<Task>> {
+    let mut tx = state.db().begin().await?;
+    let current = list_task_ids(&mut tx, &list_id).await?;
+    let order = merge_order(&current, &ordered_ids, &format!("list {list_id}"))?;
+    let changed = write_order(&mut tx, "tasks", &order).await?;
+    let tasks = fetch_tasks(&mut tx, &changed).await?;
+    tx.commit().await?;
+    if !tasks.is_empty() {
+        events::tasks_changed(&app);
+    }
+    Ok(tasks)
+}
+
+/// Where a task sits: its list and index in that list's manual order.
+async fn position(conn: &mut SqliteConnection, task: &Task) -> Result<Option<(String, usize)>> {
+    let Some(list_id) = &task.list_id else {
+        return Ok(None);
+    };
+    let ids = list_task_ids(conn, list_id).await?;
+    Ok(ids.iter().position(|id| *id == task.id).map(|index| (list_id.clone(), index)))
+}
+
+/// Moves a task to `new_index` in a list inside the caller's transaction and
+/// renumbers both lists, returning the ids whose list or position changed.
+pub(crate) async fn move_in(
+    tx: &mut Transaction<'_, Sqlite>,
+    task_id: &str,
+    target_list_id: &str,
+    new_index: usize,
+) -> Result<Vec<String>> {
+    let task = fetch_task(tx, task_id).await?;
+    let (target_list_id, target_list_name) = resolve_list(tx, Some(target_list_id)).await?;
+
+    let mut target: Vec<String> = list_task_ids(tx, &target_list_id)
+        .await?
+        .into_iter()
+        .filter(|id| id != task_id)
+        .collect();
+    target.insert(new_index.min(target.len()), task_id.to_string());
+
+    let mut changed = Vec::new();
+    if task.list_id.as_deref() != Some(target_list_id.as_str()) {
+        sqlx::query("UPDATE tasks SET list_id = ?, list_name = ?, updated_at = ? WHERE id = ?")
+            .bind(&target_list_id)
+            .bind(&target_list_name)
+            .bind(now_ms())
+            .bind(task_id)
+            .execute(&mut **tx)
+            .await?;
+        changed.push(task_id.to_string());
+
+        if let Some(source_list_id) = &task.list_id {
+            let source = list_task_ids(tx, source_list_id).await?;
+            changed.extend(write_order(tx, "tasks", &source).await?);
+        }
+    }
+    for id in write_order(tx, "tasks", &target).await? {
+        if !changed.contains(&id) {
+            changed.push(id);
+        }
+    }
+    Ok(changed)
+}
+
+/// Moves a task to `new_index` in another (or the same) list and renumbers both
+/// lists. Returns every task whose list or position changed.
+#[tauri::command]
+pub async fn move_task(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    target_list_id: String,
+    new_index: usize,
+) -> Result<Vec<Task>> {
+    let mut tx = state.db().begin().await?;
+    let from = position(&mut tx, &fetch_task(&mut tx, &task_id).await?).await?;
+    let changed = move_in(&mut tx, &task_id, &target_list_id, new_index).await?;
+    let to = position(&mut tx, &fetch_task(&mut tx, &task_id).await?).await?;
+    let tasks = fetch_tasks(&mut tx, &changed).await?;
+    tx.commit().await?;
+    if !tasks.is_empty() {
+        events::tasks_changed(&app);
+    }
+    if let Some((from, to)) = from.zip(to).filter(|(from, to)| from != to) {
+        undo::record(&app, UndoEntry::Move { task_id, from, to });
+    }
+    Ok(tasks)
+}
+
+/// Sets the order of a task's subtasks in one transaction, returning the subtasks that moved.
+#[tauri::command]
+pub async fn reorder_subtasks(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    parent_id: String,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<Subtask>> {
+    let mut tx = state.db().begin().await?;
+    let current: Vec<String> =
+        sqlx::query_scalar(r#"SELECT id FROM subtasks WHERE parent_id = ? ORDER BY "order", created_at"#)
+            .bind(&parent_id)
+            .fetch_all(&mut *tx)
+            .await?;
+    let order = merge_order(&current, &ordered_ids, &format!("task {parent_id}"))?;
+    let changed = write_order(&mut tx, "subtasks", &order).await?;
+
+    let mut subtasks = Vec::with_capacity(changed.len());
+    for id in &changed {
+        subtasks.push(
+            sqlx::query_as::<_, Subtask>("SELECT * FROM subtasks WHERE id = ?")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?,
+        );
+    }
+    tx.commit().await?;
+    if !subtasks.is_empty() {
+        events::tasks_changed(&app);
+    }
+    Ok(subtasks)
+}
+
+/// Sets the priority and due-date offset new tasks in a list start with. `None` clears a default.
+#[tauri::command]
+pub async fn update_list_defaults(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    list_id: String,
+    priority: Option<i64>,
+    due_offset_days: Option<i64>,
+) -> Result<()> {
+    if priority.is_some_and(|p| !(1..=3).contains(&p)) {
+        return Err(Error::InvalidInput("Priority must be 1 (high) to 3 (low)".into()));
+    }
+    if due_offset_days.is_some_and(|d| !(0..=3650).contains(&d)) {
+        return Err(Error::InvalidInput("The due offset must be between 0 and 3650 days".into()));
+    }
+    let updated = sqlx::query(
+        "UPDATE lists SET default_priority = ?, default_due_offset_days = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(priority)
+    .bind(due_offset_days)
+    .bind(now_ms())
+    .bind(&list_id)
+    .execute(&state.db())
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("List {list_id}")));
+    }
+    events::list_updated(&app, Some(&list_id));
+    Ok(())
+}
+
+/// Renames a list, along with the `list_name` its tasks carry, in one transaction.
+/// Names are unique among live lists, ignoring case, since lists are found by name.
+#[tauri::command]
+pub async fn rename_list(app: AppHandle, state: State<'_, AppState>, list_id: String, new_name: String) -> Result<()> {
+    let name = new_name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidInput("List names can't be empty".into()));
+    }
+    // Tasks named into "Trash" read as deleted
+    if name.eq_ignore_ascii_case("Trash") {
+        return Err(Error::InvalidInput("\"Trash\" is reserved".into()));
+    }
+    let now = now_ms();
+    let mut tx = state.db().begin().await?;
+    let taken: Option<String> = sqlx::query_scalar(
+        "SELECT id FROM lists WHERE name = ? COLLATE NOCASE AND id != ? AND deleted_at IS NULL",
+    )
+    .bind(name)
+    .bind(&list_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if taken.is_some() {
+        return Err(Error::InvalidInput(format!("A list named \"{name}\" already exists")));
+    }
+    let renamed = sqlx::query("UPDATE lists SET name = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(name)
+        .bind(now)
+        .bind(&list_id)
+        .execute(&mut *tx)
+        .await?;
+    if renamed.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("List {list_id}")));
+    }
+    // The lists_name_cascade trigger already copied the name; this marks the tasks changed for sync
+    for table in ["tasks", "archived_tasks"] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET list_name = ?1, updated_at = ?2 WHERE list_id = ?3 AND list_name != 'Trash'"
+        ))
+        .bind(name)
+        .bind(now)
+        .bind(&list_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    log::debug!("Renamed list {list_id} to {name}");
+    events::list_updated(&app, Some(&list_id));
+    events::tasks_changed(&app);
+    Ok(())
+}
+
+/// Moves a list to the trash. With `include_tasks` its tasks go along and come
+/// back when the list is restored; otherwise they move to the end of the Inbox.
+#[tauri::command]
+pub async fn delete_list(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    list_id: String,
+    include_tasks: Option<bool>,
+) -> Result<()> {
+    if list_id == INBOX_LIST_ID {
+        return Err(Error::InvalidInput("The Inbox can't be deleted".into()));
+    }
+    let now = now_ms();
+    let mut tx = state.db().begin().await?;
+    let trashed = sqlx::query("UPDATE lists SET deleted_at = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(now)
+        .bind(now)
+        .bind(&list_id)
+        .execute(&mut *tx)
+        .await?;
+    if trashed.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("List {list_id}")));
+    }
+
+    if include_tasks.unwrap_or(false) {
+        // The shared timestamp is what ties these tasks to the list in the trash
+        sqlx::query("UPDATE tasks SET deleted_at = ?1, updated_at = ?1 WHERE list_id = ?2 AND deleted_at IS NULL")
+            .bind(now)
+            .bind(&list_id)
+            .execute(&mut *tx)
+            .await?;
+    } else {
+        let (inbox_id, inbox_name) = resolve_list(&mut tx, None).await?;
+        let base = next_order(&mut tx, &inbox_id).await?;
+        sqlx::query(
+            r#"
+            UPDATE tasks SET list_id = ?, list_name = ?, "order" = ? + "order", updated_at = ?
+            WHERE list_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(&inbox_id)
+        .bind(&inbox_name)
+        .bind(base)
+        .bind(now)
+        .bind(&list_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    log::debug!("Moved list {list_id} to the trash");
+    events::list_updated(&app, Some(&list_id));
+    events::tasks_changed(&app);
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Open-task counts for the sidebar: the smart lists and every user list.
+//!
+//! Everything comes from one query, whatever the number of lists, and days are
+//! cut at local midnight. The counts are pushed as `counts-changed` after any
+//! task or list change and when the day turns.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::Days;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+
+use crate::commands::INBOX_LIST_ID;
+use crate::dates::{local_date, start_of_local_day};
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::{events, AppState};
+
+/// Days the Upcoming list covers, today included.
+const UPCOMING_DAYS: u64 = 7;
+/// Slack after midnight, so the refresh can't land a hair before it.
+const AFTER_MIDNIGHT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartCounts {
+    /// Tasks in the Inbox or in no list.
+    pub inbox: i64,
+    pub today: i64,
+    /// Due from today through the next six days.
+    pub upcoming: i64,
+    /// Due before today.
+    pub overdue: i64,
+    /// Without a due date.
+    pub anytime: i64,
+    /// Pinned, in any list.
+    pub pinned: i64,
+    /// Open tasks per list id.
+    pub lists: BTreeMap<String, i64>,
+}
+
+pub async fn compute(pool: &SqlitePool) -> Result<SmartCounts> {
+    let today = local_date(now_ms());
+    let start = start_of_local_day(today);
+    let tomorrow = start_of_local_day(today + Days::new(1));
+    let upcoming_end = start_of_local_day(today + Days::new(UPCOMING_DAYS));
+
+    // The first row, with no list id, holds the smart lists; the rest one list each
+    let rows: Vec<(Option<String>, i64, i64, i64, i64, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT NULL,
+               COALESCE(SUM(list_id IS NULL OR list_id = ?1), 0),
+               COALESCE(SUM(due_date >= ?2 AND due_date < ?3), 0),
+               COALESCE(SUM(due_date >= ?2 AND due_date < ?4), 0),
+               COALESCE(SUM(due_date < ?2), 0),
+               COALESCE(SUM(due_date IS NULL), 0),
+               COALESCE(SUM(pinned), 0)
+        FROM tasks
+        WHERE completed = 0 AND deleted_at IS NULL AND list_name != 'Trash'
+        UNION ALL
+        SELECT l.id, COUNT(t.id), 0, 0, 0, 0, 0
+        FROM lists l
+        LEFT JOIN tasks t ON t.list_id = l.id AND t.completed = 0 AND t.deleted_at IS NULL
+        WHERE l.deleted_at IS NULL AND l.name != 'Trash'
+        GROUP BY l.id
+        "#,
+    )
+    .bind(INBOX_LIST_ID)
+    .bind(start)
+    .bind(tomorrow)
+    .bind(upcoming_end)
+    .fetch_all(pool)
+    .await?;
+
+    let mut counts = SmartCounts::default();
+    for (list_id, first, today, upcoming, overdue, anytime, pinned) in rows {
+        match list_id {
+            Some(list_id) => {
+                counts.lists.insert(list_id, first);
+            }
+            None => {
+                counts.inbox = first;
+                counts.today = today;
+                counts.upcoming = upcoming;
+                counts.overdue = overdue;
+                counts.anytime = anytime;
+                counts.pinned = pinned;
+            }
+        }
+    }
+    Ok(counts)
+}
+
+async fn refresh(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let _permit = state.job_permit().await;
+    match compute(&state.db()).await {
+        Ok(counts) => {
+            let _ = app.emit("counts-changed", counts);
+        }
+        Err(e) => log::error!("Failed to count tasks: {e}"),
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    events::on_tasks_changed(app, |handle| {
+        tauri::async_runtime::spawn(async move { refresh(&handle).await });
+    });
+    let handle = app.clone();
+    app.listen(events::LIST_UPDATED, move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move { refresh(&handle).await });
+    });
+
+    // Today and Overdue move on at midnight without any task changing
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let now = now_ms();
+            let midnight = start_of_local_day(local_date(now) + Days::new(1));
+            let wait = Duration::from_millis(u64::try_from(midnight - now).unwrap_or_default());
+            tokio::time::sleep(wait + AFTER_MIDNIGHT).await;
+            refresh(&handle).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn smart_list_counts(state: State<'_, AppState>) -> Result<SmartCounts> {
+    compute(&state.db()).await
+}
@@ -0,0 +1,227 @@
+//! Passphrase-based key derivation for encrypted database copies and exports.
+//!
+//! The app itself always runs on the plain database: the frontend reads it
+//! through the SQL plugin, which can't supply a key, and nothing unlocks an
+//! encrypted file at launch. What a build with the `sqlcipher` feature offers
+//! is `write_encrypted_copy`, which writes a SQLCipher copy of the active
+//! database beside it (`tada.db.encrypted`) and leaves the live file alone.
+//! The copy is keyed with the raw Argon2 key, so opening it takes the
+//! passphrase and the salt stored in `encryption.json`.
+//!
+//! Exports are sealed with XChaCha20-Poly1305 under an Argon2 key, behind a
+//! header of magic bytes, format version, salt and nonce. The header is
+//! authenticated along with the payload, and a version this build doesn't know
+//! is refused rather than guessed at.
+
+use std::path::PathBuf;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::error::{Error, Result};
+use crate::{paths, workspaces};
+use crate::AppState;
+
+pub const KEY_LEN: usize = 32;
+pub const SALT_LEN: usize = 16;
+const CONFIG_FILE: &str = "encryption.json";
+
+/// Stored beside the database: the salt can't live inside the encrypted copy.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    pub salt: String,
+}
+
+fn argon2() -> Argon2<'static> {
+    // Argon2id with 64 MiB / 3 passes: slow enough to resist guessing, fast enough to unlock at launch
+    let params = Params::new(64 * 1024, 3, 1, Some(KEY_LEN)).expect("valid Argon2 parameters");
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+}
+
+/// Derives a 256-bit key from a passphrase and salt.
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    argon2()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| Error::Crypto(e.to_string()))?;
+    Ok(key)
+}
+
+pub fn generate_salt() -> Result<[u8; SALT_LEN]> {
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::getrandom(&mut salt).map_err(|e| Error::Crypto(e.to_string()))?;
+    Ok(salt)
+}
+
+const SEALED_MAGIC: &[u8; 8] = b"TADAENC\0";
+/// Bumped whenever the KDF parameters, cipher or header layout change.
+const SEALED_VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = SEALED_MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// Whether `bytes` start like something `seal` wrote.
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(SEALED_MAGIC)
+}
+
+/// Encrypts `plaintext` under a key derived from `passphrase`, with a fresh salt and nonce.
+pub fn seal(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let salt = generate_salt()?;
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce).map_err(|e| Error::Crypto(e.to_string()))?;
+    let key = derive_key(passphrase, &salt)?;
+
+    let mut sealed = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    sealed.extend_from_slice(SEALED_MAGIC);
+    sealed.push(SEALED_VERSION);
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad: &sealed })
+        .map_err(|e| Error::Crypto(e.to_string()))?;
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypts what `seal` wrote. A wrong passphrase and a tampered file look the
+/// same to the cipher, and both come back as `IncorrectPassword`.
+pub fn open(passphrase: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+    if !is_sealed(sealed) || sealed.len() < HEADER_LEN {
+        return Err(Error::InvalidInput("Not an encrypted Tada export".into()));
+    }
+    let version = sealed[SEALED_MAGIC.len()];
+    if version != SEALED_VERSION {
+        return Err(Error::InvalidInput(format!(
+            "This export uses encryption format {version}, which needs a newer version of Tada"
+        )));
+    }
+    let (header, ciphertext) = sealed.split_at(HEADER_LEN);
+    let salt = &header[SEALED_MAGIC.len() + 1..SEALED_MAGIC.len() + 1 + SALT_LEN];
+    let nonce = &header[HEADER_LEN - NONCE_LEN..];
+    let key = derive_key(passphrase, salt)?;
+    XChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: header })
+        .map_err(|_| Error::IncorrectPassword)
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// SQLCipher's raw-key form, `x'…'`, which `PRAGMA key` and `ATTACH ... KEY`
+/// take as a string. Given that rather than a passphrase, SQLCipher uses the
+/// key as it is instead of running it through its own KDF.
+pub fn raw_key(key: &[u8; KEY_LEN]) -> String {
+    format!("x'{}'", to_hex(key))
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(paths::config_dir(app)?.join(CONFIG_FILE))
+}
+
+/// Writes a SQLCipher-encrypted copy of the active database beside it, e.g.
+/// `tada.db.encrypted`, keyed from the passphrase, and returns its path. The
+/// live database stays unencrypted and in use.
+#[tauri::command]
+pub async fn write_encrypted_copy(app: AppHandle, state: State<'_, AppState>, passphrase: String) -> Result<String> {
+    if passphrase.chars().count() < 8 {
+        return Err(Error::InvalidInput("Passphrase must be at least 8 characters".into()));
+    }
+
+    let cipher: Option<String> = sqlx::query_scalar("PRAGMA cipher_version")
+        .fetch_optional(&state.db())
+        .await?;
+    if cipher.is_none() {
+        return Err(Error::InvalidInput("This build of Tada was compiled without SQLCipher support".into()));
+    }
+
+    let salt = generate_salt()?;
+    let key = derive_key(&passphrase, &salt)?;
+    // Next to the active workspace's file, e.g. `tada.db.encrypted`
+    let mut target = workspaces::active_db_path(&paths::config_dir(&app)?).into_os_string();
+    target.push(".encrypted");
+    let target = PathBuf::from(target);
+    let _ = std::fs::remove_file(&target);
+
+    let mut conn = state.db().acquire().await?;
+    // ATTACH takes the path and key as expressions, so both are bound
+    sqlx::query("ATTACH DATABASE ? AS encrypted KEY ?")
+        .bind(target.to_string_lossy().into_owned())
+        .bind(raw_key(&key))
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("SELECT sqlcipher_export('encrypted')").execute(&mut *conn).await?;
+    sqlx::query("DETACH DATABASE encrypted").execute(&mut *conn).await?;
+
+    let config = EncryptionConfig { salt: to_hex(&salt) };
+    std::fs::write(config_path(&app)?, serde_json::to_vec_pretty(&config)?)?;
+    log::info!("Wrote an encrypted copy of the database to {}", target.display());
+    Ok(target.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT: [u8; SALT_LEN] = [7; SALT_LEN];
+
+    #[test]
+    fn derive_key_is_deterministic_for_a_salt() {
+        assert_eq!(derive_key("correct horse", &SALT).unwrap(), derive_key("correct horse", &SALT).unwrap());
+    }
+
+    #[test]
+    fn derive_key_differs_by_salt() {
+        let other = [8; SALT_LEN];
+        assert_ne!(derive_key("correct horse", &SALT).unwrap(), derive_key("correct horse", &other).unwrap());
+    }
+
+    #[test]
+    fn raw_key_is_sqlcipher_hex_form() {
+        let key = [0xab; KEY_LEN];
+        let raw = raw_key(&key);
+        assert_eq!(raw, format!("x'{}'", "ab".repeat(KEY_LEN)));
+    }
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let sealed = seal("correct horse", b"{\"tables\":{}}").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(sealed[SEALED_MAGIC.len()], SEALED_VERSION);
+        assert_eq!(open("correct horse", &sealed).unwrap(), b"{\"tables\":{}}");
+    }
+
+    #[test]
+    fn seal_uses_a_fresh_salt_and_nonce() {
+        assert_ne!(seal("correct horse", b"same").unwrap(), seal("correct horse", b"same").unwrap());
+    }
+
+    #[test]
+    fn open_rejects_a_wrong_passphrase() {
+        let sealed = seal("correct horse", b"secret").unwrap();
+        assert!(matches!(open("battery staple", &sealed), Err(Error::IncorrectPassword)));
+    }
+
+    #[test]
+    fn open_rejects_a_tampered_header() {
+        let mut sealed = seal("correct horse", b"secret").unwrap();
+        sealed[SEALED_MAGIC.len() + 1] ^= 1;
+        assert!(matches!(open("correct horse", &sealed), Err(Error::IncorrectPassword)));
+    }
+
+    #[test]
+    fn open_refuses_an_unknown_version() {
+        let mut sealed = seal("correct horse", b"secret").unwrap();
+        sealed[SEALED_MAGIC.len()] = SEALED_VERSION + 1;
+        assert!(matches!(open("correct horse", &sealed), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn open_refuses_plain_bytes() {
+        assert!(!is_sealed(b"{}"));
+        assert!(matches!(open("correct horse", b"{}"), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,149 @@
+//! A morning nudge to plan the day: "You have N tasks due today".
+//!
+//! `dailyPlanningTime` holds a local wall-clock time like `"08:30"`; empty,
+//! `null` or missing turns the nudge off. The day it last fired is stored, so
+//! it fires once per day however often the app restarts. A launch after the
+//! time still nudges, up to `LATE_LIMIT` later; past that the day is skipped.
+//!
+//! The desktop notification plugin reports no clicks, so `daily-planning`
+//! also goes to the UI to offer the same in-app. Either way the click lands
+//! in `start_daily_review`, which focuses the window and emits `start-daily-review`.
+
+use std::time::Duration;
+
+use chrono::{Days, NaiveDate, NaiveTime};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::dates::{local_date, local_day_bounds, resolve_local};
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::{settings, show_main_window, AppState};
+
+const TIME_KEY: &str = "dailyPlanningTime";
+/// Local date of the last nudge, `YYYY-MM-DD`.
+pub const LAST_FIRED_KEY: &str = "dailyPlanningLastFired";
+/// Longest the scheduler sleeps, so a changed setting or a wake from sleep is noticed soon.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// How late a nudge may still arrive, e.g. when the app starts after the time.
+const LATE_LIMIT_MS: i64 = 3 * 60 * 60 * 1000;
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DailyPlanning {
+    due_today: i64,
+}
+
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let next = {
+                let state = handle.state::<AppState>();
+                let _permit = state.job_permit().await;
+                check(&handle, &state.db()).await.unwrap_or_else(|e| {
+                    log::error!("Failed to check the daily planning nudge: {e}");
+                    None
+                })
+            };
+            // Sleep to the fire time when it's close, but never past a check
+            let wait = next
+                .map(|at| Duration::from_millis((at - now_ms()).max(0) as u64))
+                .map_or(CHECK_INTERVAL, |until| until.min(CHECK_INTERVAL));
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+/// The configured time, `None` when the nudge is off.
+async fn planning_time(pool: &SqlitePool) -> Result<Option<NaiveTime>> {
+    let value = settings::get::<Option<String>>(pool, TIME_KEY).await?.flatten().unwrap_or_default();
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let time = NaiveTime::parse_from_str(value, "%H:%M").or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"));
+    match time {
+        Ok(time) => Ok(Some(time)),
+        Err(_) => {
+            log::debug!("Ignoring {TIME_KEY} '{value}', which isn't HH:MM");
+            Ok(None)
+        }
+    }
+}
+
+/// When the next nudge is due, epoch millis: today's time unless today already
+/// had one or it's too late for it, otherwise tomorrow's. The date is resolved
+/// in the local zone each time, so the nudge stays at the same wall-clock time
+/// across DST changes; a time skipped by the jump moves an hour later.
+fn next_fire(time: NaiveTime, now: i64, last_fired: Option<NaiveDate>) -> Option<i64> {
+    let at = |date: NaiveDate| resolve_local(date.and_time(time)).map(|d| d.timestamp_millis());
+    let today = local_date(now);
+    let today_at = at(today)?;
+    if last_fired.is_none_or(|last| last < today) && now <= today_at + LATE_LIMIT_MS {
+        return Some(today_at);
+    }
+    at(today.checked_add_days(Days::new(1))?)
+}
+
+/// Fires the nudge if it's due and returns when the next one is, `None` while it's off.
+async fn check(app: &AppHandle, pool: &SqlitePool) -> Result<Option<i64>> {
+    let Some(time) = planning_time(pool).await? else {
+        return Ok(None);
+    };
+    let last_fired = settings::get::<String>(pool, LAST_FIRED_KEY)
+        .await?
+        .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok());
+    let now = now_ms();
+    let Some(next) = next_fire(time, now, last_fired) else {
+        return Ok(None);
+    };
+    if next > now {
+        return Ok(Some(next));
+    }
+
+    // Recorded before showing, so a failed notification isn't retried all morning
+    let today = local_date(now);
+    settings::set(pool, LAST_FIRED_KEY, &today.format("%Y-%m-%d").to_string()).await?;
+    fire(app, pool).await?;
+    Ok(next_fire(time, now, Some(today)))
+}
+
+async fn fire(app: &AppHandle, pool: &SqlitePool) -> Result<()> {
+    let (start, end) = local_day_bounds(now_ms());
+    let due_today: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM tasks
+        WHERE completed = 0 AND deleted_at IS NULL AND list_name != 'Trash'
+          AND due_date >= ? AND due_date < ?
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_one(pool)
+    .await?;
+
+    let body = match due_today {
+        0 => "Nothing is due today. Take a minute to plan your day.".to_string(),
+        1 => "You have 1 task due today".to_string(),
+        n => format!("You have {n} tasks due today"),
+    };
+    log::info!("Sending the daily planning nudge ({due_today} due today)");
+    app.notification()
+        .builder()
+        .title("Plan your day")
+        .body(body)
+        .extra("kind", "dailyPlanning")
+        .show()?;
+    let _ = app.emit("daily-planning", DailyPlanning { due_today });
+    Ok(())
+}
+
+/// Handles a click on the nudge, from the notification or the in-app prompt.
+#[tauri::command]
+pub fn start_daily_review(app: AppHandle) {
+    show_main_window(&app);
+    let _ = app.emit("start-daily-review", ());
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+/// Resolves a wall-clock time in the local zone. A time skipped by a DST jump
+/// moves forward an hour; an ambiguous one picks the earlier instant.
+pub fn resolve_local(dt: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local
+        .from_local_datetime(&dt)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(dt + Duration::hours(1))).earliest())
+}
+
+/// Epoch millis of local midnight at the start of `date`.
+pub fn start_of_local_day(date: NaiveDate) -> i64 {
+    resolve_local(date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .map(|d| d.timestamp_millis())
+        .unwrap_or_default()
+}
+
+/// Local calendar date containing the given instant.
+pub fn local_date(ms: i64) -> NaiveDate {
+    Local
+        .timestamp_millis_opt(ms)
+        .single()
+        .map(|d| d.date_naive())
+        .unwrap_or_else(|| Local::now().date_naive())
+}
+
+/// `[start, end)` epoch-millis bounds of the local day containing `ms`.
+///
+/// Due dates are stored as UTC epoch millis, so "today" has to be cut in the
+/// user's timezone rather than on UTC midnight.
+pub fn local_day_bounds(ms: i64) -> (i64, i64) {
+    let date = local_date(ms);
+    let next = date.succ_opt().unwrap_or(date);
+    (start_of_local_day(date), start_of_local_day(next))
+}
+
+/// Local time as `YYYY-MM-DD HH:MM:SS`, the ISO 8601 form spreadsheets parse as a date.
+pub fn format_local(ms: i64) -> String {
+    Local
+        .timestamp_millis_opt(ms)
+        .single()
+        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
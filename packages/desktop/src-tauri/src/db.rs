@@ -0,0 +1,121 @@
+use serde_json::{Map, Value};
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow, SqliteSynchronous,
+};
+use sqlx::{Column, Executor, Row, Sqlite, TypeInfo, ValueRef};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::error::Result;
+use crate::{collation, paths, workspaces};
+
+/// The default workspace's database file name; `paths::sql_url` turns it into the SQL plugin's connection string.
+pub const DB_FILE: &str = "tada.db";
+/// The bundle identifier from `tauri.conf.json`, for resolving the config dir without a Tauri runtime.
+const IDENTIFIER: &str = "com.loadshine.tada";
+
+/// How long a connection waits on another writer (often the webview) before failing with `SQLITE_BUSY`.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Prepared statements kept per connection. Fixed queries stay cached; the
+/// dynamic ones (`query_tasks`, the palette) rotate through the rest.
+const STATEMENT_CACHE_CAPACITY: usize = 256;
+
+/// Opens a pool on the active workspace's database file, the one the webview
+/// loads through the SQL plugin. Creates it if it's missing; `setup` runs the
+/// migrations on it, since the plugin only does so once the webview loads it.
+///
+/// WAL lets background jobs (reminders, sync, badge counts) read while the UI writes.
+/// The journal mode is stored in the file, so the plugin's connections pick it up too.
+/// Foreign keys are per connection and the plugin doesn't turn them on, so every
+/// connection here does, or the `ON DELETE CASCADE` clauses would be ignored.
+/// Collations are per connection as well, so `NATURAL` only exists on this pool.
+pub async fn connect(app: &AppHandle) -> Result<SqlitePool> {
+    open(&workspaces::active_db_path(&paths::config_dir(app)?)).await
+}
+
+/// `app_config_dir` resolved the same way Tauri does but without an `AppHandle`,
+/// for the command-line interface and plugin setup. Portable installs use their own folder.
+pub fn config_dir() -> Result<PathBuf> {
+    if let Some(root) = paths::portable_root() {
+        return Ok(root.to_path_buf());
+    }
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no config directory for this user"))?;
+    Ok(config_dir.join(IDENTIFIER))
+}
+
+/// The active workspace's database, without an `AppHandle`.
+pub fn default_path() -> Result<PathBuf> {
+    Ok(workspaces::active_db_path(&config_dir()?))
+}
+
+pub async fn open(path: &Path) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(BUSY_TIMEOUT)
+        .statement_cache_capacity(STATEMENT_CACHE_CAPACITY)
+        .foreign_keys(true)
+        .collation(collation::NATURAL, collation::compare);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(4)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
+
+/// A fresh row id: a hyphenated UUIDv7, which is URL-safe and sorts by
+/// creation time, so new rows land at the end of the primary-key index.
+/// Ids are opaque text, so the v4 ids of older rows stay valid alongside.
+///
+/// Also a command, so the webview mints ids the same way.
+#[tauri::command]
+pub fn new_id() -> String {
+    uuid::Uuid::now_v7().to_string()
+}
+
+/// Current time as epoch milliseconds, the unit used by every timestamp column.
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Converts a row of any table into a JSON object keyed by column name,
+/// using the storage class of each value.
+pub fn row_to_json(row: &SqliteRow) -> Map<String, Value> {
+    let mut map = Map::new();
+    for column in row.columns() {
+        map.insert(column.name().to_string(), column_to_json(row, column.ordinal()));
+    }
+    map
+}
+
+/// The value of one column by position, by its storage class.
+pub fn column_to_json(row: &SqliteRow, i: usize) -> Value {
+    match row.try_get_raw(i) {
+        Ok(raw) if raw.is_null() => Value::Null,
+        Ok(raw) => match raw.type_info().name() {
+            "INTEGER" => row.try_get::<i64, _>(i).map(Value::from).unwrap_or_default(),
+            "REAL" => row.try_get::<f64, _>(i).map(Value::from).unwrap_or_default(),
+            "BLOB" => row.try_get::<Vec<u8>, _>(i).map(Value::from).unwrap_or_default(),
+            _ => row.try_get::<String, _>(i).map(Value::from).unwrap_or_default(),
+        },
+        Err(_) => Value::Null,
+    }
+}
+
+/// Column names of a table, in declaration order.
+pub async fn table_columns<'e, E>(executor: E, table: &str) -> Result<Vec<String>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    Ok(sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(executor)
+        .await?)
+}
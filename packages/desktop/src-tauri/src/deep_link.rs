@@ -0,0 +1,113 @@
+//! `tada://` links from other apps:
+//!
+//! - `tada://task/<id>` focuses the window and emits `navigate-to-task` with the id
+//! - `tada://new?title=...&list=...` emits `create-task-from-url`
+//!
+//! A second launch with a link is forwarded here by the single-instance plugin.
+//! Clicked reminders jump to their task through the same `navigate_to_task`.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::error::Result;
+use crate::{show_main_window, AppState};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTaskLink {
+    pub title: String,
+    pub list: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    OpenTask(String),
+    NewTask(NewTaskLink),
+}
+
+/// Parses a `tada://` URL. Query values arrive percent-decoded.
+pub fn parse(url: &Url) -> Result<DeepLink, String> {
+    if url.scheme() != "tada" {
+        return Err(format!("unsupported scheme '{}'", url.scheme()));
+    }
+    match url.host_str() {
+        Some("task") => {
+            let id = url.path().trim_matches('/');
+            if id.is_empty() || id.contains('/') {
+                return Err("expected tada://task/<id>".into());
+            }
+            Ok(DeepLink::OpenTask(id.to_string()))
+        }
+        Some("new") => {
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            let title = param("title").ok_or("tada://new needs a title parameter")?;
+            Ok(DeepLink::NewTask(NewTaskLink { title, list: param("list") }))
+        }
+        _ => Err("unknown link target".into()),
+    }
+}
+
+/// Brings the window up and emits `navigate-to-task` with the id, or
+/// `task-not-found` if the task has since been deleted, for the UI to say so.
+pub async fn navigate_to_task(app: &AppHandle, task_id: &str) -> Result<()> {
+    show_main_window(app);
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM tasks WHERE id = ? AND deleted_at IS NULL)")
+        .bind(task_id)
+        .fetch_one(&app.state::<AppState>().db())
+        .await?;
+    if exists {
+        let _ = app.emit("navigate-to-task", task_id);
+    } else {
+        log::info!("Task {task_id} no longer exists");
+        let _ = app.emit("task-not-found", task_id);
+    }
+    Ok(())
+}
+
+fn open(app: &AppHandle, url: &Url) {
+    match parse(url) {
+        Ok(DeepLink::OpenTask(id)) => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = navigate_to_task(&handle, &id).await {
+                    log::error!("Failed to open task {id}: {e}");
+                }
+            });
+        }
+        Ok(DeepLink::NewTask(task)) => {
+            show_main_window(app);
+            let _ = app.emit("create-task-from-url", task);
+        }
+        // Links come from other apps, so a bad one is logged and otherwise ignored
+        Err(e) => log::warn!("Ignoring deep link {url}: {e}"),
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    // Installers register the scheme on Windows and Linux; this covers dev and portable builds.
+    // On macOS the plugin writes it into Info.plist at bundle time
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::error!("Failed to register the tada:// scheme: {e}");
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(&handle, &url);
+        }
+    });
+
+    // The link the app was launched with, if any
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            open(app, &url);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Blocked-by relationships between tasks.
+
+use sqlx::SqliteConnection;
+use tauri::{AppHandle, State};
+
+use crate::db::{new_id, now_ms};
+use crate::error::{Error, Result};
+use crate::events;
+use crate::AppState;
+
+/// Ids of the open tasks `task_id` is still waiting on.
+pub async fn incomplete_dependencies(conn: &mut SqliteConnection, task_id: &str) -> Result<Vec<String>> {
+    Ok(sqlx::query_scalar(
+        r#"
+        SELECT d.depends_on_id FROM task_dependencies d
+        JOIN tasks t ON t.id = d.depends_on_id
+        WHERE d.task_id = ? AND t.completed = 0 AND t.deleted_at IS NULL
+        "#,
+    )
+    .bind(task_id)
+    .fetch_all(conn)
+    .await?)
+}
+
+/// Whether making `task_id` wait on `depends_on_id` would close a cycle: walks
+/// everything `depends_on_id` waits on, looking for `task_id`.
+pub(crate) async fn would_cycle(conn: &mut SqliteConnection, task_id: &str, depends_on_id: &str) -> Result<bool> {
+    let cycle: Option<i64> = sqlx::query_scalar(
+        r#"
+        WITH RECURSIVE reachable(id) AS (
+            SELECT ?
+            UNION
+            SELECT d.depends_on_id FROM task_dependencies d JOIN reachable r ON d.task_id = r.id
+        )
+        SELECT 1 FROM reachable WHERE id = ?
+        "#,
+    )
+    .bind(depends_on_id)
+    .bind(task_id)
+    .fetch_optional(conn)
+    .await?;
+    Ok(cycle.is_some())
+}
+
+/// Makes `task_id` wait on `depends_on_id`. Rejected if `depends_on_id` already
+/// depends on `task_id`, directly or through other tasks.
+#[tauri::command]
+pub async fn add_dependency(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    depends_on_id: String,
+) -> Result<()> {
+    if task_id == depends_on_id {
+        return Err(Error::InvalidInput("A task can't depend on itself".into()));
+    }
+    let mut tx = state.db().begin().await?;
+    let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE id IN (?, ?) AND deleted_at IS NULL")
+        .bind(&task_id)
+        .bind(&depends_on_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if found < 2 {
+        return Err(Error::NotFound("Task".into()));
+    }
+
+    if would_cycle(&mut tx, &task_id, &depends_on_id).await? {
+        return Err(Error::InvalidInput("This dependency would create a cycle".into()));
+    }
+
+    sqlx::query(
+        "INSERT OR IGNORE INTO task_dependencies (id, task_id, depends_on_id, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(new_id())
+    .bind(&task_id)
+    .bind(&depends_on_id)
+    .bind(now_ms())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    events::tasks_changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_dependency(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    depends_on_id: String,
+) -> Result<()> {
+    sqlx::query("DELETE FROM task_dependencies WHERE task_id = ? AND depends_on_id = ?")
+        .bind(&task_id)
+        .bind(&depends_on_id)
+        .execute(&state.db())
+        .await?;
+    events::tasks_changed(&app);
+    Ok(())
+}
+
+/// Ids of open tasks that still have an open dependency, for graying them out.
+#[tauri::command]
+pub async fn blocked_tasks(state: State<'_, AppState>) -> Result<Vec<String>> {
+    Ok(sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT d.task_id FROM task_dependencies d
+        JOIN tasks t ON t.id = d.task_id
+        JOIN tasks dep ON dep.id = d.depends_on_id
+        WHERE t.completed = 0 AND dep.completed = 0 AND t.deleted_at IS NULL AND dep.deleted_at IS NULL
+        "#,
+    )
+    .fetch_all(&state.db())
+    .await?)
+}
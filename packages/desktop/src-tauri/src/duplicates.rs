@@ -0,0 +1,176 @@
+//! Finding tasks that look the same, and folding them into one.
+//!
+//! Titles match after trimming, lowercasing and collapsing whitespace. Merging
+//! moves the duplicates' subtasks, attachments and dependencies onto the task
+//! that's kept, unions the tags, and sends the duplicates to the trash, all in
+//! one transaction. With `mergeCompletesIfAny` on (the default), the kept task
+//! ends up completed if any of them was.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::commands::{delete_in, fetch_task, group_category};
+use crate::db::now_ms;
+use crate::dependencies::would_cycle;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::Task;
+use crate::subtasks::update_percentage;
+use crate::{settings, AppState};
+
+const COMPLETES_IF_ANY_KEY: &str = "mergeCompletesIfAny";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// The normalized title the tasks share.
+    pub title: String,
+    /// Set when the group was also matched on due date.
+    pub due_date: Option<i64>,
+    /// Oldest first, the natural one to keep.
+    pub tasks: Vec<Task>,
+}
+
+fn normalize(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Groups of two or more tasks outside the trash with the same normalized title,
+/// in one list or all of them. `same_due_date` also splits groups by due date.
+#[tauri::command]
+pub async fn find_duplicates(
+    state: State<'_, AppState>,
+    list_id: Option<String>,
+    same_due_date: Option<bool>,
+) -> Result<Vec<DuplicateGroup>> {
+    let same_due_date = same_due_date.unwrap_or(false);
+    let tasks: Vec<Task> = sqlx::query_as(
+        "SELECT * FROM tasks WHERE deleted_at IS NULL AND (?1 IS NULL OR list_id = ?1) ORDER BY created_at, id",
+    )
+    .bind(&list_id)
+    .fetch_all(&state.db())
+    .await?;
+
+    let mut groups: BTreeMap<(String, Option<i64>), Vec<Task>> = BTreeMap::new();
+    for task in tasks {
+        let title = normalize(&task.title);
+        if title.is_empty() {
+            continue;
+        }
+        let due_date = if same_due_date { task.due_date } else { None };
+        groups.entry((title, due_date)).or_default().push(task);
+    }
+    Ok(groups
+        .into_iter()
+        .filter(|(_, tasks)| tasks.len() > 1)
+        .map(|((title, due_date), tasks)| DuplicateGroup { title, due_date, tasks })
+        .collect())
+}
+
+/// Folds `merge_ids` into `keep_id` and trashes them. Returns the kept task.
+/// Dependencies move over too, except any that would close a cycle.
+#[tauri::command]
+pub async fn merge_tasks(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    keep_id: String,
+    merge_ids: Vec<String>,
+) -> Result<Task> {
+    if merge_ids.contains(&keep_id) {
+        return Err(Error::InvalidInput("A task can't be merged into itself".into()));
+    }
+    let completes_if_any = settings::get::<bool>(&state.db(), COMPLETES_IF_ANY_KEY).await?.unwrap_or(true);
+
+    let mut tx = state.db().begin().await?;
+    let keep = fetch_task(&mut tx, &keep_id).await?;
+    let mut tags = keep.tags.clone();
+    let mut completed_at = keep.completed.then_some(keep.completed_at).flatten();
+    let mut any_completed = keep.completed;
+    let now = now_ms();
+
+    for merge_id in &merge_ids {
+        let merged = fetch_task(&mut tx, merge_id).await?;
+        for tag in merged.tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        if merged.completed {
+            any_completed = true;
+            completed_at = completed_at.max(merged.completed_at);
+        }
+
+        // Appended after the kept task's own subtasks, in their own order
+        let offset: i64 =
+            sqlx::query_scalar(r#"SELECT COALESCE(MAX("order"), -1) + 1 FROM subtasks WHERE parent_id = ?"#)
+                .bind(&keep_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        sqlx::query(r#"UPDATE subtasks SET parent_id = ?, "order" = "order" + ?, updated_at = ? WHERE parent_id = ?"#)
+            .bind(&keep_id)
+            .bind(offset)
+            .bind(now)
+            .bind(merge_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE attachments SET task_id = ? WHERE task_id = ?")
+            .bind(&keep_id)
+            .bind(merge_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // One link at a time, so each is checked against the ones moved before it. Links the
+        // kept task already has stay as they are; the rest of the duplicate's go away below
+        let links: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT id, task_id, depends_on_id FROM task_dependencies WHERE ?1 IN (task_id, depends_on_id)")
+                .bind(merge_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        for (link_id, task_id, depends_on_id) in links {
+            let task_id = if task_id == *merge_id { keep_id.clone() } else { task_id };
+            let depends_on_id = if depends_on_id == *merge_id { keep_id.clone() } else { depends_on_id };
+            if task_id == depends_on_id {
+                continue;
+            }
+            if would_cycle(&mut tx, &task_id, &depends_on_id).await? {
+                log::info!("Dropped dependency {task_id} -> {depends_on_id} when merging {merge_id}; it would close a cycle");
+                continue;
+            }
+            sqlx::query("UPDATE OR IGNORE task_dependencies SET task_id = ?, depends_on_id = ? WHERE id = ?")
+                .bind(&task_id)
+                .bind(&depends_on_id)
+                .bind(&link_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("DELETE FROM task_dependencies WHERE task_id = depends_on_id OR ?1 IN (task_id, depends_on_id)")
+            .bind(merge_id)
+            .execute(&mut *tx)
+            .await?;
+
+        delete_in(&mut tx, merge_id).await?;
+    }
+
+    let completed = if completes_if_any { any_completed } else { keep.completed };
+    let completed_at = if completed { completed_at.or(Some(now)) } else { None };
+    sqlx::query(
+        "UPDATE tasks SET tags = ?, completed = ?, completed_at = ?, group_category = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(serde_json::to_string(&tags)?)
+    .bind(completed)
+    .bind(completed_at)
+    .bind(group_category(completed, keep.due_date, keep.start_date))
+    .bind(now)
+    .bind(&keep_id)
+    .execute(&mut *tx)
+    .await?;
+    update_percentage(&mut tx, &keep_id).await?;
+    let task = fetch_task(&mut tx, &keep_id).await?;
+    tx.commit().await?;
+
+    log::info!("Merged {} duplicates into task {keep_id}", merge_ids.len());
+    events::tasks_changed(&app);
+    Ok(task)
+}
@@ -0,0 +1,64 @@
+use serde::{Serialize, Serializer};
+
+/// Error type shared by every Rust-side command and background job.
+///
+/// Commands surface it to the frontend as a plain message string.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    Tauri(#[from] tauri::Error),
+    #[error(transparent)]
+    Notification(#[from] tauri_plugin_notification::Error),
+    #[cfg(desktop)]
+    #[error(transparent)]
+    Autostart(#[from] tauri_plugin_autostart::Error),
+    #[cfg(desktop)]
+    #[error(transparent)]
+    Updater(#[from] tauri_plugin_updater::Error),
+    #[error("{0}")]
+    Crypto(String),
+    #[error("This file is encrypted; enter its password to open it")]
+    PasswordRequired,
+    #[error("Incorrect password")]
+    IncorrectPassword,
+    #[error("Network request failed: {0}")]
+    Http(String),
+    #[error("Backup failed: {0}")]
+    Backup(String),
+    #[error(
+        "This database was last opened by a newer version of Tada (schema version {found}; this version \
+         supports up to {supported}). To keep your data safe it won't be opened here. Please update Tada."
+    )]
+    SchemaTooNew { found: i64, supported: i64 },
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidInput(String),
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        // URLs can carry credentials, so keep them out of the message
+        Self::Http(e.without_url().to_string())
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
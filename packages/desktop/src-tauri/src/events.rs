@@ -0,0 +1,68 @@
+//! Events the Rust side emits after changing data, so the UI can patch its store
+//! instead of showing stale rows.
+//!
+//! Single-row changes carry the row's id. Bulk changes (reorders, sync, imports)
+//! emit one `tasks-changed` instead of a flood of per-row events; it means "reload".
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Listener};
+
+use crate::models::Task;
+
+pub const TASK_CREATED: &str = "task-created";
+pub const TASK_UPDATED: &str = "task-updated";
+pub const TASK_DELETED: &str = "task-deleted";
+pub const LIST_UPDATED: &str = "list-updated";
+pub const TASKS_CHANGED: &str = "tasks-changed";
+
+/// Every event after which task-derived state (tray, badge, reminders) is stale.
+const TASK_EVENTS: &[&str] = &[TASK_CREATED, TASK_UPDATED, TASK_DELETED, TASKS_CHANGED];
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskEvent<'a> {
+    pub id: &'a str,
+    pub list_id: Option<&'a str>,
+}
+
+/// `id` is `None` when several lists changed at once.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListEvent<'a> {
+    pub id: Option<&'a str>,
+}
+
+fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        log::warn!("Failed to emit {event}: {e}");
+    }
+}
+
+pub fn task_created(app: &AppHandle, task: &Task) {
+    emit(app, TASK_CREATED, TaskEvent { id: &task.id, list_id: task.list_id.as_deref() });
+}
+
+pub fn task_updated(app: &AppHandle, task: &Task) {
+    emit(app, TASK_UPDATED, TaskEvent { id: &task.id, list_id: task.list_id.as_deref() });
+}
+
+pub fn task_deleted(app: &AppHandle, id: &str, list_id: Option<&str>) {
+    emit(app, TASK_DELETED, TaskEvent { id, list_id });
+}
+
+pub fn list_updated(app: &AppHandle, id: Option<&str>) {
+    emit(app, LIST_UPDATED, ListEvent { id });
+}
+
+pub fn tasks_changed(app: &AppHandle) {
+    emit(app, TASKS_CHANGED, ());
+}
+
+/// Runs `handler` after any task change, from the UI or the Rust side.
+pub fn on_tasks_changed(app: &AppHandle, handler: impl Fn(AppHandle) + Send + Sync + Clone + 'static) {
+    for event in TASK_EVENTS {
+        let handle = app.clone();
+        let handler = handler.clone();
+        app.listen(*event, move |_| handler(handle.clone()));
+    }
+}
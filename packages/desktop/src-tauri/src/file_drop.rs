@@ -0,0 +1,122 @@
+//! Files dropped onto the main window.
+//!
+//! By default each file becomes an inbox task named after it, with the file
+//! attached. With `fileDropBehavior` set to `attachToSelected` the paths are only
+//! forwarded, since the selected task lives in the frontend. Either way one
+//! `files-dropped` event describes the whole drop.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+use crate::commands::{self, TaskInput, INBOX_LIST_ID};
+use crate::error::Result;
+use crate::models::Task;
+use crate::{attachments, settings, AppState};
+
+const SETTINGS_KEY: &str = "fileDropBehavior";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum FileDropBehavior {
+    #[default]
+    CreateTask,
+    AttachToSelected,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SkippedFile {
+    path: String,
+    reason: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FilesDropped {
+    /// Files still to attach to the selected task; empty once tasks were created.
+    paths: Vec<String>,
+    created_task_ids: Vec<String>,
+    skipped: Vec<SkippedFile>,
+}
+
+pub(crate) fn on_drop(window: &Window, paths: Vec<PathBuf>) {
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = window.app_handle();
+        let pool = app.state::<AppState>().db();
+        let behavior = match settings::get::<FileDropBehavior>(&pool, SETTINGS_KEY).await {
+            Ok(behavior) => behavior.unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Failed to read {SETTINGS_KEY}, creating tasks: {e}");
+                FileDropBehavior::CreateTask
+            }
+        };
+
+        let mut skipped = Vec::new();
+        let mut files = Vec::new();
+        for path in paths {
+            if path.is_dir() {
+                skipped.push(SkippedFile { path: path.display().to_string(), reason: "Folders can't be attached".into() });
+            } else {
+                files.push(path);
+            }
+        }
+
+        let dropped = match behavior {
+            FileDropBehavior::AttachToSelected => FilesDropped {
+                paths: files.iter().map(|p| p.display().to_string()).collect(),
+                created_task_ids: Vec::new(),
+                skipped,
+            },
+            FileDropBehavior::CreateTask => {
+                let mut created = Vec::new();
+                for path in files {
+                    match create_task_for(app, &pool, &path).await {
+                        Ok(task) => created.push(task),
+                        Err(e) => skipped.push(SkippedFile { path: path.display().to_string(), reason: e.to_string() }),
+                    }
+                }
+                if !created.is_empty() {
+                    log::info!("Created {} tasks from dropped files", created.len());
+                    commands::announce_created(app, &pool, &created).await;
+                }
+                let created_task_ids = created.into_iter().map(|task| task.id).collect();
+                FilesDropped { paths: Vec::new(), created_task_ids, skipped }
+            }
+        };
+        let _ = window.emit("files-dropped", dropped);
+    });
+}
+
+/// An inbox task titled after the file, e.g. `Invoice 2024.pdf` → "Invoice 2024".
+async fn create_task_for(app: &AppHandle, pool: &SqlitePool, path: &std::path::Path) -> Result<Task> {
+    let title = path
+        .file_stem()
+        .or_else(|| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let input = TaskInput {
+        title,
+        content: None,
+        due_date: None,
+        list_id: Some(INBOX_LIST_ID.to_string()),
+        tags: Vec::new(),
+        priority: None,
+        recurrence_rule: None,
+        color: None,
+        start_date: None,
+    };
+    let mut tx = pool.begin().await?;
+    let task = commands::insert_task(&mut tx, &input).await?;
+    tx.commit().await?;
+
+    // A file that can't be attached (too large, unreadable) shouldn't leave an empty task behind
+    if let Err(e) = attachments::attach(app, pool, task.id.clone(), &path.to_string_lossy()).await {
+        sqlx::query("DELETE FROM tasks WHERE id = ?").bind(&task.id).execute(pool).await?;
+        return Err(e);
+    }
+    Ok(task)
+}
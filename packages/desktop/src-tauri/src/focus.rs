@@ -0,0 +1,222 @@
+//! Pomodoro-style focus sessions tied to a task.
+//!
+//! The timer runs in Rust, so it keeps going while the window is hidden to the tray.
+//! The active session is also stored in settings and resumed after a restart.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::Task;
+use crate::settings;
+use crate::AppState;
+
+const SETTINGS_KEY: &str = "focusSession";
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub task_id: String,
+    pub started_at: i64,
+    pub ends_at: i64,
+}
+
+impl FocusSession {
+    /// Planned length in whole minutes, what a completed session adds to `actual_minutes`.
+    fn minutes(&self) -> i64 {
+        (self.ends_at - self.started_at) / 60_000
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FocusTick<'a> {
+    task_id: &'a str,
+    remaining_ms: i64,
+    total_ms: i64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FocusComplete<'a> {
+    task_id: &'a str,
+    elapsed_ms: i64,
+}
+
+/// The one running session and the task driving its timer.
+#[derive(Default)]
+pub struct FocusState {
+    active: Mutex<Option<(FocusSession, JoinHandle<()>)>>,
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(FocusState::default());
+    resume(app);
+}
+
+/// Picks up the session stored in the database, as left when the app quit or the workspace changed.
+pub fn resume(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = handle.state::<AppState>();
+        match settings::get::<FocusSession>(&state.db(), SETTINGS_KEY).await {
+            Ok(Some(session)) => {
+                log::info!("Resuming focus session for task {}", session.task_id);
+                run(&handle, session);
+            }
+            Ok(None) => {}
+            Err(e) => log::error!("Failed to read focus session: {e}"),
+        }
+    });
+}
+
+/// Replaces the active session, cancelling the old timer.
+fn run(app: &AppHandle, session: FocusSession) {
+    let focus = app.state::<FocusState>();
+    // Held until the slot is filled, so an already-elapsed session can't finish before it's stored
+    let mut active = focus.active.lock().unwrap();
+    let handle = app.clone();
+    let timer_session = session.clone();
+    let timer = tauri::async_runtime::spawn(async move {
+        let session = timer_session;
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let remaining_ms = (session.ends_at - now_ms()).max(0);
+            let _ = handle.emit(
+                "focus-tick",
+                FocusTick {
+                    task_id: &session.task_id,
+                    remaining_ms,
+                    total_ms: session.ends_at - session.started_at,
+                },
+            );
+            if remaining_ms == 0 {
+                break;
+            }
+        }
+        if let Err(e) = finish(&handle, &session).await {
+            log::error!("Failed to finish focus session: {e}");
+        }
+    });
+
+    if let Some((_, timer)) = active.replace((session, timer)) {
+        timer.abort();
+    }
+}
+
+async fn finish(app: &AppHandle, session: &FocusSession) -> Result<()> {
+    // Only clear the slot if a newer session hasn't already taken it
+    {
+        let mut active = app.state::<FocusState>().active.lock().unwrap();
+        if active.as_ref().is_some_and(|(s, _)| s.started_at == session.started_at) {
+            *active = None;
+        }
+    }
+    let state = app.state::<AppState>();
+    // Credit the task in the same transaction that clears the stored session,
+    // so a session is counted once even if it's finished again after a restart
+    let mut tx = state.db().begin().await?;
+    let cleared = sqlx::query("DELETE FROM settings WHERE key = ?")
+        .bind(SETTINGS_KEY)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if cleared > 0 {
+        sqlx::query("UPDATE tasks SET actual_minutes = COALESCE(actual_minutes, 0) + ?, updated_at = ? WHERE id = ?")
+            .bind(session.minutes())
+            .bind(now_ms())
+            .bind(&session.task_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    let task: Option<Task> = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
+        .bind(&session.task_id)
+        .fetch_optional(&state.db())
+        .await?;
+    if let Some(task) = task.as_ref().filter(|_| cleared > 0) {
+        events::task_updated(app, task);
+    }
+    let mut builder = app.notification().builder().title("Focus session complete");
+    if let Some(task) = &task {
+        builder = builder.body(&task.title);
+    }
+    builder.show()?;
+    let _ = app.emit(
+        "focus-complete",
+        FocusComplete {
+            task_id: &session.task_id,
+            elapsed_ms: now_ms() - session.started_at,
+        },
+    );
+    log::info!("Focus session for task {} complete", session.task_id);
+    Ok(())
+}
+
+/// Starts a focus session on a task; any session already running is cancelled.
+#[tauri::command]
+pub async fn start_focus(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    minutes: u32,
+) -> Result<FocusSession> {
+    if !(1..=MAX_MINUTES).contains(&minutes) {
+        return Err(Error::InvalidInput(format!(
+            "Focus sessions must be between 1 and {MAX_MINUTES} minutes"
+        )));
+    }
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM tasks WHERE id = ? AND deleted_at IS NULL")
+        .bind(&task_id)
+        .fetch_optional(&state.db())
+        .await?;
+    if exists.is_none() {
+        return Err(Error::NotFound(format!("Task {task_id}")));
+    }
+
+    let started_at = now_ms();
+    let session = FocusSession {
+        task_id,
+        started_at,
+        ends_at: started_at + i64::from(minutes) * 60_000,
+    };
+    settings::set(&state.db(), SETTINGS_KEY, &session).await?;
+    run(&app, session.clone());
+    Ok(session)
+}
+
+/// Stops the timer but keeps the stored session, so `resume` can restart it.
+pub fn stop(app: &AppHandle) {
+    let previous = app.state::<FocusState>().active.lock().unwrap().take();
+    if let Some((session, timer)) = previous {
+        timer.abort();
+        log::info!("Stopped focus session for task {}", session.task_id);
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_focus(app: AppHandle, state: State<'_, AppState>) -> Result<()> {
+    stop(&app);
+    sqlx::query("DELETE FROM settings WHERE key = ?")
+        .bind(SETTINGS_KEY)
+        .execute(&state.db())
+        .await?;
+    Ok(())
+}
+
+/// The running session, so a reloaded webview can pick the progress ring back up.
+#[tauri::command]
+pub fn current_focus(focus: State<'_, FocusState>) -> Option<FocusSession> {
+    focus.active.lock().unwrap().as_ref().map(|(session, _)| session.clone())
+}
@@ -0,0 +1,131 @@
+//! The date group a task is filed under (`group_category`), in the local zone
+//! and with a configurable first day of the week.
+//!
+//! Groups are cut on local calendar days, so a due date's group only changes
+//! at local midnight; the stored values are recomputed then, and whenever the
+//! week start or timezone changes through `recompute_all_groups`.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use chrono::{Datelike, Days, Weekday};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::group_category;
+use crate::dates::{local_date, local_day_bounds};
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::{events, settings, AppState};
+
+/// The week start in effect, as days from Monday.
+static WEEK_START: AtomicU8 = AtomicU8::new(0);
+/// Slack after midnight, so the rerun can't land a hair before it.
+const AFTER_MIDNIGHT: Duration = Duration::from_secs(5);
+
+pub fn week_start() -> Weekday {
+    Weekday::try_from(WEEK_START.load(Ordering::Relaxed)).unwrap_or(Weekday::Mon)
+}
+
+/// `weekStart` from the `preferences` setting: a day name, or a number with
+/// 0 for Sunday as the frontend's date library counts. Monday when unset.
+async fn load_week_start(pool: &SqlitePool) -> Result<Weekday> {
+    let preferences = settings::get::<Value>(pool, "preferences").await?.unwrap_or_default();
+    let week_start = match preferences.get("weekStart") {
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .filter(|n| *n < 7)
+            .and_then(|n| Weekday::try_from(((n + 6) % 7) as u8).ok()),
+        Some(Value::String(name)) => name.parse().ok(),
+        _ => None,
+    };
+    let week_start = week_start.unwrap_or(Weekday::Mon);
+    WEEK_START.store(week_start.num_days_from_monday() as u8, Ordering::Relaxed);
+    Ok(week_start)
+}
+
+/// Group of an open task due at `due_ms`: `nodate`, `overdue`, `today`,
+/// `tomorrow`, `thisweek`, `nextweek` or `later`. Tomorrow wins over the next
+/// week when the week turns overnight.
+pub fn compute_group_category(due_ms: Option<i64>, now_ms: i64, week_start: Weekday) -> String {
+    let Some(due_ms) = due_ms else {
+        return "nodate".into();
+    };
+    let today = local_date(now_ms);
+    let due = local_date(due_ms);
+    let into_week = (today.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    let this_week = today - Days::new(u64::from(into_week));
+    let next_week = this_week + Days::new(7);
+    let after_next_week = next_week + Days::new(7);
+
+    let group = match (due - today).num_days() {
+        d if d < 0 => "overdue",
+        0 => "today",
+        1 => "tomorrow",
+        _ if due < next_week => "thisweek",
+        _ if due < after_next_week => "nextweek",
+        _ => "later",
+    };
+    group.into()
+}
+
+/// Rewrites every stored group that no longer matches, returning how many changed.
+/// `updated_at` is left alone: the group is derived, not an edit to sync.
+pub(crate) async fn recompute(pool: &SqlitePool) -> Result<u64> {
+    let week_start = load_week_start(pool).await?;
+    let rows: Vec<(String, bool, Option<i64>, Option<i64>, String)> =
+        sqlx::query_as("SELECT id, completed, due_date, start_date, group_category FROM tasks")
+            .fetch_all(pool)
+            .await?;
+    let mut tx = pool.begin().await?;
+    let mut changed = 0;
+    for (id, completed, due_date, start_date, stored) in rows {
+        let group = group_category(completed, due_date, start_date);
+        if group != stored {
+            sqlx::query("UPDATE tasks SET group_category = ? WHERE id = ?")
+                .bind(&group)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+            changed += 1;
+        }
+    }
+    tx.commit().await?;
+    log::debug!("Recomputed task groups with weeks starting {week_start}: {changed} changed");
+    Ok(changed)
+}
+
+/// Loads the week start and keeps groups current: once now, for changes made
+/// while the app was closed, then after every local midnight.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let state = handle.state::<AppState>();
+                let _permit = state.job_permit().await;
+                match recompute(&state.db()).await {
+                    Ok(0) => {}
+                    Ok(_) => events::tasks_changed(&handle),
+                    Err(e) => log::error!("Failed to recompute task groups: {e}"),
+                }
+            }
+            let now = now_ms();
+            let (_, midnight) = local_day_bounds(now);
+            let wait = Duration::from_millis(u64::try_from(midnight - now).unwrap_or_default());
+            tokio::time::sleep(wait + AFTER_MIDNIGHT).await;
+        }
+    });
+}
+
+/// Backfills every task's group, after a week-start or timezone change.
+/// Returns how many changed.
+#[tauri::command]
+pub async fn recompute_all_groups(app: AppHandle, state: State<'_, AppState>) -> Result<u64> {
+    let changed = recompute(&state.db()).await?;
+    if changed > 0 {
+        events::tasks_changed(&app);
+    }
+    Ok(changed)
+}
@@ -0,0 +1,315 @@
+//! Optional HTTP endpoint for adding tasks from iOS Shortcuts, scripts or webhooks.
+//!
+//! Off unless the `httpApi` setting enables it. It binds to the one configured
+//! address only: loopback by default, or a LAN interface to reach it from a
+//! phone. Every request must carry the configured token as
+//! `Authorization: Bearer <token>`, and the server won't start without one.
+//!
+//! ```text
+//! POST /tasks
+//! {"title": "Buy milk", "list": "Groceries", "due": "tomorrow 9am", "tags": ["errand"]}
+//! → 201 {"id": "…"}
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::State as Extract;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::oneshot;
+
+use crate::commands::{self, TaskInput, INBOX_LIST_ID};
+use crate::dates::start_of_local_day;
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::models::Task;
+use crate::{nlp_date, settings, AppState};
+
+const SETTINGS_KEY: &str = "httpApi";
+const DEFAULT_PORT: u16 = 7878;
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+const TOKEN_BYTES: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HttpApiConfig {
+    pub enabled: bool,
+    /// IP address of the interface to listen on; `127.0.0.1` keeps it on this machine.
+    pub bind_address: String,
+    pub port: u16,
+    pub token: String,
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: Ipv4Addr::LOCALHOST.to_string(),
+            port: DEFAULT_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+impl HttpApiConfig {
+    fn addr(&self) -> Result<SocketAddr> {
+        let ip: IpAddr = self
+            .bind_address
+            .trim()
+            .parse()
+            .map_err(|_| Error::InvalidInput(format!("'{}' is not an IP address", self.bind_address)))?;
+        Ok(SocketAddr::new(ip, self.port))
+    }
+}
+
+/// The running server: a trigger for graceful shutdown and the task serving it.
+#[derive(Default)]
+pub struct HttpApiServer(Mutex<Option<(oneshot::Sender<()>, JoinHandle<()>)>>);
+
+#[derive(Clone)]
+struct ServerState {
+    app: AppHandle,
+    token: String,
+}
+
+/// Body of `POST /tasks`.
+#[derive(Debug, Deserialize)]
+struct NewTask {
+    title: String,
+    /// List name or id; the Inbox when left out.
+    #[serde(default)]
+    list: Option<String>,
+    #[serde(default)]
+    due: Option<Due>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Epoch millis, or text: RFC 3339 (what Shortcuts sends for a date), `YYYY-MM-DD`,
+/// or anything the quick-add parser understands.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Due {
+    Millis(i64),
+    Text(String),
+}
+
+impl Due {
+    fn resolve(&self) -> Result<i64> {
+        let text = match self {
+            Due::Millis(ms) => return Ok(*ms),
+            Due::Text(text) => text.trim(),
+        };
+        if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+            return Ok(at.timestamp_millis());
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+            return Ok(start_of_local_day(date));
+        }
+        nlp_date::parse(text, now_ms())
+            .map(|parsed| parsed.due_date)
+            .ok_or_else(|| Error::InvalidInput(format!("Couldn't understand the due date '{text}'")))
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(HttpApiServer::default());
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let pool = handle.state::<AppState>().db();
+        match settings::get::<HttpApiConfig>(&pool, SETTINGS_KEY).await {
+            Ok(config) => {
+                if let Err(e) = apply(&handle, config.unwrap_or_default()).await {
+                    log::error!("Failed to start HTTP API: {e}");
+                }
+            }
+            Err(e) => log::error!("Failed to read HTTP API setting: {e}"),
+        }
+    });
+}
+
+/// Stops any running server, then starts one if the config enables it.
+async fn apply(app: &AppHandle, config: HttpApiConfig) -> Result<()> {
+    stop(app).await;
+    if !config.enabled {
+        return Ok(());
+    }
+    if config.token.is_empty() {
+        return Err(Error::InvalidInput("The HTTP API needs a token".into()));
+    }
+    let addr = config.addr()?;
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    let state = ServerState { app: app.clone(), token: config.token };
+    let router = Router::new().route("/tasks", post(create)).with_state(state);
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let server = tauri::async_runtime::spawn(async move {
+        let result = axum::serve(listener, router)
+            .with_graceful_shutdown(async {
+                let _ = signal.await;
+            })
+            .await;
+        if let Err(e) = result {
+            log::error!("HTTP API failed: {e}");
+        }
+    });
+    log::info!("Serving the HTTP API on http://{addr}");
+    *app.state::<HttpApiServer>().0.lock().unwrap() = Some((shutdown, server));
+    Ok(())
+}
+
+/// Signals the server to finish in-flight requests, giving up after a short wait.
+async fn stop(app: &AppHandle) {
+    let running = app.state::<HttpApiServer>().0.lock().unwrap().take();
+    if let Some((shutdown, server)) = running {
+        let _ = shutdown.send(());
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, server).await.is_err() {
+            log::warn!("HTTP API didn't stop in time");
+        }
+        log::info!("Stopped the HTTP API");
+    }
+}
+
+/// Called on app exit so the port is released before the process ends.
+pub fn shutdown(app: &AppHandle) {
+    if app.try_state::<HttpApiServer>().is_some() {
+        tauri::async_runtime::block_on(stop(app));
+    }
+}
+
+/// Compares in time independent of where the first difference is.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| constant_time_eq(given.trim().as_bytes(), token.as_bytes()))
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+async fn list_id(pool: &SqlitePool, list: &str) -> Result<String> {
+    sqlx::query_scalar(
+        r#"
+        SELECT id FROM lists
+        WHERE (id = ?1 OR name = ?1 COLLATE NOCASE) AND name != 'Trash' AND deleted_at IS NULL
+        ORDER BY id = ?1 DESC LIMIT 1
+        "#,
+    )
+    .bind(list.trim())
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| Error::NotFound(format!("List '{list}'")))
+}
+
+async fn insert(pool: &SqlitePool, body: NewTask) -> Result<Task> {
+    let list_id = match &body.list {
+        Some(list) => list_id(pool, list).await?,
+        None => INBOX_LIST_ID.to_string(),
+    };
+    let input = TaskInput {
+        title: body.title,
+        content: None,
+        // Left out, the list's defaults apply as they do in the app
+        due_date: body.due.as_ref().map(Due::resolve).transpose()?.map(Some),
+        list_id: Some(list_id),
+        tags: body.tags,
+        priority: None,
+        recurrence_rule: None,
+        color: None,
+        start_date: None,
+    };
+    let mut tx = pool.begin().await?;
+    let task = commands::insert_task(&mut tx, &input).await?;
+    tx.commit().await?;
+    Ok(task)
+}
+
+// The body is parsed by hand so nothing about it is looked at before the token checks out
+async fn create(Extract(server): Extract<ServerState>, headers: HeaderMap, body: Bytes) -> Response {
+    if !authorized(&headers, &server.token) {
+        let mut response = error_response(StatusCode::UNAUTHORIZED, "Missing or wrong bearer token");
+        response.headers_mut().insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Bearer"));
+        return response;
+    }
+    let body: NewTask = match serde_json::from_slice(&body) {
+        Ok(body) => body,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &format!("Invalid task: {e}")),
+    };
+    let state = server.app.state::<AppState>();
+    let _permit = state.job_permit().await;
+    let pool = state.db();
+    match insert(&pool, body).await {
+        Ok(task) => {
+            log::info!("Created task {} over the HTTP API", task.id);
+            commands::announce_created(&server.app, &pool, std::slice::from_ref(&task)).await;
+            (StatusCode::CREATED, Json(json!({ "id": task.id }))).into_response()
+        }
+        Err(e @ (Error::InvalidInput(_) | Error::NotFound(_))) => {
+            error_response(StatusCode::BAD_REQUEST, &e.to_string())
+        }
+        Err(e) => {
+            log::error!("HTTP API failed to create a task: {e}");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create the task")
+        }
+    }
+}
+
+fn new_token() -> Result<String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    getrandom::getrandom(&mut bytes).map_err(|e| Error::Crypto(e.to_string()))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[tauri::command]
+pub async fn get_http_api_config(state: State<'_, AppState>) -> Result<HttpApiConfig> {
+    Ok(settings::get::<HttpApiConfig>(&state.db(), SETTINGS_KEY).await?.unwrap_or_default())
+}
+
+/// Saves the config and starts, stops or rebinds the server to match. Enabling
+/// it without a token generates one.
+#[tauri::command]
+pub async fn set_http_api_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    mut config: HttpApiConfig,
+) -> Result<HttpApiConfig> {
+    if config.port == 0 {
+        return Err(Error::InvalidInput("The HTTP API port can't be 0".into()));
+    }
+    config.addr()?;
+    config.token = config.token.trim().to_string();
+    if config.enabled && config.token.is_empty() {
+        config.token = new_token()?;
+    }
+    settings::set(&state.db(), SETTINGS_KEY, &config).await?;
+    apply(&app, config.clone()).await?;
+    Ok(config)
+}
+
+/// Replaces the token, so anything still using the old one is locked out.
+#[tauri::command]
+pub async fn regenerate_http_api_token(app: AppHandle, state: State<'_, AppState>) -> Result<HttpApiConfig> {
+    let mut config = settings::get::<HttpApiConfig>(&state.db(), SETTINGS_KEY).await?.unwrap_or_default();
+    config.token = new_token()?;
+    settings::set(&state.db(), SETTINGS_KEY, &config).await?;
+    apply(&app, config.clone()).await?;
+    Ok(config)
+}
@@ -0,0 +1,135 @@
+use chrono::{TimeZone, Utc};
+use tauri::State;
+
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::models::Task;
+use crate::AppState;
+
+/// RFC 5545 limits content lines to 75 octets, excluding the line break.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// UTC date-time in the `19970714T173000Z` form.
+fn format_utc(ms: i64) -> String {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .map(|d| d.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+/// Escapes a TEXT property value.
+fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Appends a content line, folding it at 75 octets without splitting a UTF-8 sequence.
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts toward their length
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn ical_priority(priority: Option<i64>) -> Option<u8> {
+    // Tada's P1..P3 onto iCalendar's high (1) / medium (5) / low (9)
+    match priority? {
+        1 => Some(1),
+        2 => Some(5),
+        3 => Some(9),
+        _ => None,
+    }
+}
+
+pub fn render_calendar(tasks: &[Task]) -> String {
+    let mut out = String::new();
+    let stamp = format_utc(now_ms());
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//Loadshine//Tada//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+
+    for task in tasks {
+        let Some(due) = task.due_date else {
+            continue;
+        };
+        push_line(&mut out, "BEGIN:VTODO");
+        push_line(&mut out, &format!("UID:{}", task.id));
+        push_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        // DUE has to come after DTSTART, so an equal pair is never written
+        match task.start_date.filter(|start| *start < due) {
+            Some(start) => {
+                push_line(&mut out, &format!("DTSTART:{}", format_utc(start)));
+                push_line(&mut out, &format!("DUE:{}", format_utc(due)));
+            }
+            // An RRULE counts from DTSTART, which then carries the due time on its own
+            None if task.recurrence_rule.is_some() => {
+                push_line(&mut out, &format!("DTSTART:{}", format_utc(due)));
+                push_line(&mut out, "DURATION:PT0S");
+            }
+            None => push_line(&mut out, &format!("DUE:{}", format_utc(due))),
+        }
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&task.title)));
+        if let Some(content) = task.content.as_deref().filter(|c| !c.trim().is_empty()) {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(content)));
+        }
+        if !task.tags.is_empty() {
+            let tags: Vec<_> = task.tags.iter().map(|t| escape_text(t)).collect();
+            push_line(&mut out, &format!("CATEGORIES:{}", tags.join(",")));
+        }
+        if let Some(priority) = ical_priority(task.priority) {
+            push_line(&mut out, &format!("PRIORITY:{priority}"));
+        }
+        if let Some(rule) = &task.recurrence_rule {
+            push_line(&mut out, &format!("RRULE:{rule}"));
+        }
+        if task.completed {
+            push_line(&mut out, "STATUS:COMPLETED");
+            if let Some(completed_at) = task.completed_at {
+                push_line(&mut out, &format!("COMPLETED:{}", format_utc(completed_at)));
+            }
+        } else {
+            push_line(&mut out, "STATUS:NEEDS-ACTION");
+        }
+        push_line(&mut out, &format!("LAST-MODIFIED:{}", format_utc(task.updated_at)));
+        push_line(&mut out, "END:VTODO");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Writes every task with a due date as a VTODO, optionally limited to one list.
+#[tauri::command]
+pub async fn export_ical(state: State<'_, AppState>, path: String, list_id: Option<String>) -> Result<()> {
+    let tasks: Vec<Task> = sqlx::query_as(
+        r#"
+        SELECT * FROM tasks
+        WHERE due_date IS NOT NULL AND list_name != 'Trash' AND deleted_at IS NULL AND (?1 IS NULL OR list_id = ?1)
+        ORDER BY due_date
+        "#,
+    )
+    .bind(&list_id)
+    .fetch_all(&state.db())
+    .await?;
+
+    std::fs::write(path, render_calendar(&tasks))?;
+    Ok(())
+}
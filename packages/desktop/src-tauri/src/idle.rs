@@ -0,0 +1,152 @@
+//! Holds reminders back while nobody is at the machine.
+//!
+//! With `suppressWhileIdle` on, a reminder that comes due after the user has
+//! been idle for `idleThresholdMinutes` (default 5) is recorded in
+//! `held_reminders` instead of shown, so it survives a restart. On return they
+//! arrive together: a single one as usual, several as one "while you were away"
+//! notification plus `reminders-missed` listing the tasks for the UI.
+//!
+//! There is no portable lock-screen event, but a locked session stops input, so
+//! locking counts as idle once the threshold passes. Mobile never counts as away.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::reminders::NotificationStyle;
+use crate::{settings, AppState};
+
+const SUPPRESS_KEY: &str = "suppressWhileIdle";
+const THRESHOLD_KEY: &str = "idleThresholdMinutes";
+const DEFAULT_THRESHOLD_MINUTES: u64 = 5;
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Default)]
+pub struct Presence {
+    away: AtomicBool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemindersMissed<'a> {
+    task_ids: &'a [String],
+}
+
+/// Whether reminders should be held right now.
+pub fn is_away(app: &AppHandle) -> bool {
+    app.try_state::<Presence>().is_some_and(|presence| presence.away.load(Ordering::Relaxed))
+}
+
+#[cfg(desktop)]
+fn idle_for() -> Option<Duration> {
+    match user_idle::UserIdle::get_time() {
+        Ok(idle) => Some(idle.duration()),
+        Err(e) => {
+            log::debug!("Couldn't read the idle time: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(mobile)]
+fn idle_for() -> Option<Duration> {
+    None
+}
+
+async fn check(app: &AppHandle) -> Result<bool> {
+    let pool = app.state::<AppState>().db();
+    if !settings::get::<bool>(&pool, SUPPRESS_KEY).await?.unwrap_or(false) {
+        return Ok(false);
+    }
+    let minutes = settings::get::<u64>(&pool, THRESHOLD_KEY).await?.unwrap_or(DEFAULT_THRESHOLD_MINUTES);
+    Ok(idle_for().is_some_and(|idle| idle >= Duration::from_secs(minutes * 60)))
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(Presence::default());
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = handle.state::<AppState>();
+            let _permit = state.job_permit().await;
+            let away = match check(&handle).await {
+                Ok(away) => away,
+                Err(e) => {
+                    log::error!("Failed to check for idleness: {e}");
+                    continue;
+                }
+            };
+            let was_away = handle.state::<Presence>().away.swap(away, Ordering::Relaxed);
+            if away != was_away {
+                log::info!("{}", if away { "User is away; holding reminders" } else { "User is back" });
+            }
+            // Also delivers whatever was still held when the app last quit
+            if !away {
+                if let Err(e) = deliver_held(&handle).await {
+                    log::error!("Failed to deliver held reminders: {e}");
+                }
+            }
+        }
+    });
+}
+
+/// Keeps a reminder that already passed its gate for later.
+pub async fn hold(pool: &SqlitePool, task_id: &str) -> Result<()> {
+    sqlx::query("INSERT OR IGNORE INTO held_reminders (task_id, held_at) VALUES (?, ?)")
+        .bind(task_id)
+        .bind(now_ms())
+        .execute(pool)
+        .await?;
+    log::info!("Holding reminder for task {task_id} until the user is back");
+    Ok(())
+}
+
+async fn deliver_held(app: &AppHandle) -> Result<()> {
+    let pool = app.state::<AppState>().db();
+    let mut tx = pool.begin().await?;
+    // Tasks finished or deleted meanwhile don't need reminding
+    let held: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT h.task_id, t.title FROM held_reminders h
+        JOIN tasks t ON t.id = h.task_id
+        WHERE t.completed = 0 AND t.deleted_at IS NULL
+        ORDER BY h.held_at
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM held_reminders").execute(&mut *tx).await?;
+    tx.commit().await?;
+
+    if held.is_empty() {
+        return Ok(());
+    }
+    let style = NotificationStyle::load(&pool).await;
+    let task_ids: Vec<String> = match held.as_slice() {
+        [(task_id, title)] => {
+            style.apply(app.notification().builder()).title("Tada").body(title).extra("taskId", task_id).show()?;
+            vec![task_id.clone()]
+        }
+        _ => {
+            let task_ids: Vec<String> = held.into_iter().map(|(task_id, _)| task_id).collect();
+            style
+                .apply(app.notification().builder())
+                .title("Tada")
+                .body(format!("{} reminders while you were away", task_ids.len()))
+                .extra("taskIds", &task_ids)
+                .show()?;
+            task_ids
+        }
+    };
+    log::info!("Delivered {} held reminders", task_ids.len());
+    let _ = app.emit("reminders-missed", RemindersMissed { task_ids: &task_ids });
+    Ok(())
+}
@@ -0,0 +1,508 @@
+//! Importers for other to-do apps' exports, and for Markdown checklists.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::commands::{self, TaskInput};
+use crate::dates::start_of_local_day;
+use crate::db::{new_id, now_ms};
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::Task;
+use crate::subtasks::update_percentage;
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExternalFormat {
+    /// The CSV from TickTick's Settings → Account → Backup.
+    TickTickCsv,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRow {
+    /// 1-based line in the source file.
+    pub line: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub lists: u64,
+    pub tasks: u64,
+    pub subtasks: u64,
+    pub skipped: Vec<SkippedRow>,
+    /// The new tasks as committed, for announcing them.
+    #[serde(skip)]
+    pub created: Vec<Task>,
+}
+
+/// TickTick priorities are 0 (none), 1 (low), 3 (medium) and 5 (high); ours are 1 (high) to 3 (low).
+fn ticktick_priority(raw: &str) -> Option<i64> {
+    match raw.trim().parse::<i64>().ok()? {
+        4.. => Some(1),
+        2..=3 => Some(2),
+        1 => Some(3),
+        _ => None,
+    }
+}
+
+/// TickTick writes `2024-03-01T09:00:00+0000`. All-day dates keep their calendar
+/// day in the local zone instead of shifting with the offset.
+fn ticktick_date(raw: &str, all_day: bool) -> Option<i64> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if let Ok(dt) = DateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%z") {
+        return Some(if all_day { start_of_local_day(dt.date_naive()) } else { dt.timestamp_millis() });
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok().map(start_of_local_day)
+}
+
+/// One task row of a TickTick export.
+struct TickTickRow {
+    line: u64,
+    list: String,
+    title: String,
+    content: String,
+    is_checklist: bool,
+    tags: Vec<String>,
+    due_date: Option<i64>,
+    start_date: Option<i64>,
+    priority: Option<i64>,
+    completed: bool,
+    created_at: Option<i64>,
+    completed_at: Option<i64>,
+    task_id: String,
+    parent_id: String,
+}
+
+fn read_ticktick(path: &str, report: &mut ImportReport) -> Result<Vec<TickTickRow>> {
+    let mut reader = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_path(path)?;
+    let mut columns: Option<HashMap<String, usize>> = None;
+    let mut rows = Vec::new();
+
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |p| p.line());
+        // The file starts with a few lines of metadata before the real header
+        let Some(columns) = &columns else {
+            if record.iter().any(|f| f == "Title") && record.iter().any(|f| f == "List Name") {
+                columns = Some(record.iter().enumerate().map(|(i, f)| (f.to_string(), i)).collect());
+            }
+            continue;
+        };
+        let field = |name: &str| columns.get(name).and_then(|&i| record.get(i)).unwrap_or("").trim();
+
+        let title = field("Title");
+        if title.is_empty() {
+            report.skipped.push(SkippedRow { line, reason: "Missing title".into() });
+            continue;
+        }
+        // Notes and habits come through the same export
+        let kind = field("Kind");
+        if !kind.is_empty() && !matches!(kind, "TEXT" | "CHECKLIST") {
+            report.skipped.push(SkippedRow { line, reason: format!("Unsupported item kind '{kind}'") });
+            continue;
+        }
+        let all_day = field("Is All Day").eq_ignore_ascii_case("true");
+        rows.push(TickTickRow {
+            line,
+            list: Some(field("List Name")).filter(|l| !l.is_empty()).unwrap_or("Inbox").to_string(),
+            title: title.to_string(),
+            content: field("Content").to_string(),
+            is_checklist: kind == "CHECKLIST" || field("Is Check list") == "Y",
+            tags: field("Tags").split(',').map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect(),
+            due_date: ticktick_date(field("Due Date"), all_day),
+            // TickTick repeats the due date as the start of single-day tasks; only ranges defer
+            start_date: ticktick_date(field("Start Date"), all_day).filter(|_| field("Start Date") != field("Due Date")),
+            priority: ticktick_priority(field("Priority")),
+            // 0 is open; 1 completed, 2 archived
+            completed: matches!(field("Status"), "1" | "2"),
+            created_at: ticktick_date(field("Created Time"), false),
+            completed_at: ticktick_date(field("Completed Time"), false),
+            task_id: field("taskId").to_string(),
+            parent_id: field("parentId").to_string(),
+        });
+    }
+    if columns.is_none() {
+        return Err(Error::InvalidInput("This doesn't look like a TickTick backup".into()));
+    }
+    Ok(rows)
+}
+
+/// Checklist content lists items as `▫item` (open) and `▪item` (done), one per line.
+fn checklist_items(content: &str) -> (Vec<(String, bool)>, String) {
+    let mut items = Vec::new();
+    let mut notes = Vec::new();
+    for line in content.lines() {
+        if let Some(item) = line.trim_start().strip_prefix('▫') {
+            items.push((item.trim().to_string(), false));
+        } else if let Some(item) = line.trim_start().strip_prefix('▪') {
+            items.push((item.trim().to_string(), true));
+        } else {
+            notes.push(line);
+        }
+    }
+    (items, notes.join("\n").trim().to_string())
+}
+
+/// Finds a list by name, creating it at the end when it doesn't exist.
+async fn ensure_list(
+    tx: &mut Transaction<'_, Sqlite>,
+    cache: &mut HashMap<String, String>,
+    name: &str,
+    report: &mut ImportReport,
+) -> Result<String> {
+    if let Some(id) = cache.get(name) {
+        return Ok(id.clone());
+    }
+    let existing: Option<String> = sqlx::query_scalar("SELECT id FROM lists WHERE name = ? COLLATE NOCASE AND deleted_at IS NULL")
+        .bind(name)
+        .fetch_optional(&mut **tx)
+        .await?;
+    let id = match existing {
+        Some(id) => id,
+        None => {
+            let id = new_id();
+            let now = now_ms();
+            sqlx::query(
+                r#"
+                INSERT INTO lists (id, name, "order", created_at, updated_at)
+                VALUES (?, ?, (SELECT COALESCE(MAX("order"), 0) + 1 FROM lists), ?, ?)
+                "#,
+            )
+            .bind(&id)
+            .bind(name)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+            report.lists += 1;
+            id
+        }
+    };
+    cache.insert(name.to_string(), id.clone());
+    Ok(id)
+}
+
+/// The imported tasks as they ended up, once completion and subtasks are in.
+async fn fetch_created(tx: &mut Transaction<'_, Sqlite>, ids: &[String]) -> Result<Vec<Task>> {
+    let mut tasks = Vec::with_capacity(ids.len());
+    for id in ids {
+        tasks.push(commands::fetch_task(tx, id).await?);
+    }
+    Ok(tasks)
+}
+
+async fn insert_subtask(
+    tx: &mut Transaction<'_, Sqlite>,
+    parent_id: &str,
+    title: &str,
+    completed: bool,
+    due_date: Option<i64>,
+    order: i64,
+) -> Result<()> {
+    let now = now_ms();
+    sqlx::query(
+        r#"
+        INSERT INTO subtasks (id, parent_id, title, completed, completed_at, due_date, "order", created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(new_id())
+    .bind(parent_id)
+    .bind(title)
+    .bind(completed)
+    .bind(completed.then_some(now))
+    .bind(due_date)
+    .bind(order)
+    .bind(now)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn import_ticktick(tx: &mut Transaction<'_, Sqlite>, path: &str) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+    let rows = read_ticktick(path, &mut report)?;
+    let mut lists = HashMap::new();
+    // TickTick id → our id, for child rows that point at their parent
+    let mut imported: HashMap<String, String> = HashMap::new();
+    let mut child_rows = Vec::new();
+    let mut created_ids = Vec::new();
+
+    for row in rows {
+        if !row.parent_id.is_empty() {
+            child_rows.push(row);
+            continue;
+        }
+        let list_id = ensure_list(tx, &mut lists, &row.list, &mut report).await?;
+        let (items, notes) = if row.is_checklist { checklist_items(&row.content) } else { (Vec::new(), row.content.clone()) };
+        let input = TaskInput {
+            title: row.title.clone(),
+            content: Some(notes).filter(|n| !n.is_empty()),
+            // The export says what each task had, so list defaults don't apply
+            due_date: Some(row.due_date),
+            list_id: Some(list_id),
+            tags: row.tags.clone(),
+            priority: Some(row.priority),
+            recurrence_rule: None,
+            color: None,
+            start_date: row.start_date,
+        };
+        let task = commands::insert_task(tx, &input).await?;
+
+        let created_at = row.created_at.unwrap_or(task.created_at);
+        sqlx::query(
+            r#"
+            UPDATE tasks
+            SET created_at = ?, completed = ?, completed_at = ?,
+                complete_percentage = CASE WHEN ? THEN 100 ELSE complete_percentage END,
+                group_category = CASE WHEN ? THEN 'nodate' ELSE group_category END
+            WHERE id = ?
+            "#,
+        )
+        .bind(created_at)
+        .bind(row.completed)
+        .bind(if row.completed { row.completed_at.or(Some(task.updated_at)) } else { None })
+        .bind(row.completed)
+        .bind(row.completed)
+        .bind(&task.id)
+        .execute(&mut **tx)
+        .await?;
+        report.tasks += 1;
+        created_ids.push(task.id.clone());
+
+        for (order, (title, done)) in items.iter().enumerate() {
+            insert_subtask(tx, &task.id, title, *done, None, order as i64).await?;
+            report.subtasks += 1;
+        }
+        if !row.task_id.is_empty() {
+            imported.insert(row.task_id.clone(), task.id);
+        }
+    }
+
+    let mut next_order: HashMap<String, i64> = HashMap::new();
+    for row in child_rows {
+        let Some(parent_id) = imported.get(&row.parent_id) else {
+            report.skipped.push(SkippedRow { line: row.line, reason: "Parent task not found".into() });
+            continue;
+        };
+        let order = next_order.entry(parent_id.clone()).or_insert(1000);
+        insert_subtask(tx, parent_id, &row.title, row.completed, None, *order).await?;
+        *order += 1;
+        report.subtasks += 1;
+    }
+    report.created = fetch_created(tx, &created_ids).await?;
+    Ok(report)
+}
+
+/// A line of a Markdown checklist that means something to the importer.
+#[derive(Debug, PartialEq)]
+enum MarkdownLine {
+    Heading(String),
+    Item {
+        /// Leading whitespace in columns, a tab counting as four.
+        indent: usize,
+        completed: bool,
+        title: String,
+        tags: Vec<String>,
+        due_date: Option<NaiveDate>,
+    },
+}
+
+/// Reads `## Heading` and `- [ ] item` / `- [x] item` lines (`*` and `+`
+/// bullets too); anything else is `None`. Inline `#tags` and a trailing
+/// `(due: YYYY-MM-DD)` are taken out of the title.
+fn parse_markdown_line(line: &str) -> Option<MarkdownLine> {
+    let trimmed = line.trim_start();
+    if let Some(heading) = trimmed.strip_prefix('#') {
+        let text = heading.trim_start_matches('#');
+        // `#tag` at the start of a line is neither a heading nor a checkbox
+        if !text.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let text = text.trim().trim_end_matches('#').trim();
+        return (!text.is_empty()).then(|| MarkdownLine::Heading(text.to_string()));
+    }
+
+    let indent: usize = line[..line.len() - trimmed.len()].chars().map(|c| if c == '\t' { 4 } else { 1 }).sum();
+    let rest = trimmed.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?.trim_start();
+    let completed = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let mut text = rest[3..].trim();
+
+    let mut due_date = None;
+    if let Some(open) = text.rfind('(').filter(|_| text.ends_with(')')) {
+        let inner = text[open + 1..text.len() - 1].trim();
+        let date = inner
+            .get(..4)
+            .filter(|key| key.eq_ignore_ascii_case("due:"))
+            .and_then(|_| NaiveDate::parse_from_str(inner[4..].trim(), "%Y-%m-%d").ok());
+        if date.is_some() {
+            due_date = date;
+            text = text[..open].trim_end();
+        }
+    }
+
+    let mut tags = Vec::new();
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        // `#42` is an issue number rather than a tag
+        match word.strip_prefix('#').filter(|tag| tag.starts_with(|c: char| c.is_alphabetic())) {
+            Some(tag) => tags.push(tag.trim_end_matches([',', '.', ';', ':']).to_string()),
+            None => words.push(word),
+        }
+    }
+    Some(MarkdownLine::Item { indent, completed, title: words.join(" "), tags, due_date })
+}
+
+/// Imports a Markdown checklist. Each checkbox becomes a task, and indented
+/// checkboxes below it its subtasks; tasks only have one level of subtasks,
+/// so deeper ones are flattened into it. Other lines are passed over.
+///
+/// Everything goes into `list_id` when given. Without it, each heading starts
+/// a list of that name (found or created) and items before the first go to the inbox.
+async fn import_markdown_file(
+    tx: &mut Transaction<'_, Sqlite>,
+    path: &str,
+    list_id: Option<&str>,
+) -> Result<ImportReport> {
+    let text = std::fs::read_to_string(path)?;
+    let mut report = ImportReport::default();
+    let mut lists = HashMap::new();
+    let mut current_list = list_id.unwrap_or(commands::INBOX_LIST_ID).to_string();
+    // The task indented items attach to, with its indent and next subtask order
+    let mut parent: Option<(usize, String, i64)> = None;
+    let mut with_subtasks: Vec<String> = Vec::new();
+    let mut created_ids = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line_number = number as u64 + 1;
+        match parse_markdown_line(line) {
+            None => {}
+            Some(MarkdownLine::Heading(heading)) => {
+                parent = None;
+                if list_id.is_none() {
+                    current_list = ensure_list(tx, &mut lists, &heading, &mut report).await?;
+                }
+            }
+            Some(MarkdownLine::Item { title, .. }) if title.is_empty() => {
+                report.skipped.push(SkippedRow { line: line_number, reason: "Missing title".into() });
+            }
+            Some(MarkdownLine::Item { indent, completed, title, tags, due_date }) => {
+                let due_date = due_date.map(start_of_local_day);
+                if let Some((_, parent_id, order)) = parent.as_mut().filter(|(i, _, _)| indent > *i) {
+                    // Subtasks have no tags of their own, so those stay in the title
+                    let title = tags.iter().fold(title, |title, tag| format!("{title} #{tag}"));
+                    insert_subtask(tx, parent_id, &title, completed, due_date, *order).await?;
+                    if *order == 0 {
+                        with_subtasks.push(parent_id.clone());
+                    }
+                    *order += 1;
+                    report.subtasks += 1;
+                    continue;
+                }
+
+                let input = TaskInput {
+                    title,
+                    content: None,
+                    due_date: Some(due_date),
+                    list_id: Some(current_list.clone()),
+                    tags,
+                    priority: None,
+                    recurrence_rule: None,
+                    color: None,
+                    start_date: None,
+                };
+                let task = commands::insert_task(tx, &input).await?;
+                if completed {
+                    sqlx::query(
+                        r#"
+                        UPDATE tasks
+                        SET completed = 1, completed_at = ?, complete_percentage = 100, group_category = 'nodate'
+                        WHERE id = ?
+                        "#,
+                    )
+                    .bind(task.updated_at)
+                    .bind(&task.id)
+                    .execute(&mut **tx)
+                    .await?;
+                }
+                report.tasks += 1;
+                created_ids.push(task.id.clone());
+                parent = Some((indent, task.id, 0));
+            }
+        }
+    }
+
+    for task_id in &with_subtasks {
+        update_percentage(tx, task_id).await?;
+    }
+    report.created = fetch_created(tx, &created_ids).await?;
+    Ok(report)
+}
+
+/// Imports a Markdown checklist file in one transaction; see `import_markdown_file`.
+#[tauri::command]
+pub async fn import_markdown(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    list_id: Option<String>,
+) -> Result<ImportReport> {
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let report = import_markdown_file(&mut tx, &path, list_id.as_deref()).await?;
+    tx.commit().await?;
+    log::info!(
+        "Imported Markdown checklist {path}: {} lists, {} tasks, {} subtasks, {} skipped",
+        report.lists,
+        report.tasks,
+        report.subtasks,
+        report.skipped.len()
+    );
+    commands::announce_created(&app, &pool, &report.created).await;
+    if report.lists > 0 {
+        events::list_updated(&app, None);
+    }
+    Ok(report)
+}
+
+/// Imports another app's export in one transaction; nothing is written if the file fails to parse.
+#[tauri::command]
+pub async fn import_external(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    format: ExternalFormat,
+    path: String,
+) -> Result<ImportReport> {
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let report = match format {
+        ExternalFormat::TickTickCsv => import_ticktick(&mut tx, &path).await?,
+    };
+    tx.commit().await?;
+    log::info!(
+        "Imported {format:?}: {} lists, {} tasks, {} subtasks, {} skipped",
+        report.lists,
+        report.tasks,
+        report.subtasks,
+        report.skipped.len()
+    );
+    commands::announce_created(&app, &pool, &report.created).await;
+    events::list_updated(&app, None);
+    Ok(report)
+}
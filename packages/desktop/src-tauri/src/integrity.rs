@@ -0,0 +1,95 @@
+//! Finds and repairs rows left dangling while foreign keys weren't enforced.
+
+use serde::Serialize;
+use sqlx::{Sqlite, SqliteConnection, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::events;
+use crate::AppState;
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    /// Rows reported by `PRAGMA foreign_key_check`, across all tables.
+    pub foreign_key_violations: u64,
+    pub orphaned_subtasks: u64,
+    /// Tasks whose `list_id` names a list that no longer exists.
+    pub dangling_list_refs: u64,
+    /// Summaries whose `task_ids` mention deleted tasks. Reported only; they keep their text.
+    pub summaries_with_missing_tasks: u64,
+}
+
+async fn count(conn: &mut SqliteConnection, sql: &str) -> Result<u64> {
+    let n: i64 = sqlx::query_scalar(sql).fetch_one(conn).await?;
+    Ok(n as u64)
+}
+
+async fn inspect(conn: &mut SqliteConnection) -> Result<IntegrityReport> {
+    let violations = sqlx::query("PRAGMA foreign_key_check").fetch_all(&mut *conn).await?;
+    Ok(IntegrityReport {
+        foreign_key_violations: violations.len() as u64,
+        orphaned_subtasks: count(
+            conn,
+            "SELECT COUNT(*) FROM subtasks s WHERE NOT EXISTS (SELECT 1 FROM tasks t WHERE t.id = s.parent_id)",
+        )
+        .await?,
+        dangling_list_refs: count(
+            conn,
+            r#"
+            SELECT COUNT(*) FROM tasks t
+            WHERE t.list_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM lists l WHERE l.id = t.list_id)
+            "#,
+        )
+        .await?,
+        summaries_with_missing_tasks: count(
+            conn,
+            r#"
+            SELECT COUNT(DISTINCT s.id)
+            FROM summaries s, json_each(CASE WHEN json_valid(s.task_ids) THEN s.task_ids ELSE '[]' END) j
+            WHERE NOT EXISTS (SELECT 1 FROM tasks t WHERE t.id = j.value)
+            "#,
+        )
+        .await?,
+    })
+}
+
+/// Deletes orphaned subtasks and clears dangling list references, returning what was fixed.
+async fn repair(tx: &mut Transaction<'_, Sqlite>) -> Result<IntegrityReport> {
+    let mut report = inspect(tx).await?;
+    report.orphaned_subtasks = sqlx::query(
+        "DELETE FROM subtasks WHERE NOT EXISTS (SELECT 1 FROM tasks t WHERE t.id = subtasks.parent_id)",
+    )
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    // Matches ON DELETE SET NULL; list_name stays, so the task still shows under its old list name
+    report.dangling_list_refs = sqlx::query(
+        r#"
+        UPDATE tasks SET list_id = NULL, updated_at = ?
+        WHERE list_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM lists l WHERE l.id = tasks.list_id)
+        "#,
+    )
+    .bind(now_ms())
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn check_integrity(state: State<'_, AppState>) -> Result<IntegrityReport> {
+    let mut conn = state.db().acquire().await?;
+    inspect(&mut conn).await
+}
+
+#[tauri::command]
+pub async fn repair_integrity(app: AppHandle, state: State<'_, AppState>) -> Result<IntegrityReport> {
+    let mut tx = state.db().begin().await?;
+    let report = repair(&mut tx).await?;
+    tx.commit().await?;
+    log::info!("Repaired database integrity: {report:?}");
+    events::tasks_changed(&app);
+    Ok(report)
+}
@@ -1,211 +1,450 @@
-use tauri_plugin_sql::{Migration, MigrationKind};
-use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
-    Manager, WindowEvent,
-    image::Image,
-};
+mod about;
+mod agenda;
+mod ai;
+#[cfg(target_os = "macos")]
+mod app_menu;
+mod archive;
+mod attachments;
+mod autostart;
+mod backup;
+mod badge;
+mod board;
+mod capture;
+mod carry_over;
+#[cfg(desktop)]
+pub mod cli;
+mod close_behavior;
+mod collation;
+mod commands;
+mod counts;
+mod crypto;
+mod daily_planning;
+mod dates;
+mod db;
+mod deep_link;
+mod diagnostics;
+mod dependencies;
+mod duplicates;
+mod error;
+mod events;
+mod file_drop;
+mod focus;
+mod grouping;
+mod http_api;
+mod ical;
+mod idle;
+mod import;
+mod integrity;
+mod list_share;
+mod list_windows;
+mod logging;
+mod maintenance;
+mod markdown;
+mod metrics;
+mod migrations;
+mod models;
+mod nlp_date;
+mod nlp_recurrence;
+mod palette;
+mod paths;
+mod power;
+mod query;
+#[cfg(desktop)]
+mod quick_add;
+mod recovery;
+mod recurrence;
+mod reminders;
+mod report;
+mod search;
+mod settings;
+mod snoozes;
+mod sql_console;
+mod stats;
+mod streaks;
+mod subtasks;
+mod summaries;
+mod sync;
+mod tags;
+mod task_queries;
+mod templates;
+mod theme;
+mod trash;
+mod tray;
+mod undo;
+#[cfg(desktop)]
+mod updater;
+mod wake;
+mod webhook;
+mod window_state;
+mod workspaces;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, DragDropEvent, Emitter, Manager, WindowEvent};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use crate::error::Error;
+
+/// What `benches/` needs from the crate. Not a stable API.
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::db::open;
+    pub use crate::migrations::run as migrate;
+    pub use crate::task_queries::{count_due_between, tasks_in_list, COUNT_DUE_BETWEEN_SQL, TASKS_IN_LIST_SQL};
+}
 
 // Define the application status to track whether a real exit operation is being performed
 struct AppState {
     is_quitting: AtomicBool,
+    /// The active workspace's database; swapped by `switch_workspace`.
+    pool: RwLock<SqlitePool>,
+    /// Held shared by each background job run and exclusively by `vacuum_database`.
+    jobs: tokio::sync::RwLock<()>,
+    /// Recent completions, deletes and moves for `undo` and `redo`.
+    history: Mutex<undo::History>,
+    /// Lets `search_tasks` abandon searches a newer keystroke made stale.
+    searches: search::SearchGeneration,
+}
+
+impl AppState {
+    /// A handle to the current pool. Take a fresh one per operation rather than
+    /// keeping it, so work started after a workspace switch lands in the new database.
+    fn db(&self) -> SqlitePool {
+        self.pool.read().unwrap().clone()
+    }
+
+    /// Installs a new pool and returns the old one for closing.
+    fn replace_db(&self, pool: SqlitePool) -> SqlitePool {
+        std::mem::replace(&mut *self.pool.write().unwrap(), pool)
+    }
+
+    /// Taken by a background job for the length of one run. Don't take it again
+    /// while holding it: a waiting `pause_jobs` blocks new permits.
+    async fn job_permit(&self) -> tokio::sync::RwLockReadGuard<'_, ()> {
+        self.jobs.read().await
+    }
+
+    /// Waits for running jobs to finish and keeps new ones from starting until dropped.
+    async fn pause_jobs(&self) -> tokio::sync::RwLockWriteGuard<'_, ()> {
+        self.jobs.write().await
+    }
+}
+
+/// Arguments of a second launch, forwarded to the running instance
+#[derive(Clone, Serialize)]
+struct SecondInstancePayload {
+    args: Vec<String>,
+    cwd: String,
+}
+
+/// Brings the main window back from the tray, minimized state or behind other windows
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Stops before anything touches a database from a newer build. The windows
+/// are hidden and emptied so the frontend never loads it (closing them would
+/// end the app before the dialog shows), and the app exits once the user has
+/// read why.
+fn refuse_to_start(app: &AppHandle, e: &Error) {
+    log::error!("Refusing to start: {e}");
+    for window in app.webview_windows().into_values() {
+        let _ = window.hide();
+        if let Ok(blank) = tauri::Url::parse("about:blank") {
+            let _ = window.navigate(blank);
+        }
+    }
+    app.dialog()
+        .message(e.to_string())
+        .title("Tada needs an update")
+        .kind(MessageDialogKind::Error)
+        .show(|_| std::process::exit(1));
+}
+
+/// Registers the migrations under every workspace's URL, whichever one the webview loads.
+fn sql_plugin() -> tauri_plugin_sql::Builder {
+    let mut builder = tauri_plugin_sql::Builder::default();
+    let urls = match db::config_dir() {
+        Ok(dir) => workspaces::sql_urls(&dir),
+        Err(_) => vec![paths::sql_url(db::DB_FILE)],
+    };
+    for url in urls {
+        builder = builder.add_migrations(&url, migrations::all());
+    }
+    builder
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let migrations = vec![
-        Migration {
-            version: 1,
-            description: "create_initial_tables",
-            sql: r#"
-                -- Lists table
-                CREATE TABLE IF NOT EXISTS lists (
-                    id TEXT PRIMARY KEY,
-                    name TEXT NOT NULL,
-                    icon TEXT,
-                    color TEXT,
-                    "order" INTEGER,
-                    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000),
-                    updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000)
-                );
-
-                -- Tasks table
-                CREATE TABLE IF NOT EXISTS tasks (
-                    id TEXT PRIMARY KEY,
-                    title TEXT NOT NULL,
-                    completed INTEGER NOT NULL DEFAULT 0,
-                    completed_at INTEGER,
-                    complete_percentage INTEGER,
-                    due_date INTEGER,
-                    list_id TEXT,
-                    list_name TEXT NOT NULL,
-                    content TEXT,
-                    "order" INTEGER NOT NULL,
-                    created_at INTEGER NOT NULL,
-                    updated_at INTEGER NOT NULL,
-                    tags TEXT, -- JSON array
-                    priority INTEGER,
-                    group_category TEXT NOT NULL DEFAULT 'nodate',
-                    FOREIGN KEY (list_id) REFERENCES lists (id) ON DELETE SET NULL
-                );
-
-                -- Subtasks table
-                CREATE TABLE IF NOT EXISTS subtasks (
-                    id TEXT PRIMARY KEY,
-                    parent_id TEXT NOT NULL,
-                    title TEXT NOT NULL,
-                    completed INTEGER NOT NULL DEFAULT 0,
-                    completed_at INTEGER,
-                    due_date INTEGER,
-                    "order" INTEGER NOT NULL,
-                    created_at INTEGER NOT NULL,
-                    updated_at INTEGER NOT NULL,
-                    FOREIGN KEY (parent_id) REFERENCES tasks (id) ON DELETE CASCADE
-                );
-
-                -- Summaries table
-                CREATE TABLE IF NOT EXISTS summaries (
-                    id TEXT PRIMARY KEY,
-                    created_at INTEGER NOT NULL,
-                    updated_at INTEGER NOT NULL,
-                    period_key TEXT NOT NULL,
-                    list_key TEXT NOT NULL,
-                    task_ids TEXT NOT NULL, -- JSON array
-                    summary_text TEXT NOT NULL
-                );
-
-                -- Settings table
-                CREATE TABLE IF NOT EXISTS settings (
-                    key TEXT PRIMARY KEY,
-                    value TEXT NOT NULL,
-                    updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000)
-                );
-
-                -- Insert default data
-                INSERT OR IGNORE INTO lists (id, name, icon, "order")
-                VALUES ('inbox-default', 'Inbox', 'inbox', 1);
-
-                INSERT OR IGNORE INTO settings (key, value) VALUES
-                ('appearance', '{"themeId":"default-coral","darkMode":"system","interfaceDensity":"default"}'),
-                ('preferences', '{"language":"zh-CN","defaultNewTaskDueDate":null,"defaultNewTaskPriority":null,"defaultNewTaskList":"Inbox","confirmDeletions":true}'),
-                ('ai', '{"provider":"openai","apiKey":"","model":"","baseUrl":"","availableModels":[]}');
-
-                -- Create indexes
-                CREATE INDEX IF NOT EXISTS idx_tasks_list_id ON tasks(list_id);
-                CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed);
-                CREATE INDEX IF NOT EXISTS idx_tasks_due_date ON tasks(due_date);
-                CREATE INDEX IF NOT EXISTS idx_subtasks_parent_id ON subtasks(parent_id);
-                CREATE INDEX IF NOT EXISTS idx_summaries_period_list ON summaries(period_key, list_key);
-            "#,
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 2,
-            description: "add_echo_reports",
-            sql: r#"
-                -- Echo Reports table
-                CREATE TABLE IF NOT EXISTS echo_reports (
-                    id TEXT PRIMARY KEY,
-                    created_at INTEGER NOT NULL,
-                    content TEXT NOT NULL,
-                    job_types TEXT NOT NULL, -- JSON array
-                    style TEXT NOT NULL,
-                    user_input TEXT
-                );
-
-                CREATE INDEX IF NOT EXISTS idx_echo_reports_created_at ON echo_reports(created_at);
-            "#,
-            kind: MigrationKind::Up,
-        }
-    ];
+    let builder = tauri::Builder::default();
 
-    tauri::Builder::default()
-        .manage(AppState {
-            is_quitting: AtomicBool::new(false),
-        })
+    // Must be registered first: a second launch hands its arguments over and exits
+    // before any other plugin touches the database
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+        show_main_window(app);
+        let _ = app.emit("second-instance", SecondInstancePayload { args, cwd });
+    }));
+
+    builder
+        .register_asynchronous_uri_scheme_protocol(attachments::PROTOCOL, attachments::serve)
+        .plugin(logging::plugin())
+        // Before the SQL plugin opens the database
+        .plugin(recovery::plugin())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_http::init())
-        .plugin(
-            tauri_plugin_sql::Builder::default()
-                .add_migrations("sqlite:tada.db", migrations)
-                .build(),
-        )
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(sql_plugin().build())
+        .invoke_handler(tauri::generate_handler![
+            reminders::reschedule_reminders,
+            reminders::reminder_action,
+            reminders::snooze_reminder,
+            reminders::set_reminder_offset,
+            reminders::get_reminder_offset,
+            daily_planning::start_daily_review,
+            carry_over::carry_over_overdue,
+            db::new_id,
+            commands::create_task,
+            commands::update_task,
+            commands::duplicate_task,
+            commands::toggle_pin,
+            commands::set_estimate,
+            commands::complete_task,
+            commands::toggle_complete,
+            commands::delete_task,
+            commands::bulk_complete,
+            commands::bulk_delete,
+            commands::purge_completed,
+            commands::reorder_tasks,
+            commands::move_task,
+            board::board,
+            board::move_task_status,
+            undo::undo,
+            undo::redo,
+            tags::list_tags,
+            tags::rename_tag,
+            tags::merge_tags,
+            snoozes::get_snooze_presets,
+            snoozes::snooze_task,
+            snoozes::cancel_snooze,
+            duplicates::find_duplicates,
+            duplicates::merge_tasks,
+            counts::smart_list_counts,
+            commands::reorder_subtasks,
+            subtasks::create_subtask,
+            subtasks::set_subtask_completed,
+            subtasks::delete_subtask,
+            commands::update_list_defaults,
+            commands::rename_list,
+            webhook::set_list_webhook,
+            commands::delete_list,
+            trash::query_trash,
+            trash::restore,
+            trash::empty_trash,
+            dependencies::add_dependency,
+            dependencies::remove_dependency,
+            dependencies::blocked_tasks,
+            autostart::set_autostart,
+            autostart::get_autostart,
+            search::search_tasks,
+            palette::search_everything,
+            query::query_tasks,
+            task_queries::list_tasks,
+            agenda::agenda,
+            stats::list_stats,
+            grouping::recompute_all_groups,
+            archive::archive_old_completed,
+            archive::query_archive,
+            archive::unarchive,
+            backup::export_all,
+            backup::import_all,
+            backup::export_tasks_csv,
+            backup::list_backups,
+            backup::restore_backup,
+            import::import_external,
+            import::import_markdown,
+            close_behavior::confirm_quit,
+            focus::start_focus,
+            focus::cancel_focus,
+            focus::current_focus,
+            crypto::write_encrypted_copy,
+            ical::export_ical,
+            markdown::export_markdown,
+            list_share::export_list,
+            list_share::import_list,
+            report::export_summaries_report,
+            summaries::get_latest_summary,
+            summaries::prune_summaries,
+            ai::generate_summary,
+            ai::test_ai_connection,
+            logging::open_log_dir,
+            paths::get_data_dir,
+            nlp_date::parse_due_date,
+            nlp_recurrence::parse_recurrence,
+            nlp_recurrence::describe_recurrence,
+            capture::parse_quick_capture,
+            integrity::check_integrity,
+            integrity::repair_integrity,
+            attachments::attach_file,
+            attachments::remove_attachment,
+            attachments::read_attachment,
+            attachments::save_voice_note,
+            sync::sync_now,
+            power::power_status,
+            workspaces::list_workspaces,
+            workspaces::create_workspace,
+            workspaces::switch_workspace,
+            metrics::get_metrics_config,
+            metrics::set_metrics_config,
+            http_api::get_http_api_config,
+            http_api::set_http_api_config,
+            http_api::regenerate_http_api_token,
+            maintenance::database_info,
+            maintenance::vacuum_database,
+            templates::save_template,
+            templates::list_templates,
+            templates::delete_template,
+            templates::instantiate_template,
+            diagnostics::export_diagnostics,
+            about::app_info,
+            streaks::get_streaks,
+            sql_console::run_readonly_query,
+            settings::export_settings,
+            settings::import_settings,
+            window_state::set_always_on_top,
+            window_state::set_compact_mode,
+            list_windows::open_list_window,
+            theme::set_dark_mode,
+            #[cfg(desktop)]
+            quick_add::set_quick_add_shortcut,
+            #[cfg(desktop)]
+            quick_add::hide_quick_add,
+            #[cfg(desktop)]
+            updater::check_for_updates,
+            #[cfg(desktop)]
+            updater::install_update,
+            #[cfg(debug_assertions)]
+            migrations::reset_database,
+        ])
         .setup(|app| {
-            // Create a tray menu
-            let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let show_i = MenuItem::with_id(app, "show", "Show Tada", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
-
-            let icon_bytes = include_bytes!("../icons/tray-icon.png");
-            let icon = Image::from_bytes(icon_bytes).expect("Failed to load tray icon");
-
-            // Build the tray icon
-            let tray_builder = TrayIconBuilder::with_id("tray")
-                .menu(&menu)
-                .icon(icon)
-                .show_menu_on_left_click(false)
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "quit" => {
-                        // User clicked the exit button of the tray
-                        let state = app.state::<AppState>();
-                        state.is_quitting.store(true, Ordering::Relaxed);
-                        app.exit(0);
-                    }
-                    "show" => {
-                        // User clicked "Display"
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| match event {
-                    // Left-click the tray icon on Windows/Linux to display the window
-                    TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        ..
-                    } => {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    _ => {}
-                });
+            // Migrated here rather than left to the SQL plugin, which only does it once the webview loads the file
+            logging::install_panic_hook();
+            let db = tauri::async_runtime::block_on(db::connect(app.handle()))?;
+            tauri::async_runtime::block_on(logging::apply_level(&db));
+            let supported: Result<i64, Error> = tauri::async_runtime::block_on(async {
+                migrations::ensure_supported(&mut *db.acquire().await?).await
+            });
+            if let Err(e @ Error::SchemaTooNew { .. }) = supported {
+                refuse_to_start(app.handle(), &e);
+                return Ok(());
+            }
+            supported?;
+            tauri::async_runtime::block_on(migrations::run(&db))?;
+            let schema_version = tauri::async_runtime::block_on(migrations::sync_user_version(&db))?;
+            log::info!("Database schema at version {schema_version} (PRAGMA user_version)");
+            app.manage(AppState {
+                is_quitting: AtomicBool::new(false),
+                pool: RwLock::new(db),
+                jobs: tokio::sync::RwLock::new(()),
+                history: Mutex::new(undo::History::default()),
+                searches: search::SearchGeneration::default(),
+            });
+            workspaces::init(app.handle())?;
+            recovery::notify(app.handle());
+            app.manage(list_windows::ListWindows::default());
 
-            #[cfg(target_os = "macos")]
-            let tray_builder = tray_builder.icon_as_template(true);
+            backup::init(app.handle());
+            idle::init(app.handle());
+            reminders::init(app.handle());
+            daily_planning::init(app.handle());
+            focus::init(app.handle());
+            window_state::restore(app.handle());
+            theme::init(app.handle());
+
+            #[cfg(desktop)]
+            app.handle().plugin(tauri_plugin_autostart::init(
+                tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+                None,
+            ))?;
+            autostart::sync_from_settings(app.handle());
+
+            #[cfg(desktop)]
+            quick_add::init(app.handle())?;
+
+            #[cfg(desktop)]
+            {
+                if updater::configured(app.handle()) {
+                    app.handle().plugin(tauri_plugin_updater::Builder::new().build())?;
+                    updater::init(app.handle());
+                }
+            }
 
-            tray_builder.build(app)?;
+            tray::init(app.handle())?;
+            #[cfg(target_os = "macos")]
+            {
+                app.set_menu(app_menu::build(app.handle())?)?;
+                app.on_menu_event(app_menu::on_menu_event);
+            }
+            badge::init(app.handle());
+            grouping::init(app.handle());
+            carry_over::init(app.handle());
+            counts::init(app.handle());
+            wake::init(app.handle());
+            power::init(app.handle());
+            sync::init(app.handle());
+            webhook::init(app.handle());
+            attachments::init(app.handle());
+            deep_link::init(app.handle());
+            metrics::init(app.handle());
+            http_api::init(app.handle());
 
             Ok(())
         })
         // Handle window events (block the close button)
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
+        .on_window_event(|window, event| match event {
+            WindowEvent::CloseRequested { api, .. } => {
                 let app_handle = window.app_handle();
                 let state = app_handle.state::<AppState>();
 
-                // If it is not exited through the Quit button on the tray or Cmd+Q (which triggers the App Exit process), it will be blocked and hidden
-                if !state.is_quitting.load(Ordering::Relaxed) {
+                // Unless quitting through the tray or app menu Quit (both set is_quitting first), the closeButtonBehavior setting decides.
+                // List windows simply close
+                if !state.is_quitting.load(Ordering::Relaxed) && !list_windows::is_list_window(window.label()) {
                     api.prevent_close();
-                    window.hide().unwrap();
+                    close_behavior::on_close_requested(window);
                 }
             }
+            WindowEvent::Destroyed if list_windows::is_list_window(window.label()) => {
+                list_windows::on_destroyed(window.app_handle(), window.label());
+            }
+            WindowEvent::ThemeChanged(theme) => theme::on_theme_changed(window, *theme),
+            WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) if window.label() == "main" => {
+                file_drop::on_drop(window, paths.clone());
+            }
+            // Remember the main window geometry across launches
+            WindowEvent::Moved(_) | WindowEvent::Resized(_) if window.label() == "main" => {
+                window_state::track(window);
+            }
+            _ => {}
         })
-        // .plugin(tauri_plugin_updater::Builder::new().build())
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app_handle, event| match event {
             // To handle macOS, click the Dock icon to reopen the window
             #[cfg(target_os = "macos")]
             tauri::RunEvent::Reopen { .. } => {
-                if let Some(window) = app_handle.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+                show_main_window(app_handle);
+            }
+            tauri::RunEvent::Exit => {
+                metrics::shutdown(app_handle);
+                http_api::shutdown(app_handle);
             }
             _ => {}
         });
@@ -0,0 +1,243 @@
+//! One list with its tasks and subtasks as a file to hand to someone else.
+//!
+//! JSON keeps every column and is what `import_list` reads back; Markdown and
+//! CSV are for reading. Trashed tasks stay behind. An import always creates a
+//! new list with fresh ids, named apart from the lists already there, so it
+//! can't overwrite anything, even when the file came from this same database.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{Sqlite, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::backup::bind_value;
+use crate::dates::format_local;
+use crate::db::{new_id, now_ms, row_to_json, table_columns};
+use crate::error::{Error, Result};
+use crate::events;
+use crate::markdown;
+use crate::migrations;
+use crate::models::{Subtask, Task};
+use crate::AppState;
+
+/// Marks a list export, so a full backup isn't taken for one.
+const KIND: &str = "tada-list";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Csv,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListExport {
+    pub kind: String,
+    pub schema_version: i64,
+    pub exported_at: i64,
+    pub list: Map<String, Value>,
+    /// In list order, as are `subtasks` within each task.
+    pub tasks: Vec<Map<String, Value>>,
+    pub subtasks: Vec<Map<String, Value>>,
+}
+
+const TASKS_SQL: &str = r#"
+    SELECT * FROM tasks WHERE list_id = ? AND deleted_at IS NULL AND list_name != 'Trash'
+    ORDER BY "order", created_at, id
+"#;
+const SUBTASKS_SQL: &str = r#"
+    SELECT s.* FROM subtasks s JOIN tasks t ON t.id = s.parent_id
+    WHERE t.list_id = ? AND t.deleted_at IS NULL AND t.list_name != 'Trash'
+    ORDER BY t."order", t.created_at, t.id, s."order", s.id
+"#;
+
+async fn list_name(state: &AppState, list_id: &str) -> Result<String> {
+    let name: Option<String> = sqlx::query_scalar("SELECT name FROM lists WHERE id = ? AND deleted_at IS NULL")
+        .bind(list_id)
+        .fetch_optional(&state.db())
+        .await?;
+    name.ok_or_else(|| Error::NotFound(format!("List {list_id}")))
+}
+
+async fn export_json(state: &AppState, list_id: &str) -> Result<Vec<u8>> {
+    let pool = state.db();
+    let list = sqlx::query("SELECT * FROM lists WHERE id = ?").bind(list_id).fetch_one(&pool).await?;
+    let tasks = sqlx::query(TASKS_SQL).bind(list_id).fetch_all(&pool).await?;
+    let subtasks = sqlx::query(SUBTASKS_SQL).bind(list_id).fetch_all(&pool).await?;
+    let export = ListExport {
+        kind: KIND.to_string(),
+        schema_version: migrations::latest_version(),
+        exported_at: now_ms(),
+        list: row_to_json(&list),
+        tasks: tasks.iter().map(row_to_json).collect(),
+        subtasks: subtasks.iter().map(row_to_json).collect(),
+    };
+    Ok(serde_json::to_vec_pretty(&export)?)
+}
+
+async fn fetch_rows(state: &AppState, list_id: &str) -> Result<(Vec<Task>, Vec<Subtask>)> {
+    let pool = state.db();
+    let tasks = sqlx::query_as(TASKS_SQL).bind(list_id).fetch_all(&pool).await?;
+    let subtasks = sqlx::query_as(SUBTASKS_SQL).bind(list_id).fetch_all(&pool).await?;
+    Ok((tasks, subtasks))
+}
+
+fn write_csv(path: &str, tasks: &[Task], subtasks: &[Subtask]) -> Result<()> {
+    let mut by_parent: HashMap<&str, Vec<&Subtask>> = HashMap::new();
+    for subtask in subtasks {
+        by_parent.entry(subtask.parent_id.as_str()).or_default().push(subtask);
+    }
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(["kind", "id", "parent_id", "title", "completed", "due_date", "priority", "tags", "content"])?;
+    for task in tasks {
+        writer.write_record([
+            "task".to_string(),
+            task.id.clone(),
+            String::new(),
+            task.title.clone(),
+            task.completed.to_string(),
+            task.due_date.map(format_local).unwrap_or_default(),
+            task.priority.map(|p| p.to_string()).unwrap_or_default(),
+            task.tags.join(";"),
+            task.content.clone().unwrap_or_default(),
+        ])?;
+        // Each task's subtasks follow it, in their order
+        for subtask in by_parent.get(task.id.as_str()).into_iter().flatten() {
+            writer.write_record([
+                "subtask".to_string(),
+                subtask.id.clone(),
+                subtask.parent_id.clone(),
+                subtask.title.clone(),
+                subtask.completed.to_string(),
+                subtask.due_date.map(format_local).unwrap_or_default(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes one list, its tasks and their subtasks to `path`.
+#[tauri::command]
+pub async fn export_list(state: State<'_, AppState>, list_id: String, path: String, format: ExportFormat) -> Result<()> {
+    let name = list_name(&state, &list_id).await?;
+    match format {
+        ExportFormat::Json => std::fs::write(&path, export_json(&state, &list_id).await?)?,
+        ExportFormat::Markdown => {
+            let (tasks, subtasks) = fetch_rows(&state, &list_id).await?;
+            std::fs::write(&path, markdown::render(&[(list_id.clone(), name)], &tasks, &subtasks))?
+        }
+        ExportFormat::Csv => {
+            let (tasks, subtasks) = fetch_rows(&state, &list_id).await?;
+            write_csv(&path, &tasks, &subtasks)?
+        }
+    }
+    log::info!("Exported list {list_id} as {format:?} to {path}");
+    Ok(())
+}
+
+/// `name`, or `name (2)`, `name (3)`… when a list already has it.
+async fn unused_name(tx: &mut Transaction<'_, Sqlite>, name: &str) -> Result<String> {
+    let taken: Vec<String> = sqlx::query_scalar("SELECT name FROM lists WHERE deleted_at IS NULL")
+        .fetch_all(&mut **tx)
+        .await?;
+    let is_taken = |candidate: &str| taken.iter().any(|t| t.eq_ignore_ascii_case(candidate));
+    if !is_taken(name) {
+        return Ok(name.to_string());
+    }
+    Ok((2..)
+        .map(|n| format!("{name} ({n})"))
+        .find(|candidate| !is_taken(candidate))
+        .unwrap_or_default())
+}
+
+/// Inserts a row from the file, only with columns this schema has; the names
+/// come from the database, never from the file.
+async fn insert_row(tx: &mut Transaction<'_, Sqlite>, table: &str, known: &[String], row: &Map<String, Value>) -> Result<()> {
+    let columns: Vec<&String> = known.iter().filter(|c| row.contains_key(*c)).collect();
+    let column_list = columns.iter().map(|c| format!("\"{c}\"")).collect::<Vec<_>>().join(", ");
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let sql = format!("INSERT INTO \"{table}\" ({column_list}) VALUES ({placeholders})");
+    let mut query = sqlx::query(&sql);
+    for column in &columns {
+        query = bind_value(query, &row[column.as_str()]);
+    }
+    query.execute(&mut **tx).await?;
+    Ok(())
+}
+
+async fn import(tx: &mut Transaction<'_, Sqlite>, export: ListExport) -> Result<String> {
+    let now = now_ms();
+    let name = export.list.get("name").and_then(Value::as_str).unwrap_or("Imported list");
+    let name = unused_name(tx, name).await?;
+    let list_id = new_id();
+
+    let mut list = export.list;
+    list.insert("id".into(), list_id.clone().into());
+    list.insert("name".into(), name.clone().into());
+    list.insert("updated_at".into(), now.into());
+    list.insert("deleted_at".into(), Value::Null);
+    list.insert(
+        "order".into(),
+        sqlx::query_scalar::<_, i64>(r#"SELECT COALESCE(MAX("order"), 0) + 1 FROM lists"#)
+            .fetch_one(&mut **tx)
+            .await?
+            .into(),
+    );
+    insert_row(tx, "lists", &table_columns(&mut **tx, "lists").await?, &list).await?;
+
+    // Old task id → new, for the subtasks; "order" comes along unchanged
+    let mut task_ids = HashMap::new();
+    let columns = table_columns(&mut **tx, "tasks").await?;
+    for mut task in export.tasks {
+        let Some(old_id) = task.get("id").and_then(Value::as_str).map(str::to_string) else {
+            continue;
+        };
+        let id = new_id();
+        task.insert("id".into(), id.clone().into());
+        task.insert("list_id".into(), list_id.clone().into());
+        task.insert("list_name".into(), name.clone().into());
+        task.insert("deleted_at".into(), Value::Null);
+        insert_row(tx, "tasks", &columns, &task).await?;
+        task_ids.insert(old_id, id);
+    }
+
+    let columns = table_columns(&mut **tx, "subtasks").await?;
+    for mut subtask in export.subtasks {
+        let Some(parent_id) = subtask.get("parent_id").and_then(Value::as_str).and_then(|id| task_ids.get(id)) else {
+            continue;
+        };
+        subtask.insert("parent_id".into(), parent_id.clone().into());
+        subtask.insert("id".into(), new_id().into());
+        insert_row(tx, "subtasks", &columns, &subtask).await?;
+    }
+    log::info!("Imported list '{name}' with {} tasks", task_ids.len());
+    Ok(list_id)
+}
+
+/// Creates a new list from a JSON file written by `export_list`, returning its id.
+#[tauri::command]
+pub async fn import_list(app: AppHandle, state: State<'_, AppState>, path: String) -> Result<String> {
+    let export: ListExport = serde_json::from_slice(&std::fs::read(&path)?)?;
+    if export.kind != KIND {
+        return Err(Error::InvalidInput("This file isn't a list exported from Tada".into()));
+    }
+    if export.schema_version > migrations::latest_version() {
+        return Err(Error::InvalidInput(
+            "This list was exported by a newer version of Tada. Please update the app first.".into(),
+        ));
+    }
+    let mut tx = state.db().begin().await?;
+    let list_id = import(&mut tx, export).await?;
+    tx.commit().await?;
+    events::list_updated(&app, Some(&list_id));
+    events::tasks_changed(&app);
+    Ok(list_id)
+}
@@ -0,0 +1,108 @@
+//! Extra windows that each show a single list, labeled `list-<id>`.
+//!
+//! They load the same frontend at the list's route, so they talk to the same
+//! database and get every event `events` broadcasts, like `tasks-changed`.
+//! Closing one really closes it, unlike the main window, which hides to the
+//! tray without taking these along. Quitting and switching workspaces close
+//! them all.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::error::{Error, Result};
+use crate::{theme, AppState};
+
+pub const LABEL_PREFIX: &str = "list-";
+
+/// Labels of the list windows currently open.
+#[derive(Default)]
+pub struct ListWindows {
+    open: Mutex<HashSet<String>>,
+}
+
+pub fn is_list_window(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+/// Window labels only allow letters, digits and `-/:_`; anything else becomes `_`.
+fn label_for(list_id: &str) -> String {
+    let id: String = list_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | ':' | '_') { c } else { '_' })
+        .collect();
+    format!("{LABEL_PREFIX}{id}")
+}
+
+/// `encodeURIComponent`, which the frontend's `/list/:listName` route decodes.
+fn encode_component(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Opens a window showing one list, or brings its window forward if it's already open.
+#[tauri::command]
+pub async fn open_list_window(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    windows: State<'_, ListWindows>,
+    list_id: String,
+) -> Result<()> {
+    let label = label_for(&list_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.show()?;
+        window.unminimize()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    let name: Option<String> = sqlx::query_scalar("SELECT name FROM lists WHERE id = ? AND deleted_at IS NULL")
+        .bind(&list_id)
+        .fetch_optional(&state.db())
+        .await?;
+    let name = name.ok_or_else(|| Error::NotFound(format!("List {list_id}")))?;
+
+    // The route is keyed by list name, as the sidebar links are
+    let url = WebviewUrl::App(format!("index.html#/list/{}", encode_component(&name)).into());
+    let window = WebviewWindowBuilder::new(&app, &label, url)
+        .title(format!("{name} - Tada"))
+        .inner_size(480.0, 720.0)
+        .min_inner_size(360.0, 400.0)
+        .build()?;
+    theme::apply_to_new(&window);
+    window.set_focus()?;
+    windows.open.lock().unwrap().insert(label);
+    log::info!("Opened a window for list {list_id}");
+    Ok(())
+}
+
+/// Forgets a list window once it's gone.
+pub fn on_destroyed(app: &AppHandle, label: &str) {
+    if let Some(windows) = app.try_state::<ListWindows>() {
+        windows.open.lock().unwrap().remove(label);
+    }
+}
+
+/// Closes every list window, e.g. before quitting.
+pub fn close_all(app: &AppHandle) {
+    let Some(windows) = app.try_state::<ListWindows>() else {
+        return;
+    };
+    let labels: Vec<String> = windows.open.lock().unwrap().drain().collect();
+    for label in labels {
+        if let Some(window) = app.get_webview_window(&label) {
+            if let Err(e) = window.destroy() {
+                log::warn!("Failed to close list window {label}: {e}");
+            }
+        }
+    }
+}
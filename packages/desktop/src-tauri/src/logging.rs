@@ -0,0 +1,83 @@
+//! Log files in the app log dir (or the portable data folder), with the level
+//! taken from the `logLevel` setting.
+
+use log::LevelFilter;
+use sqlx::SqlitePool;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::{Error, Result};
+use crate::{paths, settings};
+
+const MAX_FILE_SIZE: u128 = 5 * 1024 * 1024;
+const KEEP_FILES: usize = 5;
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// The plugin passes everything through; the effective level is set from the
+/// database in `apply_level`, which isn't open yet when plugins are built.
+pub fn plugin() -> TauriPlugin<Wry> {
+    let files = match paths::custom_log_dir() {
+        Some(path) => TargetKind::Folder { path, file_name: None },
+        None => TargetKind::LogDir { file_name: None },
+    };
+    tauri_plugin_log::Builder::new()
+        .targets([Target::new(TargetKind::Stdout), Target::new(files)])
+        .rotation_strategy(RotationStrategy::KeepSome(KEEP_FILES))
+        .max_file_size(MAX_FILE_SIZE)
+        .level(LevelFilter::Trace)
+        // Dependencies are chatty at debug level
+        .level_for("sqlx", LevelFilter::Warn)
+        .level_for("hyper", LevelFilter::Warn)
+        .level_for("reqwest", LevelFilter::Warn)
+        .build()
+}
+
+/// Reads `logLevel` ("off", "error", "warn", "info", "debug" or "trace") and applies it.
+pub async fn apply_level(pool: &SqlitePool) {
+    let level = match settings::get::<String>(pool, "logLevel").await {
+        Ok(Some(level)) => level.parse().unwrap_or_else(|_| {
+            log::warn!("Unknown log level '{level}', using {DEFAULT_LEVEL}");
+            DEFAULT_LEVEL
+        }),
+        Ok(None) => DEFAULT_LEVEL,
+        Err(e) => {
+            log::error!("Failed to read log level: {e}");
+            DEFAULT_LEVEL
+        }
+    };
+    log::set_max_level(level);
+    log::info!("Log level set to {level}");
+}
+
+/// Panics inside spawned tasks would otherwise vanish with the task; this records them first.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".into());
+        log::error!(
+            target: "panic",
+            "Thread '{}' panicked at {location}: {message}",
+            thread.name().unwrap_or("<unnamed>")
+        );
+        default_hook(info);
+    }));
+}
+
+/// Opens the folder holding the log files in the OS file manager.
+#[tauri::command]
+pub fn open_log_dir(app: AppHandle) -> Result<()> {
+    let dir = paths::log_dir(&app)?;
+    std::fs::create_dir_all(&dir)?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| Error::InvalidInput(format!("Could not open the log folder: {e}")))
+}
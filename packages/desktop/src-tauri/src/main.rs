@@ -1,5 +1,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `tada add ...` / `tada list ...` run headless and exit
+    #[cfg(desktop)]
+    if let Some(code) = tada_desktop_lib::cli::run() {
+        std::process::exit(code);
+    }
     tada_desktop_lib::run()
 }
\ No newline at end of file
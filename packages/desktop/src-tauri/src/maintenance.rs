@@ -0,0 +1,148 @@
+//! Database size reporting and `VACUUM`.
+//!
+//! Deleted rows leave free pages behind that SQLite reuses but never returns to
+//! the file system. `vacuum_database` rebuilds the file to hand them back; it
+//! needs the database to itself, so background jobs are held off while it runs.
+
+use std::path::Path;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, State};
+
+use crate::error::Result;
+use crate::{paths, workspaces, AppState};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableInfo {
+    pub name: String,
+    pub rows: i64,
+    /// Bytes used by the table and its indexes, when SQLite was built with `dbstat`.
+    pub bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbInfo {
+    /// The database file plus its write-ahead log.
+    pub file_size: u64,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub free_pages: i64,
+    /// `none`, `full` or `incremental`.
+    pub auto_vacuum: String,
+    pub tables: Vec<TableInfo>,
+    /// Reminders recorded as delivered, kept until the purge in `reminders` drops them.
+    pub fired_reminders: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumReport {
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+    pub reclaimed_bytes: u64,
+    pub incremental: bool,
+}
+
+/// Size on disk of the database and its `-wal` file.
+fn file_size(path: &Path) -> u64 {
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    [path, Path::new(&wal)]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+async fn auto_vacuum(pool: &SqlitePool) -> Result<i64> {
+    Ok(sqlx::query_scalar("PRAGMA auto_vacuum").fetch_one(pool).await?)
+}
+
+pub(crate) async fn table_info(pool: &SqlitePool) -> Result<Vec<TableInfo>> {
+    // Shadow tables of the FTS indexes are listed as `shadow`, so only real tables remain
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM pragma_table_list WHERE schema = 'main' AND type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await?;
+    // dbstat is optional in SQLite builds; without it sizes are simply unknown
+    let sizes: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT COALESCE(m.tbl_name, s.name), SUM(s.pgsize)
+        FROM dbstat s LEFT JOIN sqlite_schema m ON m.name = s.name
+        GROUP BY 1
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default();
+
+    let mut tables = Vec::with_capacity(names.len());
+    for name in names {
+        // Names come from the schema, not user input; quoting handles any odd characters
+        let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")))
+            .fetch_one(pool)
+            .await?;
+        let bytes = sizes.iter().find(|(table, _)| *table == name).map(|(_, bytes)| *bytes);
+        tables.push(TableInfo { name, rows, bytes });
+    }
+    Ok(tables)
+}
+
+#[tauri::command]
+pub async fn database_info(app: AppHandle, state: State<'_, AppState>) -> Result<DbInfo> {
+    let pool = state.db();
+    let path = workspaces::active_db_path(&paths::config_dir(&app)?);
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(&pool).await?;
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(&pool).await?;
+    let free_pages: i64 = sqlx::query_scalar("PRAGMA freelist_count").fetch_one(&pool).await?;
+    let auto_vacuum = match auto_vacuum(&pool).await? {
+        1 => "full",
+        2 => "incremental",
+        _ => "none",
+    };
+    Ok(DbInfo {
+        file_size: file_size(&path),
+        page_size,
+        page_count,
+        free_pages,
+        auto_vacuum: auto_vacuum.into(),
+        tables: table_info(&pool).await?,
+        fired_reminders: sqlx::query_scalar("SELECT COUNT(*) FROM fired_reminders").fetch_one(&pool).await?,
+    })
+}
+
+/// Returns free pages to the file system and reports the size before and after.
+///
+/// With `auto_vacuum = incremental` only the free pages are released, which is
+/// quick; otherwise the whole file is rebuilt. The log is checkpointed afterwards
+/// so the shrink shows up in the file size right away.
+#[tauri::command]
+pub async fn vacuum_database(app: AppHandle, state: State<'_, AppState>) -> Result<VacuumReport> {
+    let _paused = state.pause_jobs().await;
+    let pool = state.db();
+    let path = workspaces::active_db_path(&paths::config_dir(&app)?);
+    let before_bytes = file_size(&path);
+    let incremental = auto_vacuum(&pool).await? == 2;
+
+    let mut conn = pool.acquire().await?;
+    let started = std::time::Instant::now();
+    if incremental {
+        sqlx::query("PRAGMA incremental_vacuum").execute(&mut *conn).await?;
+    } else {
+        sqlx::query("VACUUM").execute(&mut *conn).await?;
+    }
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&mut *conn).await?;
+    drop(conn);
+
+    let after_bytes = file_size(&path);
+    let reclaimed_bytes = before_bytes.saturating_sub(after_bytes);
+    log::info!(
+        "Vacuumed database in {:?}: {before_bytes} → {after_bytes} bytes",
+        started.elapsed()
+    );
+    Ok(VacuumReport { before_bytes, after_bytes, reclaimed_bytes, incremental })
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use tauri::State;
+
+use crate::dates::local_date;
+use crate::error::Result;
+use crate::models::{Subtask, Task};
+use crate::AppState;
+
+/// Backslash-escapes characters that would otherwise turn into Markdown syntax.
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '~' | '|' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            // One item per line
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub(crate) fn push_item(out: &mut String, indent: &str, completed: bool, title: &str, tags: &[String], due: Option<i64>) {
+    let title = escape(title.trim());
+    out.push_str(indent);
+    if completed {
+        out.push_str(&format!("- [x] ~~{title}~~"));
+    } else {
+        out.push_str(&format!("- [ ] {title}"));
+    }
+    for tag in tags {
+        // A space would end the tag early
+        out.push_str(&format!(" #{}", escape(&tag.split_whitespace().collect::<Vec<_>>().join("-"))));
+    }
+    if let Some(due) = due {
+        out.push_str(&format!(" (due: {})", local_date(due).format("%Y-%m-%d")));
+    }
+    out.push('\n');
+}
+
+fn push_tasks(out: &mut String, tasks: &[&Task], subtasks: &HashMap<&str, Vec<&Subtask>>) {
+    for task in tasks {
+        push_item(out, "", task.completed, &task.title, &task.tags, task.due_date);
+        for subtask in subtasks.get(task.id.as_str()).into_iter().flatten() {
+            push_item(out, "  ", subtask.completed, &subtask.title, &[], subtask.due_date);
+        }
+    }
+}
+
+/// Renders every list as a `##` section of checkbox items, subtasks nested below their task.
+pub(crate) fn render(lists: &[(String, String)], tasks: &[Task], subtasks: &[Subtask]) -> String {
+    let mut by_parent: HashMap<&str, Vec<&Subtask>> = HashMap::new();
+    for subtask in subtasks {
+        by_parent.entry(subtask.parent_id.as_str()).or_default().push(subtask);
+    }
+
+    let mut out = String::new();
+    for (id, name) in lists {
+        let in_list: Vec<&Task> = tasks.iter().filter(|t| t.list_id.as_deref() == Some(id.as_str())).collect();
+        out.push_str(&format!("## {}\n\n", escape(name)));
+        push_tasks(&mut out, &in_list, &by_parent);
+        out.push('\n');
+    }
+
+    // Tasks whose list row is gone still carry the list name
+    let mut orphans: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| !lists.iter().any(|(id, _)| t.list_id.as_deref() == Some(id.as_str())))
+        .collect();
+    orphans.sort_by(|a, b| a.list_name.cmp(&b.list_name));
+    for group in orphans.chunk_by(|a, b| a.list_name == b.list_name) {
+        out.push_str(&format!("## {}\n\n", escape(&group[0].list_name)));
+        push_tasks(&mut out, group, &by_parent);
+        out.push('\n');
+    }
+    out
+}
+
+/// Writes all lists and their tasks to a Markdown file. Trashed tasks are left out.
+#[tauri::command]
+pub async fn export_markdown(state: State<'_, AppState>, path: String) -> Result<()> {
+    let lists: Vec<(String, String)> = sqlx::query_as(r#"SELECT id, name FROM lists WHERE deleted_at IS NULL ORDER BY "order", name"#)
+        .fetch_all(&state.db())
+        .await?;
+    let tasks: Vec<Task> = sqlx::query_as(r#"SELECT * FROM tasks WHERE list_name != 'Trash' AND deleted_at IS NULL ORDER BY "order""#)
+        .fetch_all(&state.db())
+        .await?;
+    let subtasks: Vec<Subtask> = sqlx::query_as(r#"SELECT * FROM subtasks ORDER BY "order""#)
+        .fetch_all(&state.db())
+        .await?;
+
+    std::fs::write(path, render(&lists, &tasks, &subtasks))?;
+    Ok(())
+}
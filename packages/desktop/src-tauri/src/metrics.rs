@@ -0,0 +1,190 @@
+//! Optional Prometheus text endpoint at `http://127.0.0.1:<port>/metrics`.
+//!
+//! Off unless the `metrics` setting enables it. Every scrape queries SQLite
+//! directly, so the numbers are never staler than the scrape interval. The
+//! server only ever binds to loopback; nothing here is reachable from the network.
+
+use std::fmt::Write as _;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use axum::extract::State as Extract;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::oneshot;
+
+use crate::dates::local_day_bounds;
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::settings;
+use crate::AppState;
+
+const SETTINGS_KEY: &str = "metrics";
+const DEFAULT_PORT: u16 = 9464;
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self { enabled: false, port: DEFAULT_PORT }
+    }
+}
+
+/// The running server: a trigger for graceful shutdown and the task serving it.
+#[derive(Default)]
+pub struct MetricsServer(Mutex<Option<(oneshot::Sender<()>, JoinHandle<()>)>>);
+
+pub fn init(app: &AppHandle) {
+    app.manage(MetricsServer::default());
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let pool = handle.state::<AppState>().db();
+        match settings::get::<MetricsConfig>(&pool, SETTINGS_KEY).await {
+            Ok(config) => apply(&handle, config.unwrap_or_default()).await,
+            Err(e) => log::error!("Failed to read metrics setting: {e}"),
+        }
+    });
+}
+
+/// Stops any running server, then starts one if the config enables it.
+async fn apply(app: &AppHandle, config: MetricsConfig) {
+    stop(app).await;
+    if !config.enabled {
+        return;
+    }
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, config.port));
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Failed to bind metrics endpoint to {addr}: {e}");
+            return;
+        }
+    };
+
+    let router = Router::new().route("/metrics", get(scrape)).with_state(app.clone());
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let server = tauri::async_runtime::spawn(async move {
+        let result = axum::serve(listener, router)
+            .with_graceful_shutdown(async {
+                let _ = signal.await;
+            })
+            .await;
+        if let Err(e) = result {
+            log::error!("Metrics endpoint failed: {e}");
+        }
+    });
+    log::info!("Serving metrics on http://{addr}/metrics");
+    *app.state::<MetricsServer>().0.lock().unwrap() = Some((shutdown, server));
+}
+
+/// Signals the server to finish in-flight scrapes, giving up after a short wait.
+async fn stop(app: &AppHandle) {
+    let running = app.state::<MetricsServer>().0.lock().unwrap().take();
+    if let Some((shutdown, server)) = running {
+        let _ = shutdown.send(());
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, server).await.is_err() {
+            log::warn!("Metrics endpoint didn't stop in time");
+        }
+        log::info!("Stopped metrics endpoint");
+    }
+}
+
+/// Called on app exit so the port is released before the process ends.
+pub fn shutdown(app: &AppHandle) {
+    if app.try_state::<MetricsServer>().is_some() {
+        tauri::async_runtime::block_on(stop(app));
+    }
+}
+
+async fn scrape(Extract(app): Extract<AppHandle>) -> impl IntoResponse {
+    let state = app.state::<AppState>();
+    let _permit = state.job_permit().await;
+    match render(&state.db()).await {
+        Ok(body) => (StatusCode::OK, [(header::CONTENT_TYPE, CONTENT_TYPE)], body),
+        Err(e) => {
+            log::error!("Failed to collect metrics: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, [(header::CONTENT_TYPE, CONTENT_TYPE)], String::new())
+        }
+    }
+}
+
+/// Label values escaped per the exposition format: backslash, quote and newline.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', "\\\"").replace('\n', r"\n")
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: i64) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}");
+}
+
+async fn render(pool: &SqlitePool) -> Result<String> {
+    let now = now_ms();
+    let (today_start, today_end) = local_day_bounds(now);
+    let (total, open, overdue, completed_today): (i64, i64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*),
+               COALESCE(SUM(completed = 0), 0),
+               COALESCE(SUM(completed = 0 AND due_date IS NOT NULL AND due_date < ?), 0),
+               COALESCE(SUM(completed = 1 AND completed_at >= ? AND completed_at < ?), 0)
+        FROM tasks WHERE list_name != 'Trash' AND deleted_at IS NULL
+        "#,
+    )
+    .bind(now)
+    .bind(today_start)
+    .bind(today_end)
+    .fetch_one(pool)
+    .await?;
+    let lists: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT l.name, COUNT(t.id), COALESCE(SUM(t.completed = 0), 0)
+        FROM lists l LEFT JOIN tasks t ON t.list_id = l.id AND t.deleted_at IS NULL
+        WHERE l.name != 'Trash' AND l.deleted_at IS NULL
+        GROUP BY l.id ORDER BY l.name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut out = String::new();
+    gauge(&mut out, "tada_tasks_total", "Tasks outside the trash.", total);
+    gauge(&mut out, "tada_tasks_open", "Tasks not yet completed.", open);
+    gauge(&mut out, "tada_tasks_overdue", "Open tasks past their due date.", overdue);
+    gauge(&mut out, "tada_tasks_completed_today", "Tasks completed since local midnight.", completed_today);
+    out.push_str("# HELP tada_list_tasks Tasks per list, by state.\n# TYPE tada_list_tasks gauge\n");
+    for (name, count, open) in lists {
+        let list = escape_label(&name);
+        let _ = writeln!(out, "tada_list_tasks{{list=\"{list}\",state=\"all\"}} {count}");
+        let _ = writeln!(out, "tada_list_tasks{{list=\"{list}\",state=\"open\"}} {open}");
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+pub async fn get_metrics_config(state: State<'_, AppState>) -> Result<MetricsConfig> {
+    Ok(settings::get::<MetricsConfig>(&state.db(), SETTINGS_KEY).await?.unwrap_or_default())
+}
+
+/// Saves the config and starts, stops or rebinds the server to match.
+#[tauri::command]
+pub async fn set_metrics_config(app: AppHandle, state: State<'_, AppState>, config: MetricsConfig) -> Result<()> {
+    if config.port == 0 {
+        return Err(Error::InvalidInput("The metrics port can't be 0".into()));
+    }
+    settings::set(&state.db(), SETTINGS_KEY, &config).await?;
+    apply(&app, config).await;
+    Ok(())
+}
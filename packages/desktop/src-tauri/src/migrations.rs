@@ -0,0 +1,943 @@
+use futures_core::future::BoxFuture;
+use sqlx::error::BoxDynError;
+use sqlx::migrate::{Migration as SqlxMigration, MigrationSource, MigrationType, Migrator};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri_plugin_sql::{Migration, MigrationKind};
+
+use crate::error::{Error, Result};
+
+/// Schema migrations applied by the SQL plugin, in order. Append new entries; never edit shipped ones.
+///
+/// Every `Up` is followed by the `Down` that reverses it, for rolling back during development.
+pub fn all() -> Vec<Migration> {
+    vec![
+        Migration {
+            version: 1,
+            description: "create_initial_tables",
+            sql: r#"
+                -- Lists table
+                CREATE TABLE IF NOT EXISTS lists (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    icon TEXT,
+                    color TEXT,
+                    "order" INTEGER,
+                    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000),
+                    updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000)
+                );
+
+                -- Tasks table
+                CREATE TABLE IF NOT EXISTS tasks (
+                    id TEXT PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    completed INTEGER NOT NULL DEFAULT 0,
+                    completed_at INTEGER,
+                    complete_percentage INTEGER,
+                    due_date INTEGER,
+                    list_id TEXT,
+                    list_name TEXT NOT NULL,
+                    content TEXT,
+                    "order" INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    tags TEXT, -- JSON array
+                    priority INTEGER,
+                    group_category TEXT NOT NULL DEFAULT 'nodate',
+                    FOREIGN KEY (list_id) REFERENCES lists (id) ON DELETE SET NULL
+                );
+
+                -- Subtasks table
+                CREATE TABLE IF NOT EXISTS subtasks (
+                    id TEXT PRIMARY KEY,
+                    parent_id TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    completed INTEGER NOT NULL DEFAULT 0,
+                    completed_at INTEGER,
+                    due_date INTEGER,
+                    "order" INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    FOREIGN KEY (parent_id) REFERENCES tasks (id) ON DELETE CASCADE
+                );
+
+                -- Summaries table
+                CREATE TABLE IF NOT EXISTS summaries (
+                    id TEXT PRIMARY KEY,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    period_key TEXT NOT NULL,
+                    list_key TEXT NOT NULL,
+                    task_ids TEXT NOT NULL, -- JSON array
+                    summary_text TEXT NOT NULL
+                );
+
+                -- Settings table
+                CREATE TABLE IF NOT EXISTS settings (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL,
+                    updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000)
+                );
+
+                -- Insert default data
+                INSERT OR IGNORE INTO lists (id, name, icon, "order")
+                VALUES ('inbox-default', 'Inbox', 'inbox', 1);
+
+                INSERT OR IGNORE INTO settings (key, value) VALUES
+                ('appearance', '{"themeId":"default-coral","darkMode":"system","interfaceDensity":"default"}'),
+                ('preferences', '{"language":"zh-CN","defaultNewTaskDueDate":null,"defaultNewTaskPriority":null,"defaultNewTaskList":"Inbox","confirmDeletions":true}'),
+                ('ai', '{"provider":"openai","apiKey":"","model":"","baseUrl":"","availableModels":[]}');
+
+                -- Create indexes
+                CREATE INDEX IF NOT EXISTS idx_tasks_list_id ON tasks(list_id);
+                CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed);
+                CREATE INDEX IF NOT EXISTS idx_tasks_due_date ON tasks(due_date);
+                CREATE INDEX IF NOT EXISTS idx_subtasks_parent_id ON subtasks(parent_id);
+                CREATE INDEX IF NOT EXISTS idx_summaries_period_list ON summaries(period_key, list_key);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 1,
+            description: "create_initial_tables",
+            sql: r#"
+                DROP TABLE IF EXISTS settings;
+                DROP TABLE IF EXISTS summaries;
+                DROP TABLE IF EXISTS subtasks;
+                DROP TABLE IF EXISTS tasks;
+                DROP TABLE IF EXISTS lists;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 2,
+            description: "add_echo_reports",
+            sql: r#"
+                -- Echo Reports table
+                CREATE TABLE IF NOT EXISTS echo_reports (
+                    id TEXT PRIMARY KEY,
+                    created_at INTEGER NOT NULL,
+                    content TEXT NOT NULL,
+                    job_types TEXT NOT NULL, -- JSON array
+                    style TEXT NOT NULL,
+                    user_input TEXT
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_echo_reports_created_at ON echo_reports(created_at);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 2,
+            description: "add_echo_reports",
+            sql: r#"
+                DROP TABLE IF EXISTS echo_reports;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 3,
+            description: "add_fired_reminders",
+            sql: r#"
+                -- Reminders already delivered, so restarts don't notify twice
+                CREATE TABLE IF NOT EXISTS fired_reminders (
+                    task_id TEXT NOT NULL,
+                    due_date INTEGER NOT NULL,
+                    fired_at INTEGER NOT NULL,
+                    PRIMARY KEY (task_id, due_date),
+                    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 3,
+            description: "add_fired_reminders",
+            sql: r#"
+                DROP TABLE IF EXISTS fired_reminders;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 4,
+            description: "add_recurrence_rule",
+            sql: r#"
+                -- RFC 5545 RRULE string for repeating tasks
+                ALTER TABLE tasks ADD COLUMN recurrence_rule TEXT;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 4,
+            description: "add_recurrence_rule",
+            sql: r#"
+                ALTER TABLE tasks DROP COLUMN recurrence_rule;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 5,
+            description: "create_tasks_fts",
+            sql: r#"
+                -- Full-text index over task titles and content
+                CREATE VIRTUAL TABLE IF NOT EXISTS tasks_fts USING fts5(
+                    task_id UNINDEXED,
+                    title,
+                    content,
+                    tokenize = 'unicode61 remove_diacritics 2'
+                );
+
+                -- The insert trigger clears any previous entry first: INSERT OR REPLACE
+                -- doesn't fire delete triggers, so it would otherwise leave a stale row
+                CREATE TRIGGER IF NOT EXISTS tasks_fts_insert AFTER INSERT ON tasks BEGIN
+                    DELETE FROM tasks_fts WHERE task_id = new.id;
+                    INSERT INTO tasks_fts (task_id, title, content)
+                    VALUES (new.id, new.title, COALESCE(new.content, ''));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS tasks_fts_update AFTER UPDATE OF title, content ON tasks BEGIN
+                    DELETE FROM tasks_fts WHERE task_id = old.id;
+                    INSERT INTO tasks_fts (task_id, title, content)
+                    VALUES (new.id, new.title, COALESCE(new.content, ''));
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS tasks_fts_delete AFTER DELETE ON tasks BEGIN
+                    DELETE FROM tasks_fts WHERE task_id = old.id;
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "create_tasks_fts",
+            sql: r#"
+                DROP TRIGGER IF EXISTS tasks_fts_insert;
+                DROP TRIGGER IF EXISTS tasks_fts_update;
+                DROP TRIGGER IF EXISTS tasks_fts_delete;
+                DROP TABLE IF EXISTS tasks_fts;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 6,
+            description: "backfill_tasks_fts",
+            sql: r#"
+                DELETE FROM tasks_fts;
+                INSERT INTO tasks_fts (task_id, title, content)
+                SELECT id, title, COALESCE(content, '') FROM tasks;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "backfill_tasks_fts",
+            sql: r#"
+                DELETE FROM tasks_fts;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 7,
+            description: "add_tombstones",
+            sql: r#"
+                -- Deleted rows, so sync can carry deletions to other devices.
+                -- Re-inserting a row (including INSERT OR REPLACE) clears its tombstone
+                CREATE TABLE IF NOT EXISTS tombstones (
+                    table_name TEXT NOT NULL,
+                    row_id TEXT NOT NULL,
+                    deleted_at INTEGER NOT NULL,
+                    PRIMARY KEY (table_name, row_id)
+                );
+
+                CREATE TRIGGER IF NOT EXISTS lists_tombstone_delete AFTER DELETE ON lists BEGIN
+                    INSERT OR REPLACE INTO tombstones (table_name, row_id, deleted_at)
+                    VALUES ('lists', old.id, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                END;
+                CREATE TRIGGER IF NOT EXISTS lists_tombstone_insert AFTER INSERT ON lists BEGIN
+                    DELETE FROM tombstones WHERE table_name = 'lists' AND row_id = new.id;
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS tasks_tombstone_delete AFTER DELETE ON tasks BEGIN
+                    INSERT OR REPLACE INTO tombstones (table_name, row_id, deleted_at)
+                    VALUES ('tasks', old.id, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                END;
+                CREATE TRIGGER IF NOT EXISTS tasks_tombstone_insert AFTER INSERT ON tasks BEGIN
+                    DELETE FROM tombstones WHERE table_name = 'tasks' AND row_id = new.id;
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS subtasks_tombstone_delete AFTER DELETE ON subtasks BEGIN
+                    INSERT OR REPLACE INTO tombstones (table_name, row_id, deleted_at)
+                    VALUES ('subtasks', old.id, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                END;
+                CREATE TRIGGER IF NOT EXISTS subtasks_tombstone_insert AFTER INSERT ON subtasks BEGIN
+                    DELETE FROM tombstones WHERE table_name = 'subtasks' AND row_id = new.id;
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS summaries_tombstone_delete AFTER DELETE ON summaries BEGIN
+                    INSERT OR REPLACE INTO tombstones (table_name, row_id, deleted_at)
+                    VALUES ('summaries', old.id, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                END;
+                CREATE TRIGGER IF NOT EXISTS summaries_tombstone_insert AFTER INSERT ON summaries BEGIN
+                    DELETE FROM tombstones WHERE table_name = 'summaries' AND row_id = new.id;
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS echo_reports_tombstone_delete AFTER DELETE ON echo_reports BEGIN
+                    INSERT OR REPLACE INTO tombstones (table_name, row_id, deleted_at)
+                    VALUES ('echo_reports', old.id, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                END;
+                CREATE TRIGGER IF NOT EXISTS echo_reports_tombstone_insert AFTER INSERT ON echo_reports BEGIN
+                    DELETE FROM tombstones WHERE table_name = 'echo_reports' AND row_id = new.id;
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "add_tombstones",
+            sql: r#"
+                DROP TRIGGER IF EXISTS lists_tombstone_delete;
+                DROP TRIGGER IF EXISTS lists_tombstone_insert;
+                DROP TRIGGER IF EXISTS tasks_tombstone_delete;
+                DROP TRIGGER IF EXISTS tasks_tombstone_insert;
+                DROP TRIGGER IF EXISTS subtasks_tombstone_delete;
+                DROP TRIGGER IF EXISTS subtasks_tombstone_insert;
+                DROP TRIGGER IF EXISTS summaries_tombstone_delete;
+                DROP TRIGGER IF EXISTS summaries_tombstone_insert;
+                DROP TRIGGER IF EXISTS echo_reports_tombstone_delete;
+                DROP TRIGGER IF EXISTS echo_reports_tombstone_insert;
+                DROP TABLE IF EXISTS tombstones;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 8,
+            description: "add_attachments",
+            sql: r#"
+                -- Files attached to tasks; stored_path is relative to the attachments folder
+                CREATE TABLE IF NOT EXISTS attachments (
+                    id TEXT PRIMARY KEY,
+                    task_id TEXT NOT NULL,
+                    filename TEXT NOT NULL,
+                    stored_path TEXT NOT NULL,
+                    mime TEXT NOT NULL,
+                    size INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_attachments_task_id ON attachments(task_id);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "add_attachments",
+            sql: r#"
+                DROP TABLE IF EXISTS attachments;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 9,
+            description: "add_reminder_snoozes",
+            sql: r#"
+                -- A snoozed reminder, tied to the due date it was for so editing the task drops it
+                CREATE TABLE IF NOT EXISTS reminder_snoozes (
+                    task_id TEXT PRIMARY KEY,
+                    due_date INTEGER NOT NULL,
+                    remind_at INTEGER NOT NULL,
+                    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "add_reminder_snoozes",
+            sql: r#"
+                DROP TABLE IF EXISTS reminder_snoozes;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 10,
+            description: "add_task_color_and_sort_indexes",
+            sql: r#"
+                -- Optional per-task accent color, as #rrggbb
+                ALTER TABLE tasks ADD COLUMN color TEXT;
+
+                -- Common sorts of query_tasks
+                CREATE INDEX IF NOT EXISTS idx_tasks_list_order ON tasks(list_id, "order");
+                CREATE INDEX IF NOT EXISTS idx_tasks_priority ON tasks(priority);
+                CREATE INDEX IF NOT EXISTS idx_tasks_created_at ON tasks(created_at);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "add_task_color_and_sort_indexes",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_tasks_created_at;
+                DROP INDEX IF EXISTS idx_tasks_priority;
+                DROP INDEX IF EXISTS idx_tasks_list_order;
+                ALTER TABLE tasks DROP COLUMN color;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 11,
+            description: "add_task_dependencies",
+            sql: r#"
+                -- task_id can't be completed until depends_on_id is
+                CREATE TABLE IF NOT EXISTS task_dependencies (
+                    id TEXT PRIMARY KEY,
+                    task_id TEXT NOT NULL,
+                    depends_on_id TEXT NOT NULL,
+                    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now') * 1000),
+                    UNIQUE (task_id, depends_on_id),
+                    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+                    FOREIGN KEY (depends_on_id) REFERENCES tasks(id) ON DELETE CASCADE
+                );
+                CREATE INDEX IF NOT EXISTS idx_task_dependencies_depends_on ON task_dependencies(depends_on_id);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "add_task_dependencies",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_task_dependencies_depends_on;
+                DROP TABLE IF EXISTS task_dependencies;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 12,
+            description: "add_task_start_date",
+            sql: r#"
+                -- Deferred tasks stay hidden until this time, epoch millis
+                ALTER TABLE tasks ADD COLUMN start_date INTEGER;
+                CREATE INDEX IF NOT EXISTS idx_tasks_start_date ON tasks(start_date);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "add_task_start_date",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_tasks_start_date;
+                ALTER TABLE tasks DROP COLUMN start_date;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 13,
+            description: "add_list_task_defaults",
+            sql: r#"
+                -- Applied by create_task when the input leaves the field out
+                ALTER TABLE lists ADD COLUMN default_priority INTEGER;
+                ALTER TABLE lists ADD COLUMN default_due_offset_days INTEGER;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "add_list_task_defaults",
+            sql: r#"
+                ALTER TABLE lists DROP COLUMN default_due_offset_days;
+                ALTER TABLE lists DROP COLUMN default_priority;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 14,
+            description: "add_archive_tables",
+            sql: r#"
+                -- Old completed tasks moved out of the hot table, ids and timestamps intact
+                CREATE TABLE IF NOT EXISTS archived_tasks (
+                    id TEXT PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    completed INTEGER NOT NULL DEFAULT 0,
+                    completed_at INTEGER,
+                    complete_percentage INTEGER,
+                    due_date INTEGER,
+                    list_id TEXT,
+                    list_name TEXT NOT NULL,
+                    content TEXT,
+                    "order" INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    tags TEXT,
+                    priority INTEGER,
+                    group_category TEXT NOT NULL DEFAULT 'nodate',
+                    recurrence_rule TEXT,
+                    color TEXT,
+                    start_date INTEGER,
+                    archived_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_archived_tasks_completed_at ON archived_tasks(completed_at);
+
+                CREATE TABLE IF NOT EXISTS archived_subtasks (
+                    id TEXT PRIMARY KEY,
+                    parent_id TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    completed INTEGER NOT NULL DEFAULT 0,
+                    completed_at INTEGER,
+                    due_date INTEGER,
+                    "order" INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    FOREIGN KEY (parent_id) REFERENCES archived_tasks (id) ON DELETE CASCADE
+                );
+                CREATE INDEX IF NOT EXISTS idx_archived_subtasks_parent ON archived_subtasks(parent_id);
+
+                -- Searched only when asked to; rows never change while archived
+                CREATE VIRTUAL TABLE IF NOT EXISTS archived_tasks_fts USING fts5(
+                    task_id UNINDEXED,
+                    title,
+                    content,
+                    tokenize = 'unicode61 remove_diacritics 2'
+                );
+                CREATE TRIGGER IF NOT EXISTS archived_tasks_fts_insert AFTER INSERT ON archived_tasks BEGIN
+                    DELETE FROM archived_tasks_fts WHERE task_id = new.id;
+                    INSERT INTO archived_tasks_fts (task_id, title, content)
+                    VALUES (new.id, new.title, COALESCE(new.content, ''));
+                END;
+                CREATE TRIGGER IF NOT EXISTS archived_tasks_fts_delete AFTER DELETE ON archived_tasks BEGIN
+                    DELETE FROM archived_tasks_fts WHERE task_id = old.id;
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "add_archive_tables",
+            sql: r#"
+                DROP TRIGGER IF EXISTS archived_tasks_fts_delete;
+                DROP TRIGGER IF EXISTS archived_tasks_fts_insert;
+                DROP TABLE IF EXISTS archived_tasks_fts;
+                DROP TABLE IF EXISTS archived_subtasks;
+                DROP TABLE IF EXISTS archived_tasks;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 15,
+            description: "add_templates",
+            sql: r#"
+                -- Reusable task sets; payload is JSON written by templates.rs
+                CREATE TABLE IF NOT EXISTS templates (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "add_templates",
+            sql: r#"
+                DROP TABLE IF EXISTS templates;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 16,
+            description: "add_streaks",
+            sql: r#"
+                -- One row per recurring series, keyed by the occurrence that started it
+                CREATE TABLE IF NOT EXISTS streaks (
+                    id TEXT PRIMARY KEY,
+                    task_id TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    current_streak INTEGER NOT NULL DEFAULT 0,
+                    longest_streak INTEGER NOT NULL DEFAULT 0,
+                    last_completed_day TEXT,
+                    updated_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_streaks_task_id ON streaks(task_id);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "add_streaks",
+            sql: r#"
+                DROP TABLE IF EXISTS streaks;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 17,
+            description: "add_soft_delete",
+            sql: r#"
+                -- Set when a row moves to the trash, epoch millis; emptying the trash deletes it for good
+                ALTER TABLE tasks ADD COLUMN deleted_at INTEGER;
+                ALTER TABLE lists ADD COLUMN deleted_at INTEGER;
+                CREATE INDEX IF NOT EXISTS idx_tasks_deleted_at ON tasks(deleted_at);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "add_soft_delete",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_tasks_deleted_at;
+                ALTER TABLE lists DROP COLUMN deleted_at;
+                ALTER TABLE tasks DROP COLUMN deleted_at;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 18,
+            description: "add_snoozes",
+            sql: r#"
+                -- A one-off reminder at a chosen time, alongside (not instead of) the due-date reminder
+                CREATE TABLE IF NOT EXISTS snoozes (
+                    task_id TEXT PRIMARY KEY,
+                    remind_at INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+                );
+                CREATE INDEX IF NOT EXISTS idx_snoozes_remind_at ON snoozes(remind_at);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 18,
+            description: "add_snoozes",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_snoozes_remind_at;
+                DROP TABLE IF EXISTS snoozes;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 19,
+            description: "add_attachment_blobs",
+            sql: r#"
+                -- Contents of small attachments kept in the database; their stored_path is empty
+                CREATE TABLE IF NOT EXISTS attachment_blobs (
+                    attachment_id TEXT PRIMARY KEY,
+                    data BLOB NOT NULL,
+                    FOREIGN KEY (attachment_id) REFERENCES attachments (id) ON DELETE CASCADE
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 19,
+            description: "add_attachment_blobs",
+            sql: r#"
+                DROP TABLE IF EXISTS attachment_blobs;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 20,
+            description: "add_task_pinned",
+            sql: r#"
+                -- Pinned tasks sort above the rest wherever they're listed; "order" is left alone
+                ALTER TABLE tasks ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE archived_tasks ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+                CREATE INDEX IF NOT EXISTS idx_tasks_pinned ON tasks(pinned);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 20,
+            description: "add_task_pinned",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_tasks_pinned;
+                ALTER TABLE archived_tasks DROP COLUMN pinned;
+                ALTER TABLE tasks DROP COLUMN pinned;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 21,
+            description: "add_held_reminders",
+            sql: r#"
+                -- Reminders that came due while the user was away, delivered on return
+                CREATE TABLE IF NOT EXISTS held_reminders (
+                    task_id TEXT PRIMARY KEY,
+                    held_at INTEGER NOT NULL,
+                    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+                );
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 21,
+            description: "add_held_reminders",
+            sql: r#"
+                DROP TABLE IF EXISTS held_reminders;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 22,
+            description: "add_time_tracking",
+            sql: r#"
+                -- Planned and tracked effort in minutes; completed focus sessions add to actual_minutes
+                ALTER TABLE tasks ADD COLUMN estimated_minutes INTEGER;
+                ALTER TABLE tasks ADD COLUMN actual_minutes INTEGER;
+                ALTER TABLE archived_tasks ADD COLUMN estimated_minutes INTEGER;
+                ALTER TABLE archived_tasks ADD COLUMN actual_minutes INTEGER;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 22,
+            description: "add_time_tracking",
+            sql: r#"
+                ALTER TABLE archived_tasks DROP COLUMN actual_minutes;
+                ALTER TABLE archived_tasks DROP COLUMN estimated_minutes;
+                ALTER TABLE tasks DROP COLUMN actual_minutes;
+                ALTER TABLE tasks DROP COLUMN estimated_minutes;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 23,
+            description: "add_summaries_list_created_index",
+            sql: r#"
+                -- Newest-first per list, for pruning old summaries and the latest-summary lookup
+                CREATE INDEX IF NOT EXISTS idx_summaries_list_created ON summaries(list_key, created_at DESC, id DESC);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 23,
+            description: "add_summaries_list_created_index",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_summaries_list_created;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 24,
+            description: "add_subtasks_due_date_index",
+            sql: r#"
+                -- The agenda looks subtasks up by their own due date
+                CREATE INDEX IF NOT EXISTS idx_subtasks_due_date ON subtasks(due_date);
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 24,
+            description: "add_subtasks_due_date_index",
+            sql: r#"
+                DROP INDEX IF EXISTS idx_subtasks_due_date;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 25,
+            description: "add_task_board_status",
+            sql: r#"
+                -- Board column ('backlog' or 'in_progress'; completed tasks are done whatever it says)
+                -- and position in it, apart from the list's "order". NULL for tasks never on the board
+                ALTER TABLE tasks ADD COLUMN status TEXT;
+                ALTER TABLE tasks ADD COLUMN board_order INTEGER;
+                ALTER TABLE archived_tasks ADD COLUMN status TEXT;
+                ALTER TABLE archived_tasks ADD COLUMN board_order INTEGER;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 25,
+            description: "add_task_board_status",
+            sql: r#"
+                ALTER TABLE archived_tasks DROP COLUMN board_order;
+                ALTER TABLE archived_tasks DROP COLUMN status;
+                ALTER TABLE tasks DROP COLUMN board_order;
+                ALTER TABLE tasks DROP COLUMN status;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 26,
+            description: "add_reminder_offset",
+            sql: r#"
+                -- Minutes before due_date the reminder fires; NULL follows the reminderOffsetMinutes setting
+                ALTER TABLE tasks ADD COLUMN reminder_offset_minutes INTEGER;
+                ALTER TABLE archived_tasks ADD COLUMN reminder_offset_minutes INTEGER;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 26,
+            description: "add_reminder_offset",
+            sql: r#"
+                ALTER TABLE archived_tasks DROP COLUMN reminder_offset_minutes;
+                ALTER TABLE tasks DROP COLUMN reminder_offset_minutes;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 27,
+            description: "add_list_name_cascade",
+            sql: r#"
+                -- Keeps the denormalized list_name in step with however a list gets renamed,
+                -- the webview's own SQL included. Tasks in the legacy 'Trash' keep that name
+                CREATE TRIGGER IF NOT EXISTS lists_name_cascade AFTER UPDATE OF name ON lists
+                WHEN NEW.name IS NOT OLD.name BEGIN
+                    UPDATE tasks SET list_name = NEW.name WHERE list_id = NEW.id AND list_name != 'Trash';
+                    UPDATE archived_tasks SET list_name = NEW.name WHERE list_id = NEW.id AND list_name != 'Trash';
+                END;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 27,
+            description: "add_list_name_cascade",
+            sql: r#"
+                DROP TRIGGER IF EXISTS lists_name_cascade;
+            "#,
+            kind: MigrationKind::Down,
+        },
+        Migration {
+            version: 28,
+            description: "add_list_webhook_flag",
+            sql: r#"
+                -- Whether the outbound webhook fires for the list's tasks
+                ALTER TABLE lists ADD COLUMN webhook_enabled INTEGER NOT NULL DEFAULT 1;
+            "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 28,
+            description: "add_list_webhook_flag",
+            sql: r#"
+                ALTER TABLE lists DROP COLUMN webhook_enabled;
+            "#,
+            kind: MigrationKind::Down,
+        }
+    ]
+}
+
+/// Schema version of the newest migration this build knows about.
+pub fn latest_version() -> i64 {
+    all().iter().map(|m| m.version).max().unwrap_or_default()
+}
+
+/// Feeds the same migration list to sqlx, so Rust can migrate a database the
+/// plugin hasn't opened. Checksums match the ones the plugin records.
+struct MigrationList(Vec<Migration>);
+
+impl MigrationSource<'static> for MigrationList {
+    fn resolve(self) -> BoxFuture<'static, std::result::Result<Vec<SqlxMigration>, BoxDynError>> {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .into_iter()
+                .map(|m| {
+                    let kind = match m.kind {
+                        MigrationKind::Up => MigrationType::ReversibleUp,
+                        MigrationKind::Down => MigrationType::ReversibleDown,
+                    };
+                    SqlxMigration::new(m.version, m.description.into(), kind, m.sql.into(), false)
+                })
+                .collect())
+        })
+    }
+}
+
+/// Applies any pending migrations to the given database.
+pub async fn run(pool: &SqlitePool) -> Result<()> {
+    let migrator = Migrator::new(MigrationList(all())).await?;
+    migrator.run(pool).await?;
+    Ok(())
+}
+
+/// The newest schema a database has been migrated to, by whichever build: the
+/// highest applied migration, or `PRAGMA user_version` if that says more.
+pub async fn applied_version(conn: &mut SqliteConnection) -> Result<i64> {
+    let user_version: i64 = sqlx::query_scalar("PRAGMA user_version").fetch_one(&mut *conn).await?;
+    let tracked: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    let applied: Option<i64> = if tracked {
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(&mut *conn)
+            .await?
+    } else {
+        None
+    };
+    Ok(user_version.max(applied.unwrap_or_default()))
+}
+
+/// Refuses a database a newer build has migrated past what this one knows.
+/// This build would misread the newer schema or fail partway through its own
+/// migrations, so callers stop before opening it for anything else.
+pub async fn ensure_supported(conn: &mut SqliteConnection) -> Result<i64> {
+    let found = applied_version(conn).await?;
+    let supported = latest_version();
+    if found > supported {
+        return Err(Error::SchemaTooNew { found, supported });
+    }
+    Ok(found)
+}
+
+/// Mirrors the highest applied migration into `PRAGMA user_version`, so the
+/// live schema level can be read with any SQLite tool, and returns it. A file
+/// that was never migrated has no `_sqlx_migrations` table and is at 0.
+pub async fn sync_user_version(pool: &SqlitePool) -> Result<i64> {
+    let tracked: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(pool)
+    .await?;
+    let applied: Option<i64> = if tracked {
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(pool)
+            .await?
+    } else {
+        None
+    };
+    let version = applied.unwrap_or_default();
+    // PRAGMA values can't be bound; this is an integer we produced
+    sqlx::query(&format!("PRAGMA user_version = {version}"))
+        .execute(pool)
+        .await?;
+    Ok(version)
+}
+
+/// Drops every table and re-runs the migrations from scratch. Development builds only.
+#[cfg(debug_assertions)]
+#[tauri::command]
+pub async fn reset_database(app: tauri::AppHandle, state: tauri::State<'_, crate::AppState>) -> Result<i64> {
+    log::warn!("Resetting the database");
+    let mut conn = state.db().acquire().await?;
+    sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+
+    // Virtual tables go first; dropping them takes their shadow tables along
+    let tables: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT name FROM sqlite_master
+        WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+        ORDER BY sql LIKE 'CREATE VIRTUAL TABLE%' DESC
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for table in tables {
+        sqlx::query(&format!("DROP TABLE IF EXISTS \"{table}\""))
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await?;
+    drop(conn);
+
+    run(&state.db()).await?;
+    let version = sync_user_version(&state.db()).await?;
+    crate::events::tasks_changed(&app);
+    crate::events::list_updated(&app, None);
+    Ok(version)
+}
@@ -0,0 +1,129 @@
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
+
+/// A row of the `tasks` table, serialized in the shape the frontend's `Task` type expects.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    pub title: String,
+    pub completed: bool,
+    pub completed_at: Option<i64>,
+    pub complete_percentage: Option<i64>,
+    pub due_date: Option<i64>,
+    pub list_id: Option<String>,
+    pub list_name: String,
+    pub content: Option<String>,
+    pub order: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub tags: Vec<String>,
+    pub priority: Option<i64>,
+    pub group_category: String,
+    pub recurrence_rule: Option<String>,
+    pub color: Option<String>,
+    /// Hidden from `hideFuture` queries until this time.
+    pub start_date: Option<i64>,
+    pub pinned: bool,
+    pub estimated_minutes: Option<i64>,
+    /// Sum of the completed focus sessions on the task.
+    pub actual_minutes: Option<i64>,
+    /// Board column before completion; see `board`.
+    pub status: Option<String>,
+    pub board_order: Option<i64>,
+    /// Minutes before `due_date` to remind; `None` uses the `reminderOffsetMinutes` setting.
+    pub reminder_offset_minutes: Option<i64>,
+}
+
+impl<'r> FromRow<'r, SqliteRow> for Task {
+    fn from_row(row: &'r SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            title: row.try_get("title")?,
+            completed: row.try_get("completed")?,
+            completed_at: row.try_get("completed_at")?,
+            complete_percentage: row.try_get("complete_percentage")?,
+            due_date: row.try_get("due_date")?,
+            list_id: row.try_get("list_id")?,
+            list_name: row.try_get("list_name")?,
+            content: row.try_get("content")?,
+            order: row.try_get("order")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            tags: parse_tags(row.try_get("tags")?),
+            priority: row.try_get("priority")?,
+            group_category: row.try_get("group_category")?,
+            recurrence_rule: row.try_get("recurrence_rule")?,
+            color: row.try_get("color")?,
+            start_date: row.try_get("start_date")?,
+            pinned: row.try_get("pinned")?,
+            estimated_minutes: row.try_get("estimated_minutes")?,
+            actual_minutes: row.try_get("actual_minutes")?,
+            status: row.try_get("status")?,
+            board_order: row.try_get("board_order")?,
+            reminder_offset_minutes: row.try_get("reminder_offset_minutes")?,
+        })
+    }
+}
+
+/// A row of the `subtasks` table.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Subtask {
+    pub id: String,
+    pub parent_id: String,
+    pub title: String,
+    pub completed: bool,
+    pub completed_at: Option<i64>,
+    pub due_date: Option<i64>,
+    pub order: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A row of the `attachments` table.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub id: String,
+    pub task_id: String,
+    pub filename: String,
+    pub stored_path: String,
+    pub mime: String,
+    pub size: i64,
+    pub created_at: i64,
+}
+
+/// Tags (and other id lists) are stored as a JSON array; a NULL or malformed value reads as empty.
+pub fn parse_tags(raw: Option<String>) -> Vec<String> {
+    raw.and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// A row of the `summaries` table.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    pub id: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub period_key: String,
+    pub list_key: String,
+    pub task_ids: Vec<String>,
+    pub summary_text: String,
+}
+
+impl<'r> FromRow<'r, SqliteRow> for Summary {
+    fn from_row(row: &'r SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            period_key: row.try_get("period_key")?,
+            list_key: row.try_get("list_key")?,
+            task_ids: parse_tags(row.try_get("task_ids")?),
+            summary_text: row.try_get("summary_text")?,
+        })
+    }
+}
@@ -0,0 +1,223 @@
+//! Natural-language due dates for quick add: "tomorrow 5pm", "next friday",
+//! "in 3 days", "at 9:30". Resolved in the system timezone.
+
+use chrono::{Datelike, Days, Duration, Months, NaiveDate, NaiveTime, Weekday};
+use serde::Serialize;
+
+use crate::dates::{local_date, resolve_local, start_of_local_day};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedDate {
+    pub due_date: i64,
+    /// `[start, end)` of the matched text in UTF-16 code units, for highlighting in JS.
+    pub span: [usize; 2],
+    /// Whether a time of day was given; otherwise `due_date` is local midnight.
+    pub has_time: bool,
+}
+
+/// A match in byte offsets, for callers that cut it out of the input.
+pub(crate) struct DateMatch {
+    pub due_date: i64,
+    pub has_time: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A word of the input with its byte range, lowercased and stripped of trailing punctuation.
+struct Token {
+    start: usize,
+    end: usize,
+    word: String,
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in input.char_indices().chain(std::iter::once((input.len(), ' '))) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                let raw = input[s..i].trim_end_matches([',', '.', ';', '!', '?']);
+                if !raw.is_empty() {
+                    tokens.push(Token { start: s, end: s + raw.len(), word: raw.to_lowercase() });
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+pub(crate) fn weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+pub(crate) fn number(word: &str) -> Option<u32> {
+    if let Ok(n) = word.parse() {
+        return Some(n);
+    }
+    Some(match word {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        _ => return None,
+    })
+}
+
+/// The next `day` strictly after `today`, so a weekday name never means a past date.
+fn upcoming(today: NaiveDate, day: Weekday) -> NaiveDate {
+    let ahead = (day.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(if ahead == 0 { 7 } else { ahead.into() })
+}
+
+fn word_at(tokens: &[Token], i: usize) -> Option<&str> {
+    tokens.get(i).map(|t| t.word.as_str())
+}
+
+/// A date expression starting at token `i`, with the number of tokens it used.
+fn parse_date(tokens: &[Token], i: usize, today: NaiveDate) -> Option<(NaiveDate, usize)> {
+    match word_at(tokens, i)? {
+        "today" | "tonight" => Some((today, 1)),
+        "tomorrow" | "tmr" | "tmrw" => Some((today.succ_opt()?, 1)),
+        "next" => match word_at(tokens, i + 1)? {
+            "week" => Some((today + Duration::days(7), 2)),
+            "month" => Some((today.checked_add_months(Months::new(1))?, 2)),
+            // "next friday" is the one after the upcoming friday
+            word => weekday(word).map(|day| (upcoming(today, day) + Duration::days(7), 2)),
+        },
+        "in" => {
+            let n = number(word_at(tokens, i + 1)?)?;
+            let date = match word_at(tokens, i + 2)? {
+                "day" | "days" => today.checked_add_days(Days::new(n.into()))?,
+                "week" | "weeks" => today.checked_add_days(Days::new(u64::from(n) * 7))?,
+                "month" | "months" => today.checked_add_months(Months::new(n))?,
+                _ => return None,
+            };
+            Some((date, 3))
+        }
+        "on" => weekday(word_at(tokens, i + 1)?).map(|day| (upcoming(today, day), 2)),
+        word => weekday(word).map(|day| (upcoming(today, day), 1)),
+    }
+}
+
+/// `5pm`, `5:30pm`, `17:00`, `noon`, `midnight`; `5 pm` spans two tokens.
+fn parse_clock(tokens: &[Token], i: usize) -> Option<(NaiveTime, usize)> {
+    let word = word_at(tokens, i)?;
+    match word {
+        "noon" | "midday" => return Some((NaiveTime::from_hms_opt(12, 0, 0)?, 1)),
+        "midnight" => return Some((NaiveTime::from_hms_opt(0, 0, 0)?, 1)),
+        _ => {}
+    }
+
+    let (digits, suffix, used) = if let Some(d) = word.strip_suffix("am") {
+        (d, Some(false), 1)
+    } else if let Some(d) = word.strip_suffix("pm") {
+        (d, Some(true), 1)
+    } else {
+        match word_at(tokens, i + 1) {
+            Some("am") => (word, Some(false), 2),
+            Some("pm") => (word, Some(true), 2),
+            _ => (word, None, 1),
+        }
+    };
+    let (hour, minute) = match digits.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is too ambiguous to be a time without am/pm
+        None if suffix.is_some() => (digits.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match suffix {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    Some((NaiveTime::from_hms_opt(hour, minute, 0)?, used))
+}
+
+fn parse_time(tokens: &[Token], i: usize) -> Option<(NaiveTime, usize)> {
+    if word_at(tokens, i)? == "at" {
+        return parse_clock(tokens, i + 1).map(|(time, used)| (time, used + 1));
+    }
+    parse_clock(tokens, i)
+}
+
+pub(crate) fn utf16_offset(input: &str, byte: usize) -> usize {
+    input[..byte].encode_utf16().count()
+}
+
+/// Finds the first date/time expression in `input`, relative to `now`.
+pub fn parse(input: &str, now: i64) -> Option<ParsedDate> {
+    find(input, now).map(|m| ParsedDate {
+        due_date: m.due_date,
+        span: [utf16_offset(input, m.start), utf16_offset(input, m.end)],
+        has_time: m.has_time,
+    })
+}
+
+pub(crate) fn find(input: &str, now: i64) -> Option<DateMatch> {
+    let tokens = tokenize(input);
+    let today = local_date(now);
+
+    for i in 0..tokens.len() {
+        // Either side may come first: "tomorrow at 5pm", "5pm tomorrow"
+        let (date, time, used) = if let Some((date, used)) = parse_date(&tokens, i, today) {
+            match parse_time(&tokens, i + used) {
+                Some((time, more)) => (Some(date), Some(time), used + more),
+                None => (Some(date), None, used),
+            }
+        } else if let Some((time, used)) = parse_time(&tokens, i) {
+            match parse_date(&tokens, i + used, today) {
+                Some((date, more)) => (Some(date), Some(time), used + more),
+                None => (None, Some(time), used),
+            }
+        } else {
+            continue;
+        };
+
+        let due_date = match (date, time) {
+            (Some(date), Some(time)) => resolve_local(date.and_time(time))?.timestamp_millis(),
+            (Some(date), None) => start_of_local_day(date),
+            // A bare time means its next occurrence: later today, or tomorrow if it has passed
+            (None, Some(time)) => {
+                let later_today = resolve_local(today.and_time(time))?.timestamp_millis();
+                if later_today > now {
+                    later_today
+                } else {
+                    resolve_local(today.succ_opt()?.and_time(time))?.timestamp_millis()
+                }
+            }
+            (None, None) => continue,
+        };
+
+        return Some(DateMatch {
+            due_date,
+            has_time: time.is_some(),
+            start: tokens[i].start,
+            end: tokens[i + used - 1].end,
+        });
+    }
+    None
+}
+
+#[tauri::command]
+pub fn parse_due_date(input: String, now_ms: i64) -> Option<ParsedDate> {
+    parse(&input, now_ms)
+}
@@ -0,0 +1,231 @@
+//! Recurrence in words: "every weekday", "every 2 weeks on Monday", "monthly
+//! on the 15th", "every year". Turned into the RRULE stored in
+//! `recurrence_rule`, and described back the same way for the UI.
+
+use chrono::{NaiveDate, Weekday};
+use serde::Serialize;
+
+use crate::dates::{local_date, resolve_local};
+use crate::error::Result;
+use crate::nlp_date::{number, weekday};
+use crate::recurrence::{Freq, RRule};
+
+const WEEKDAYS: [Weekday; 5] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+const WEEKEND: [Weekday; 2] = [Weekday::Sat, Weekday::Sun];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedRecurrence {
+    pub rule: String,
+    /// The rule in words, as `describe_recurrence` gives it.
+    pub description: String,
+}
+
+/// Lowercased words, without commas, trailing punctuation or the filler "and"/"the".
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace(',', " ")
+        .split_whitespace()
+        .map(|word| word.trim_end_matches(['.', ';', '!', '?']).to_string())
+        .filter(|word| !matches!(word.as_str(), "" | "and" | "the"))
+        .collect()
+}
+
+/// A weekday name, also in the plural ("mondays").
+fn day_name(word: &str) -> Option<Weekday> {
+    weekday(word).or_else(|| weekday(word.strip_suffix('s')?))
+}
+
+/// A run of days like "monday thursday" or "weekdays", with the number of words used.
+fn parse_days(words: &[String]) -> Option<(Vec<Weekday>, usize)> {
+    let mut days = Vec::new();
+    let mut used = 0;
+    for word in words {
+        match word.as_str() {
+            "weekday" | "weekdays" => days.extend(WEEKDAYS),
+            "weekend" | "weekends" => days.extend(WEEKEND),
+            word => match day_name(word) {
+                Some(day) => days.push(day),
+                None => break,
+            },
+        }
+        used += 1;
+    }
+    days.sort_by_key(|day| day.num_days_from_monday());
+    days.dedup();
+    (!days.is_empty()).then_some((days, used))
+}
+
+/// "15th", "1st" or a plain "15", as a day of the month.
+fn parse_ordinal(word: &str) -> Option<u32> {
+    let digits = ["st", "nd", "rd", "th"].iter().find_map(|s| word.strip_suffix(s)).unwrap_or(word);
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// Parses recurrence in words into a rule, `None` if any of it isn't understood.
+pub fn parse(text: &str) -> Option<RRule> {
+    let words = words(text);
+    let word = |i: usize| words.get(i).map(String::as_str);
+    let mut rule = RRule {
+        freq: Freq::Daily,
+        interval: 1,
+        by_day: Vec::new(),
+        by_month_day: None,
+        count: None,
+        until: None,
+    };
+
+    let mut i = 1;
+    match word(0)? {
+        "daily" => rule.freq = Freq::Daily,
+        "weekly" => rule.freq = Freq::Weekly,
+        "monthly" => rule.freq = Freq::Monthly,
+        "yearly" | "annually" => rule.freq = Freq::Yearly,
+        "every" | "each" => {
+            let interval = match word(i)? {
+                "other" => Some(2),
+                word => number(word),
+            };
+            if let Some(interval) = interval {
+                rule.interval = interval;
+                i += 1;
+            }
+            rule.freq = match word(i)? {
+                "day" | "days" => Freq::Daily,
+                "week" | "weeks" => Freq::Weekly,
+                "month" | "months" => Freq::Monthly,
+                "year" | "years" => Freq::Yearly,
+                // "every monday and thursday", "every weekday"
+                _ if interval.is_none() => {
+                    let (days, used) = parse_days(&words[i..])?;
+                    rule.by_day = days;
+                    i += used - 1;
+                    Freq::Weekly
+                }
+                _ => return None,
+            };
+            i += 1;
+        }
+        _ => return None,
+    }
+    if rule.interval == 0 {
+        return None;
+    }
+
+    while let Some(current) = word(i) {
+        match current {
+            "on" => match rule.freq {
+                Freq::Daily | Freq::Weekly if rule.by_day.is_empty() => {
+                    let (days, used) = parse_days(&words[i + 1..])?;
+                    rule.by_day = days;
+                    i += 1 + used;
+                }
+                Freq::Monthly if rule.by_month_day.is_none() => {
+                    // "on the 15th", "on day 15"
+                    let at = if word(i + 1) == Some("day") { i + 2 } else { i + 1 };
+                    rule.by_month_day = Some(parse_ordinal(word(at)?)?);
+                    i = at + 1;
+                }
+                _ => return None,
+            },
+            "until" => {
+                let date = NaiveDate::parse_from_str(word(i + 1)?, "%Y-%m-%d").ok()?;
+                // The whole day, as a date-only UNTIL means
+                rule.until = Some(resolve_local(date.and_hms_opt(23, 59, 59)?)?.timestamp_millis());
+                i += 2;
+            }
+            "for" => i += 1,
+            "once" => {
+                rule.count = Some(1);
+                i += 1;
+            }
+            word if matches!(words.get(i + 1).map(String::as_str), Some("time" | "times")) => {
+                rule.count = Some(number(word).filter(|n| *n > 0)?);
+                i += 2;
+            }
+            _ => return None,
+        }
+    }
+    Some(rule)
+}
+
+fn day_full_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// "Monday", "Monday and Friday", "Monday, Wednesday and Friday".
+fn join_days(days: &[Weekday]) -> String {
+    let names: Vec<&str> = days.iter().map(|day| day_full_name(*day)).collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+        _ => names.join(""),
+    }
+}
+
+fn ordinal(day: u32) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{day}{suffix}")
+}
+
+/// A rule in words, phrased so `parse` reads it back to the same rule.
+pub fn describe(rule: &RRule) -> String {
+    let every = |one: &str, many: &str| match rule.interval {
+        1 => format!("Every {one}"),
+        2 => format!("Every other {one}"),
+        n => format!("Every {n} {many}"),
+    };
+    let mut text = match rule.freq {
+        Freq::Weekly if rule.interval == 1 && rule.by_day == WEEKDAYS => "Every weekday".to_string(),
+        Freq::Weekly if rule.interval == 1 && rule.by_day == WEEKEND => "Every weekend".to_string(),
+        Freq::Weekly if rule.interval == 1 && !rule.by_day.is_empty() => {
+            format!("Every {}", join_days(&rule.by_day))
+        }
+        Freq::Daily => every("day", "days"),
+        Freq::Weekly => every("week", "weeks"),
+        Freq::Monthly => every("month", "months"),
+        Freq::Yearly => every("year", "years"),
+    };
+    let spelled_out_days = rule.freq == Freq::Weekly && rule.interval == 1;
+    if !rule.by_day.is_empty() && !spelled_out_days {
+        text.push_str(&format!(" on {}", join_days(&rule.by_day)));
+    }
+    if let Some(day) = rule.by_month_day {
+        text.push_str(&format!(" on the {}", ordinal(day)));
+    }
+    match rule.count {
+        Some(1) => text.push_str(", once"),
+        Some(count) => text.push_str(&format!(", {count} times")),
+        None => {}
+    }
+    if let Some(until) = rule.until {
+        text.push_str(&format!(", until {}", local_date(until).format("%Y-%m-%d")));
+    }
+    text
+}
+
+/// Reads recurrence in words, returning the rule to store and how it reads back.
+#[tauri::command]
+pub fn parse_recurrence(text: String) -> Option<ParsedRecurrence> {
+    let rule = parse(&text)?;
+    Some(ParsedRecurrence { rule: rule.to_string(), description: describe(&rule) })
+}
+
+/// A stored rule in words.
+#[tauri::command]
+pub fn describe_recurrence(rrule: String) -> Result<String> {
+    Ok(describe(&rrule.parse()?))
+}
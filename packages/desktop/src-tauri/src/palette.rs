@@ -0,0 +1,153 @@
+//! Backend for the command palette: one ranked list of tasks, lists and app actions.
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::error::Result;
+use crate::search::to_match_query;
+use crate::{collation, AppState};
+
+const MAX_RESULTS: usize = 20;
+/// FTS candidates fetched before re-scoring against the titles.
+const TASK_CANDIDATES: i64 = 50;
+/// Tasks only found through their notes rank below any title match.
+const CONTENT_ONLY_SCORE: i64 = 1;
+
+/// Built-in actions as `(action, title)`; the frontend dispatches on the action string.
+const ACTIONS: &[(&str, &str)] = &[
+    ("create-task", "New task"),
+    ("create-list", "Create list"),
+    ("open-settings", "Settings"),
+    ("export", "Export"),
+    ("import", "Import"),
+    ("sync-now", "Sync now"),
+    ("open-summary", "Summary"),
+    ("check-updates", "Check for updates"),
+    ("open-log-dir", "Open log folder"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PaletteKind {
+    Action,
+    List,
+    Task,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteResult {
+    #[serde(rename = "type")]
+    pub kind: PaletteKind,
+    /// Task or list id, or the action name.
+    pub id: String,
+    pub title: String,
+    /// The list a task is in.
+    pub subtitle: Option<String>,
+    pub score: i64,
+}
+
+/// Scores `text` as a fuzzy match for `pattern`: every pattern character must appear
+/// in order. Consecutive runs, word starts and an early first match score higher.
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let pattern: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for (i, &c) in text.iter().enumerate() {
+        if next == pattern.len() {
+            break;
+        }
+        if c != pattern[next] {
+            continue;
+        }
+        score += 10;
+        if previous.is_some_and(|p| p + 1 == i) {
+            score += 15;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 20;
+        }
+        if next == 0 {
+            score -= i.min(20) as i64;
+        }
+        previous = Some(i);
+        next += 1;
+    }
+    // Shorter texts win among equally good matches
+    (next == pattern.len()).then(|| score * 100 / (100 + text.len() as i64))
+}
+
+/// Results sorted by score, ties broken on kind, title and id so the order is
+/// the same for the same input.
+#[tauri::command]
+pub async fn search_everything(state: State<'_, AppState>, query: String) -> Result<Vec<PaletteResult>> {
+    let query = query.trim();
+    let mut results: Vec<PaletteResult> = ACTIONS
+        .iter()
+        .filter_map(|(action, title)| {
+            fuzzy_score(query, title).map(|score| PaletteResult {
+                kind: PaletteKind::Action,
+                id: action.to_string(),
+                title: title.to_string(),
+                subtitle: None,
+                score,
+            })
+        })
+        .collect();
+
+    if !query.is_empty() {
+        let lists: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, name FROM lists WHERE name != 'Trash' AND deleted_at IS NULL")
+                .fetch_all(&state.db())
+                .await?;
+        results.extend(lists.into_iter().filter_map(|(id, name)| {
+            fuzzy_score(query, &name).map(|score| PaletteResult {
+                kind: PaletteKind::List,
+                id,
+                title: name,
+                subtitle: None,
+                score,
+            })
+        }));
+    }
+
+    if let Some(match_query) = to_match_query(query) {
+        let tasks: Vec<(String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT t.id, t.title, t.list_name
+            FROM tasks_fts
+            JOIN tasks t ON t.id = tasks_fts.task_id
+            WHERE tasks_fts MATCH ? AND t.list_name != 'Trash' AND t.deleted_at IS NULL
+            ORDER BY bm25(tasks_fts, 0.0, 10.0, 1.0), t.id
+            LIMIT ?
+            "#,
+        )
+        .bind(&match_query)
+        .bind(TASK_CANDIDATES)
+        .fetch_all(&state.db())
+        .await?;
+        results.extend(tasks.into_iter().map(|(id, title, list_name)| PaletteResult {
+            kind: PaletteKind::Task,
+            score: fuzzy_score(query, &title).unwrap_or(CONTENT_ONLY_SCORE),
+            id,
+            title,
+            subtitle: Some(list_name),
+        }));
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.kind.cmp(&b.kind))
+            .then_with(|| collation::compare(&a.title, &b.title))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    results.truncate(MAX_RESULTS);
+    Ok(results)
+}
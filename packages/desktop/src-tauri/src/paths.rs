@@ -0,0 +1,102 @@
+//! Where Tada keeps its files.
+//!
+//! Normally that's the OS app dirs. In portable mode, switched on by a
+//! `tada-portable.txt` file beside the executable, everything (databases,
+//! attachments, backups, logs and config files) lives in a `data/` folder
+//! beside it instead, so the install can move between machines on a USB stick.
+//! Nothing stores an absolute path into that folder: attachments and
+//! workspaces are named relative to it, and the database URL the SQL plugin
+//! and webview use is rebuilt from it at every launch.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::Result;
+use crate::workspaces;
+
+const PORTABLE_MARKER: &str = "tada-portable.txt";
+const PORTABLE_DIR: &str = "data";
+const PORTABLE_LOG_DIR: &str = "logs";
+
+static PORTABLE_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// The `data/` folder beside the executable, if portable mode is on. Checked
+/// once per run; the folder is created then, as the OS dirs already exist.
+pub fn portable_root() -> Option<&'static Path> {
+    PORTABLE_ROOT
+        .get_or_init(|| {
+            let exe = std::env::current_exe().ok()?;
+            let dir = exe.parent()?;
+            if !dir.join(PORTABLE_MARKER).is_file() {
+                return None;
+            }
+            let root = dir.join(PORTABLE_DIR);
+            if let Err(e) = std::fs::create_dir_all(&root) {
+                eprintln!("Failed to create portable data folder {}: {e}", root.display());
+            }
+            Some(root)
+        })
+        .as_deref()
+}
+
+/// Holds the databases and small config files like `workspaces.json`.
+pub fn config_dir(app: &AppHandle) -> Result<PathBuf> {
+    match portable_root() {
+        Some(root) => Ok(root.to_path_buf()),
+        None => Ok(app.path().app_config_dir()?),
+    }
+}
+
+/// Holds attachments and backups.
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf> {
+    match portable_root() {
+        Some(root) => Ok(root.to_path_buf()),
+        None => Ok(app.path().app_data_dir()?),
+    }
+}
+
+pub fn log_dir(app: &AppHandle) -> Result<PathBuf> {
+    match portable_root() {
+        Some(root) => Ok(root.join(PORTABLE_LOG_DIR)),
+        None => Ok(app.path().app_log_dir()?),
+    }
+}
+
+/// The log folder when it isn't the plugin's own default, for building the log plugin.
+pub fn custom_log_dir() -> Option<PathBuf> {
+    portable_root().map(|root| root.join(PORTABLE_LOG_DIR))
+}
+
+/// The SQL plugin's connection string for a database file in the config dir.
+/// The plugin resolves relative URLs against the OS config dir, so portable
+/// mode spells the path out.
+pub fn sql_url(file: &str) -> String {
+    match portable_root() {
+        Some(root) => format!("sqlite:{}", root.join(file).display()),
+        None => format!("sqlite:{file}"),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDir {
+    pub portable: bool,
+    pub data_dir: String,
+    pub config_dir: String,
+    /// What the webview passes to `Database.load` for the active workspace.
+    pub db_url: String,
+}
+
+#[tauri::command]
+pub fn get_data_dir(app: AppHandle) -> Result<DataDir> {
+    let config_dir = config_dir(&app)?;
+    Ok(DataDir {
+        portable: portable_root().is_some(),
+        data_dir: data_dir(&app)?.display().to_string(),
+        db_url: workspaces::active_sql_url(&config_dir),
+        config_dir: config_dir.display().to_string(),
+    })
+}
@@ -0,0 +1,195 @@
+//! Holds network-heavy background jobs back while the laptop runs on battery.
+//!
+//! With `pauseBackgroundOnBattery` on, a scheduled job that would start on
+//! battery, like the startup sync, is noted as deferred instead and runs once
+//! AC power is back (or the setting is turned off). Anything the user asks for
+//! runs regardless: `sync_now`, and AI summaries, which are only ever generated
+//! on request. Reminders are time-critical and never wait.
+//!
+//! The power source comes from `/sys/class/power_supply` on Linux, `pmset` on
+//! macOS and `GetSystemPowerStatus` on Windows. Where it can't be read, the
+//! machine counts as plugged in.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{settings, sync, AppState};
+
+const PAUSE_KEY: &str = "pauseBackgroundOnBattery";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A background job that can wait for AC power.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Job {
+    Sync,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PowerSource {
+    on_battery: bool,
+    battery_percent: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerStatus {
+    /// `None` where the power source can't be read.
+    pub on_battery: Option<bool>,
+    pub battery_percent: Option<u8>,
+    /// The `pauseBackgroundOnBattery` setting.
+    pub pause_on_battery: bool,
+    /// Jobs waiting for AC power.
+    pub deferred: Vec<Job>,
+}
+
+#[derive(Default)]
+pub struct PowerGuard {
+    deferred: Mutex<Vec<Job>>,
+}
+
+#[cfg(target_os = "linux")]
+fn read_source() -> Option<PowerSource> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let (mut on_mains, mut discharging, mut battery_percent) = (false, false, None);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let read = |name: &str| std::fs::read_to_string(path.join(name)).ok().map(|s| s.trim().to_string());
+        match read("type").as_deref() {
+            Some("Mains" | "USB") => on_mains |= read("online").as_deref() == Some("1"),
+            // Wireless mice and the like report a battery with scope "Device"
+            Some("Battery") if read("scope").as_deref() != Some("Device") => {
+                discharging |= read("status").as_deref() == Some("Discharging");
+                battery_percent = battery_percent.or_else(|| read("capacity")?.parse().ok());
+            }
+            _ => {}
+        }
+    }
+    Some(PowerSource { on_battery: discharging && !on_mains, battery_percent })
+}
+
+#[cfg(target_os = "macos")]
+fn read_source() -> Option<PowerSource> {
+    // "Now drawing from 'Battery Power'" then " -InternalBattery-0 (id=…)	87%; discharging; …"
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let on_battery = text.lines().next()?.contains("'Battery Power'");
+    let battery_percent = text.split_whitespace().find_map(|word| word.strip_suffix("%;")?.parse().ok());
+    Some(PowerSource { on_battery, battery_percent })
+}
+
+#[cfg(windows)]
+fn read_source() -> Option<PowerSource> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // SAFETY: a zeroed SYSTEM_POWER_STATUS is valid, and the call only writes into it
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    Some(PowerSource {
+        // 255 means unknown
+        on_battery: status.ACLineStatus == 0,
+        battery_percent: (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn read_source() -> Option<PowerSource> {
+    None
+}
+
+/// Reads the power source off the async runtime, as `pmset` is a process to wait on.
+async fn source() -> Option<PowerSource> {
+    tauri::async_runtime::spawn_blocking(read_source).await.ok().flatten()
+}
+
+async fn pause_on_battery(app: &AppHandle) -> bool {
+    let pool = app.state::<AppState>().db();
+    settings::get::<bool>(&pool, PAUSE_KEY).await.unwrap_or_else(|e| {
+        log::warn!("Failed to read {PAUSE_KEY}: {e}");
+        None
+    }) == Some(true)
+}
+
+/// Whether a scheduled `job` should wait for AC power. If so it's recorded and
+/// runs when the power comes back; the caller just skips it.
+pub async fn defer(app: &AppHandle, job: Job) -> bool {
+    if !pause_on_battery(app).await || !source().await.is_some_and(|s| s.on_battery) {
+        return false;
+    }
+    let Some(guard) = app.try_state::<PowerGuard>() else {
+        return false;
+    };
+    let mut deferred = guard.deferred.lock().unwrap();
+    if !deferred.contains(&job) {
+        deferred.push(job);
+    }
+    log::info!("On battery; deferring {job:?} until plugged in");
+    true
+}
+
+/// Drops a deferred `job` the user has just run by hand.
+pub fn settle(app: &AppHandle, job: Job) {
+    if let Some(guard) = app.try_state::<PowerGuard>() {
+        guard.deferred.lock().unwrap().retain(|deferred| *deferred != job);
+    }
+}
+
+async fn run(app: &AppHandle, job: Job) {
+    match job {
+        Job::Sync => sync::run_in_background(app).await,
+    }
+}
+
+/// Watches the power source, running the deferred jobs once they may go ahead.
+pub fn init(app: &AppHandle) {
+    app.manage(PowerGuard::default());
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        let mut last = None;
+        loop {
+            interval.tick().await;
+            let current = source().await;
+            if current.map(|s| s.on_battery) != last.map(|s: PowerSource| s.on_battery) {
+                if let Some(source) = current {
+                    log::info!("Running on {}", if source.on_battery { "battery" } else { "AC power" });
+                }
+                let _ = handle.emit("power-changed", status(&handle).await);
+            }
+            last = current;
+
+            let has_deferred = !handle.state::<PowerGuard>().deferred.lock().unwrap().is_empty();
+            let held_back = current.is_some_and(|s| s.on_battery) && pause_on_battery(&handle).await;
+            if !has_deferred || held_back {
+                continue;
+            }
+            let jobs: Vec<Job> = handle.state::<PowerGuard>().deferred.lock().unwrap().drain(..).collect();
+            for job in jobs {
+                log::info!("Running deferred {job:?}");
+                run(&handle, job).await;
+            }
+        }
+    });
+}
+
+async fn status(app: &AppHandle) -> PowerStatus {
+    let source = source().await;
+    let deferred = app.try_state::<PowerGuard>().map(|g| g.deferred.lock().unwrap().clone()).unwrap_or_default();
+    PowerStatus {
+        on_battery: source.map(|s| s.on_battery),
+        battery_percent: source.and_then(|s| s.battery_percent),
+        pause_on_battery: pause_on_battery(app).await,
+        deferred,
+    }
+}
+
+/// Whether the machine is on battery and which background jobs are waiting for AC power.
+#[tauri::command]
+pub async fn power_status(app: AppHandle) -> PowerStatus {
+    status(&app).await
+}
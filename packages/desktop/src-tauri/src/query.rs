@@ -0,0 +1,151 @@
+//! A single typed entry point for listing tasks, so the UI doesn't assemble SQL.
+
+use serde::Deserialize;
+use sqlx::{QueryBuilder, Sqlite};
+use tauri::State;
+
+use crate::collation::NATURAL;
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::models::Task;
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortBy {
+    #[default]
+    Order,
+    DueDate,
+    Priority,
+    CreatedAt,
+    Title,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskFilter {
+    pub list_id: Option<String>,
+    /// `None` matches both open and completed tasks.
+    pub completed: Option<bool>,
+    /// Inclusive bounds on `due_date`, epoch millis. Either bound excludes undated tasks.
+    pub due_from: Option<i64>,
+    pub due_to: Option<i64>,
+    pub priority: Option<i64>,
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    pub include_trash: bool,
+    /// Leaves out deferred tasks whose `start_date` hasn't arrived yet.
+    pub hide_future: bool,
+    /// `Some(true)` without a list is the Pinned view across every list.
+    pub pinned: Option<bool>,
+    /// Puts pinned tasks ahead of the rest, each part in the chosen sort.
+    pub pinned_first: bool,
+    pub sort_by: SortBy,
+    pub direction: SortDirection,
+    pub limit: Option<i64>,
+}
+
+/// Appends the filter as bound parameters; only fixed SQL fragments are pushed as text.
+pub(crate) fn push_conditions(query: &mut QueryBuilder<'_, Sqlite>, filter: &TaskFilter) {
+    query.push(" WHERE 1 = 1");
+    if !filter.include_trash {
+        query.push(" AND list_name != 'Trash'");
+    }
+    if filter.hide_future {
+        query.push(" AND (start_date IS NULL OR start_date <= ").push_bind(now_ms()).push(")");
+    }
+    if let Some(list_id) = &filter.list_id {
+        query.push(" AND list_id = ").push_bind(list_id.clone());
+    }
+    if let Some(completed) = filter.completed {
+        query.push(" AND completed = ").push_bind(completed);
+    }
+    if let Some(from) = filter.due_from {
+        query.push(" AND due_date >= ").push_bind(from);
+    }
+    if let Some(to) = filter.due_to {
+        query.push(" AND due_date <= ").push_bind(to);
+    }
+    if let Some(priority) = filter.priority {
+        query.push(" AND priority = ").push_bind(priority);
+    }
+    if let Some(pinned) = filter.pinned {
+        query.push(" AND pinned = ").push_bind(pinned);
+    }
+
+    let mut tags: Vec<&String> = filter.tags.iter().collect();
+    tags.sort();
+    tags.dedup();
+    if !tags.is_empty() {
+        // A malformed tags value reads as no tags rather than failing the whole query
+        let matching = |query: &mut QueryBuilder<'_, Sqlite>| {
+            query.push(
+                // Unqualified, so the same conditions work on `archived_tasks`
+                " FROM json_each(CASE WHEN json_valid(tags) THEN tags ELSE '[]' END) WHERE value IN (",
+            );
+            let mut values = query.separated(", ");
+            for tag in &tags {
+                values.push_bind((*tag).clone());
+            }
+            query.push(")");
+        };
+        match filter.tag_match {
+            TagMatch::Any => {
+                query.push(" AND EXISTS (SELECT 1");
+                matching(query);
+                query.push(")");
+            }
+            TagMatch::All => {
+                query.push(" AND (SELECT COUNT(DISTINCT value)");
+                matching(query);
+                query.push(") = ").push_bind(tags.len() as i64);
+            }
+        }
+    }
+}
+
+pub(crate) fn order_by(filter: &TaskFilter) -> String {
+    let direction = match filter.direction {
+        SortDirection::Asc => "ASC",
+        SortDirection::Desc => "DESC",
+    };
+    // Tasks without a due date or priority sort last either way
+    let key = match filter.sort_by {
+        SortBy::Order => format!(r#""order" {direction}"#),
+        SortBy::DueDate => format!("due_date IS NULL, due_date {direction}"),
+        SortBy::Priority => format!("priority IS NULL, priority {direction}"),
+        SortBy::CreatedAt => format!("created_at {direction}"),
+        SortBy::Title => format!("title COLLATE {NATURAL} {direction}"),
+    };
+    let pinned = if filter.pinned_first { "pinned DESC, " } else { "" };
+    format!(r#" ORDER BY {pinned}{key}, "order", id"#)
+}
+
+#[tauri::command]
+pub async fn query_tasks(state: State<'_, AppState>, filter: TaskFilter) -> Result<Vec<Task>> {
+    let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM tasks");
+    push_conditions(&mut query, &filter);
+    // Not part of the shared conditions: `archived_tasks` has no trash
+    query.push(" AND deleted_at IS NULL");
+    query.push(order_by(&filter));
+    if let Some(limit) = filter.limit {
+        query.push(" LIMIT ").push_bind(limit.max(0));
+    }
+    Ok(query.build_query_as::<Task>().fetch_all(&state.db()).await?)
+}
@@ -0,0 +1,131 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::error::{Error, Result};
+use crate::settings;
+use crate::AppState;
+
+pub const WINDOW_LABEL: &str = "quickadd";
+const SETTINGS_KEY: &str = "quickAddShortcut";
+const DEFAULT_SHORTCUT: &str = "CmdOrCtrl+Shift+Space";
+
+/// The accelerator currently registered for the quick-add popup.
+#[derive(Default)]
+pub struct QuickAddState {
+    shortcut: Mutex<Option<Shortcut>>,
+}
+
+fn parse(accel: &str) -> Result<Shortcut> {
+    accel
+        .parse::<Shortcut>()
+        .map_err(|e| Error::InvalidInput(format!("'{accel}' is not a valid shortcut: {e}")))
+}
+
+fn register(app: &AppHandle, accel: &str) -> Result<Shortcut> {
+    let shortcut = parse(accel)?;
+    app.global_shortcut().register(shortcut).map_err(|e| {
+        Error::InvalidInput(format!(
+            "Could not register '{accel}', it may already be used by another application: {e}"
+        ))
+    })?;
+    Ok(shortcut)
+}
+
+/// Installs the global shortcut plugin and registers the stored (or default) accelerator.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    app.manage(QuickAddState::default());
+    app.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app, shortcut, event| {
+                let current = *app.state::<QuickAddState>().shortcut.lock().unwrap();
+                if event.state() == ShortcutState::Pressed && current.as_ref() == Some(shortcut) {
+                    if let Err(e) = toggle(app) {
+                        log::error!("Failed to open quick add: {e}");
+                    }
+                }
+            })
+            .build(),
+    )?;
+
+    let pool = app.state::<AppState>().db();
+    let stored = tauri::async_runtime::block_on(settings::get::<String>(&pool, SETTINGS_KEY))
+        .ok()
+        .flatten()
+        .filter(|accel| !accel.trim().is_empty());
+
+    let shortcut = match stored.as_deref().map(|accel| register(app, accel)) {
+        Some(Ok(shortcut)) => Some(shortcut),
+        other => {
+            if let Some(Err(e)) = other {
+                log::warn!("Falling back to the default quick add shortcut: {e}");
+            }
+            register(app, DEFAULT_SHORTCUT)
+                .inspect_err(|e| log::error!("Failed to register quick add shortcut: {e}"))
+                .ok()
+        }
+    };
+    *app.state::<QuickAddState>().shortcut.lock().unwrap() = shortcut;
+    Ok(())
+}
+
+/// Shows the quick-add popup, creating it on first use, or hides it if it's focused.
+fn toggle(app: &AppHandle) -> Result<()> {
+    let window = match app.get_webview_window(WINDOW_LABEL) {
+        Some(window) => {
+            if window.is_visible()? && window.is_focused()? {
+                window.hide()?;
+                return Ok(());
+            }
+            window
+        }
+        None => WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("index.html#/quick-add".into()))
+            .title("Quick Add")
+            .inner_size(560.0, 72.0)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .center()
+            .build()?,
+    };
+
+    window.center()?;
+    window.show()?;
+    window.set_focus()?;
+    // The frontend focuses its input on this event
+    app.emit_to(WINDOW_LABEL, "quick-add-opened", ())?;
+    Ok(())
+}
+
+/// Validates and registers a new accelerator, keeping the old one if that fails.
+#[tauri::command]
+pub async fn set_quick_add_shortcut(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    quick_add: State<'_, QuickAddState>,
+    accel: String,
+) -> Result<()> {
+    let accel = accel.trim();
+    let previous = *quick_add.shortcut.lock().unwrap();
+    let shortcut = parse(accel)?;
+    if previous != Some(shortcut) {
+        register(&app, accel)?;
+        if let Some(previous) = previous {
+            let _ = app.global_shortcut().unregister(previous);
+        }
+        *quick_add.shortcut.lock().unwrap() = Some(shortcut);
+    }
+    settings::set(&state.db(), SETTINGS_KEY, accel).await
+}
+
+/// Called by the popup after submitting; hidden rather than closed so reopening is instant.
+#[tauri::command]
+pub fn hide_quick_add(app: AppHandle) -> Result<()> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window.hide()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Startup check for a corrupted database, before anything opens it.
+//!
+//! A partial write or a bad sector can leave `tada.db` unreadable. The check
+//! runs `PRAGMA quick_check` on the active workspace's file; if that fails, the
+//! file (with its `-wal` and `-shm`) is renamed aside to
+//! `<name>.corrupt-YYYYMMDD-HHMMSS` and never deleted, so it stays available
+//! for manual recovery. The newest of that workspace's own backups that passes
+//! the same check takes its place; without one the app starts on a fresh
+//! database. Another workspace's backup is never used, however recent. Either way a dialog
+//! says what happened and where the quarantined file is.
+//!
+//! It's a plugin so it runs after the single-instance plugin (a second launch
+//! exits before touching the file) and before setup opens and migrates it.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Local;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection};
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::error::Result;
+use crate::{backup, paths, workspaces};
+
+/// SQLite's primary result codes for a damaged file.
+const SQLITE_CORRUPT: i64 = 11;
+const SQLITE_NOTADB: i64 = 26;
+
+/// What the startup check did, kept until the dialog can be shown.
+enum Recovery {
+    Restored { quarantined: PathBuf, backup: PathBuf },
+    Fresh { quarantined: PathBuf },
+}
+
+#[derive(Default)]
+struct Pending(Mutex<Option<Recovery>>);
+
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri::plugin::Builder::new("recovery")
+        .setup(|app, _api| {
+            app.manage(Pending::default());
+            match tauri::async_runtime::block_on(check_and_recover(app)) {
+                Ok(recovery) => *app.state::<Pending>().0.lock().unwrap() = recovery,
+                // Start anyway; the file is as it was, so nothing is lost by trying
+                Err(e) => log::error!("Database recovery failed: {e}"),
+            }
+            Ok(())
+        })
+        .build()
+}
+
+/// Whether an error means the file itself is damaged, as opposed to locked or unreadable.
+fn is_corruption(e: &sqlx::Error) -> bool {
+    let code = match e {
+        sqlx::Error::Database(e) => e.code().and_then(|code| code.parse::<i64>().ok()),
+        _ => None,
+    };
+    // Extended codes keep the primary one in the low byte
+    code.is_some_and(|code| matches!(code & 0xff, SQLITE_CORRUPT | SQLITE_NOTADB))
+}
+
+/// Runs `PRAGMA quick_check` on a file without changing it. Errors that don't
+/// point at corruption are returned, so a locked file is never taken for a broken one.
+async fn is_healthy(path: &Path) -> Result<bool> {
+    let check = async {
+        let mut conn = SqliteConnectOptions::new().filename(path).read_only(true).connect().await?;
+        let rows: Vec<String> = sqlx::query_scalar("PRAGMA quick_check").fetch_all(&mut conn).await?;
+        conn.close().await?;
+        Ok::<_, sqlx::Error>(rows)
+    };
+    match check.await {
+        Ok(rows) if rows.len() == 1 && rows[0] == "ok" => Ok(true),
+        Ok(rows) => {
+            log::error!("quick_check of {} found {} problem(s), first: {}", path.display(), rows.len(), rows[0]);
+            Ok(false)
+        }
+        Err(e) if is_corruption(&e) => {
+            log::error!("Failed to read {}: {e}", path.display());
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `path` with `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Renames the database and its WAL files aside, returning the database's new path.
+/// The WAL goes along rather than being replayed into whatever replaces it.
+fn quarantine(path: &Path) -> Result<PathBuf> {
+    let target = with_suffix(path, &format!(".corrupt-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::rename(path, &target)?;
+    for suffix in ["-wal", "-shm"] {
+        let companion = with_suffix(path, suffix);
+        if companion.exists() {
+            std::fs::rename(&companion, with_suffix(&target, suffix))?;
+        }
+    }
+    Ok(target)
+}
+
+async fn check_and_recover(app: &AppHandle) -> Result<Option<Recovery>> {
+    let path = workspaces::active_db_path(&paths::config_dir(app)?);
+    if !path.exists() || is_healthy(&path).await? {
+        return Ok(None);
+    }
+
+    let quarantined = quarantine(&path)?;
+    log::warn!("Moved the corrupted database to {}", quarantined.display());
+    if let Err(e) = backup::adopt_shared_backups(app) {
+        log::warn!("Failed to move the shared backups: {e}");
+    }
+    // Newest first; a backup taken after the damage started may be broken too
+    for candidate in backup::list(&backup::backups_dir_for(app, &path)?)? {
+        let candidate = PathBuf::from(candidate.path);
+        match is_healthy(&candidate).await {
+            Ok(true) => {
+                std::fs::copy(&candidate, &path)?;
+                log::warn!("Restored the database from {}", candidate.display());
+                return Ok(Some(Recovery::Restored { quarantined, backup: candidate }));
+            }
+            Ok(false) => log::warn!("Skipping backup {}, which is corrupted too", candidate.display()),
+            Err(e) => log::warn!("Skipping backup {}: {e}", candidate.display()),
+        }
+    }
+    // Setup creates and migrates a new file in its place
+    log::warn!("No usable backup; starting with an empty database");
+    Ok(Some(Recovery::Fresh { quarantined }))
+}
+
+/// Tells the user about a recovery made at startup, once the dialog plugin is up.
+pub fn notify(app: &AppHandle) {
+    let Some(pending) = app.try_state::<Pending>() else {
+        return;
+    };
+    let Some(recovery) = pending.0.lock().unwrap().take() else {
+        return;
+    };
+    let message = match recovery {
+        Recovery::Restored { quarantined, backup } => format!(
+            "Tada's database was damaged and couldn't be opened, so it was restored from the backup {}. \
+             Changes made after that backup are missing.\n\nThe damaged file was kept at {} in case \
+             anything can be recovered from it.",
+            backup.file_name().unwrap_or_default().to_string_lossy(),
+            quarantined.display(),
+        ),
+        Recovery::Fresh { quarantined } => format!(
+            "Tada's database was damaged and couldn't be opened, and there was no backup to restore, \
+             so Tada started with an empty database.\n\nThe damaged file was kept at {} in case \
+             anything can be recovered from it.",
+            quarantined.display(),
+        ),
+    };
+    app.dialog()
+        .message(message)
+        .title("Tada recovered your database")
+        .kind(MessageDialogKind::Warning)
+        .show(|_| {});
+}
+
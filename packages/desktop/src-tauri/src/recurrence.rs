@@ -0,0 +1,296 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use sqlx::{Sqlite, Transaction};
+
+use crate::commands::{fetch_task, group_category};
+use crate::dates::resolve_local;
+use crate::db::{new_id, now_ms};
+use crate::error::{Error, Result};
+use crate::models::Task;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The subset of an RFC 5545 RRULE the engine understands:
+/// `FREQ=DAILY|WEEKLY|MONTHLY|YEARLY`, `INTERVAL`, `BYDAY`, a single
+/// `BYMONTHDAY` on monthly rules, `COUNT` and `UNTIL`.
+///
+/// `COUNT` is stored as the number of occurrences remaining, including the
+/// task the rule is attached to, and is decremented on each generated instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    pub freq: Freq,
+    pub interval: u32,
+    pub by_day: Vec<Weekday>,
+    /// Day of the month (1-31) for monthly rules; without it the due date's day is kept.
+    pub by_month_day: Option<u32>,
+    pub count: Option<u32>,
+    /// Inclusive end, epoch millis.
+    pub until: Option<i64>,
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    Some(match s {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// Accepts `YYYYMMDD`, floating `YYYYMMDDTHHMMSS` (local) and `YYYYMMDDTHHMMSSZ` (UTC).
+fn parse_until(s: &str) -> Option<i64> {
+    if let Some(utc) = s.strip_suffix('Z') {
+        let dt = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&dt).timestamp_millis());
+    }
+    let dt = match NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S") {
+        Ok(dt) => dt,
+        // A date-only UNTIL includes the whole day
+        Err(_) => NaiveDate::parse_from_str(s, "%Y%m%d").ok()?.and_hms_opt(23, 59, 59)?,
+    };
+    resolve_local(dt).map(|d| d.timestamp_millis())
+}
+
+impl FromStr for RRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |msg: &str| Error::InvalidInput(format!("Invalid recurrence rule '{s}': {msg}"));
+        let body = s.trim();
+        let body = body.strip_prefix("RRULE:").unwrap_or(body);
+
+        let mut freq = None;
+        let mut rule = RRule {
+            freq: Freq::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: None,
+            count: None,
+            until: None,
+        };
+
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| invalid("expected KEY=VALUE"))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        "YEARLY" => Freq::Yearly,
+                        _ => return Err(invalid("unsupported FREQ")),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| invalid("INTERVAL must be a positive integer"))?
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|d| parse_weekday(&d.to_ascii_uppercase()))
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| invalid("unsupported BYDAY value"))?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|day| (1..=31).contains(day))
+                            .ok_or_else(|| invalid("BYMONTHDAY must be a single day from 1 to 31"))?,
+                    )
+                }
+                "COUNT" => {
+                    rule.count = Some(value.parse().map_err(|_| invalid("COUNT must be an integer"))?)
+                }
+                "UNTIL" => rule.until = Some(parse_until(value).ok_or_else(|| invalid("bad UNTIL"))?),
+                "WKST" => {}
+                _ => return Err(invalid("unsupported rule part")),
+            }
+        }
+
+        rule.freq = freq.ok_or_else(|| invalid("FREQ is required"))?;
+        if rule.by_month_day.is_some() && rule.freq != Freq::Monthly {
+            return Err(invalid("BYMONTHDAY needs FREQ=MONTHLY"));
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for RRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Freq::Daily => "DAILY",
+            Freq::Weekly => "WEEKLY",
+            Freq::Monthly => "MONTHLY",
+            Freq::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={freq}")?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<_> = self.by_day.iter().map(|d| weekday_code(*d)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={day}")?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until.and_then(|ms| Utc.timestamp_millis_opt(ms).single()) {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        Ok(())
+    }
+}
+
+impl RRule {
+    /// Whether another instance may follow the one this rule is attached to.
+    fn has_more(&self) -> bool {
+        self.count.is_none_or(|c| c > 1)
+    }
+
+    /// Next occurrence after `current_ms`, or `None` once the rule is exhausted.
+    ///
+    /// Arithmetic happens on the local wall-clock date, so a 9am task stays at 9am
+    /// across DST changes instead of drifting by the offset difference.
+    pub fn next_after(&self, current_ms: i64) -> Option<i64> {
+        if !self.has_more() {
+            return None;
+        }
+        let current = Local.timestamp_millis_opt(current_ms).single()?.naive_local();
+        let (date, time) = (current.date(), current.time());
+        let interval = self.interval;
+
+        let next_date = match self.freq {
+            Freq::Daily if self.by_day.is_empty() => date + Duration::days(interval.into()),
+            Freq::Daily => (1..=7 * i64::from(interval))
+                .map(|n| date + Duration::days(n))
+                .find(|d| {
+                    (*d - date).num_days() % i64::from(interval) == 0 && self.by_day.contains(&d.weekday())
+                })?,
+            Freq::Weekly if self.by_day.is_empty() => date + Duration::weeks(interval.into()),
+            Freq::Weekly => {
+                let week_start = |d: NaiveDate| d - Duration::days(d.weekday().num_days_from_monday().into());
+                let origin = week_start(date);
+                (1..=7 * (i64::from(interval) + 1))
+                    .map(|n| date + Duration::days(n))
+                    .find(|d| {
+                        let weeks = (week_start(*d) - origin).num_weeks();
+                        weeks % i64::from(interval) == 0 && self.by_day.contains(&d.weekday())
+                    })?
+            }
+            // Months without the anchor day (e.g. the 31st) are skipped, as in RFC 5545
+            Freq::Monthly => match self.by_month_day {
+                // The current month counts too: "on the 15th" from the 10th is five days away
+                Some(day) => {
+                    let first = date.with_day(1)?;
+                    (0..=24)
+                        .filter_map(|n| first.checked_add_months(Months::new(interval * n))?.with_day(day))
+                        .find(|d| *d > date)?
+                }
+                None => (1..=12)
+                    .filter_map(|n| date.checked_add_months(Months::new(interval * n)))
+                    .find(|d| d.day() == date.day())?,
+            },
+            // February 29th waits for the next leap year
+            Freq::Yearly => (1..=8)
+                .filter_map(|n| date.checked_add_months(Months::new(12 * interval * n)))
+                .find(|d| d.day() == date.day())?,
+        };
+
+        let next = resolve_local(next_date.and_time(time))?.timestamp_millis();
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+
+    /// The rule to store on the generated instance.
+    pub(crate) fn advanced(&self) -> Self {
+        Self {
+            count: self.count.map(|c| c.saturating_sub(1)),
+            ..self.clone()
+        }
+    }
+}
+
+/// Validates a rule string from the frontend, normalizing it for storage.
+pub fn normalize(rule: Option<&str>) -> Result<Option<String>> {
+    match rule.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(rule) => Ok(Some(rule.parse::<RRule>()?.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Inserts the next instance of a just-completed recurring task, copying its
+/// title, list, content, tags and priority. Returns `None` when the task isn't
+/// recurring, has no due date, or its rule is exhausted.
+pub async fn spawn_next(tx: &mut Transaction<'_, Sqlite>, task: &Task) -> Result<Option<Task>> {
+    let (Some(raw), Some(due_date)) = (task.recurrence_rule.as_deref(), task.due_date) else {
+        return Ok(None);
+    };
+    let rule: RRule = raw.parse()?;
+    let Some(next_due) = rule.next_after(due_date) else {
+        return Ok(None);
+    };
+
+    // A deferred occurrence keeps the same lead time before its due date
+    let next_start = task.start_date.map(|start| next_due - (due_date - start));
+
+    let id = new_id();
+    let now = now_ms();
+    sqlx::query(
+        r#"
+        INSERT INTO tasks (id, title, completed, due_date, list_id, list_name, content, "order",
+                           created_at, updated_at, tags, priority, group_category, recurrence_rule, color,
+                           start_date, pinned, estimated_minutes, reminder_offset_minutes)
+        SELECT ?, title, 0, ?, list_id, list_name, content,
+               (SELECT COALESCE(MAX("order"), -1) + 1 FROM tasks WHERE list_id = t.list_id),
+               ?, ?, tags, priority, ?, ?, color, ?, pinned, estimated_minutes, reminder_offset_minutes
+        FROM tasks t WHERE id = ?
+        "#,
+    )
+    .bind(&id)
+    .bind(next_due)
+    .bind(now)
+    .bind(now)
+    .bind(group_category(false, Some(next_due), next_start))
+    .bind(rule.advanced().to_string())
+    .bind(next_start)
+    .bind(&task.id)
+    .execute(&mut **tx)
+    .await?;
+
+    fetch_task(tx, &id).await.map(Some)
+}
@@ -0,0 +1,697 @@
+//! Due-date reminders as OS notifications, with "Done" and "Snooze 10m" actions.
+//!
+//! Notification action buttons only exist on mobile in the notification plugin,
+//! so desktop reminders also emit `reminder-fired` for the UI to offer the same
+//! actions in-app. Either way the action lands in `reminder_action`, which also
+//! takes "open" for a click on the notification (mobile reports a tap on its
+//! body as "tap"); the desktop plugin reports no clicks, so there the in-app
+//! prompt offers it. Every notification carries its `taskId`. Snoozes are
+//! stored in the database, so they hold however the notification is dismissed.
+//!
+//! The due-date reminder fires `reminder_offset_minutes` before the due date,
+//! or the `reminderOffsetMinutes` setting's default (0) for tasks without one.
+//! An offset that puts it in the past, say one hour on a task due in ten
+//! minutes, fires it right away; `fired_reminders` keeps that to once.
+//!
+//! Besides the due-date reminder (and its snooze), a task can have one reminder
+//! at a time of the user's choosing from `snoozes::snooze_task`; the two don't
+//! replace each other. While the user is away, `idle` holds reminders back.
+//!
+//! `fired_reminders` only needs a row while the reminder could still fire
+//! again, so it's purged every few hours of rows for tasks that were completed,
+//! trashed or moved to another due date, and of rows older than
+//! `firedReminderRetentionDays` (30 by default). A task reopened after the
+//! purge reminds again if its reminder time comes around once more.
+//!
+//! `notificationSound` picks the sound: `"default"` (or unset), `"silent"`, or
+//! the path of a sound file, which falls back to the default once it's gone.
+//! With `groupNotifications` on, reminders firing together arrive as one
+//! notification, and every reminder carries a group id so the OS stacks them
+//! where it supports that.
+//!
+//! A single timer sleeps until the nearest reminder of all three kinds, however
+//! many are further out; nothing polls in between. Anything that may move the
+//! nearest one (a task change, a snooze, a wake from sleep, a workspace switch)
+//! goes through `reschedule`, which wakes the timer to look again.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_notification::{NotificationBuilder, NotificationExt};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::commands;
+use crate::deep_link;
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::idle;
+use crate::models::Task;
+use crate::{settings, AppState};
+
+/// Tasks that became due shortly before a check still fire; anything older is
+/// considered missed, so a launch after a long break doesn't flood the user.
+const GRACE_MS: i64 = 60_000;
+/// After a sleep, up to this many missed reminders still fire one by one;
+/// more than that become a single summary notification.
+const CATCH_UP_INDIVIDUAL: usize = 3;
+/// When the timer looks again after failing to read the reminders.
+const RETRY_AFTER: Duration = Duration::from_secs(30);
+const RETENTION_KEY: &str = "firedReminderRetentionDays";
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+const PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Default for the "Snooze" action.
+const SNOOZE_MINUTES: i64 = 10;
+const OFFSET_KEY: &str = "reminderOffsetMinutes";
+const MAX_OFFSET_MINUTES: u32 = 30 * 24 * 60;
+#[cfg(mobile)]
+const ACTION_TYPE_ID: &str = "task-reminder";
+const SOUND_KEY: &str = "notificationSound";
+const GROUP_KEY: &str = "groupNotifications";
+/// Group (Android) and thread (iOS) id shared by every reminder.
+const GROUP_ID: &str = "tada-reminders";
+
+#[derive(Debug, Clone)]
+struct Reminder {
+    task_id: String,
+    kind: ReminderKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ReminderKind {
+    /// The task's due date, less its offset.
+    Due { due_date: i64 },
+    /// The "Snooze" action on the due-date reminder; dropped if the due date changes.
+    Snoozed { due_date: i64 },
+    /// A reminder at a chosen time, whatever the due date.
+    Scheduled { remind_at: i64 },
+}
+
+#[derive(Default)]
+pub struct ReminderState {
+    /// Wakes the timer to find the nearest reminder again.
+    wake: Notify,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReminderFired<'a> {
+    task_id: &'a str,
+    title: &'a str,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemindersMissed<'a> {
+    task_ids: &'a [String],
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderOffset {
+    /// The task's own offset, `None` when it follows the default.
+    pub minutes: Option<i64>,
+    /// The offset in effect.
+    pub effective_minutes: i64,
+    /// When the due-date reminder fires, `None` without a due date.
+    pub remind_at: Option<i64>,
+}
+
+enum Sound {
+    Default,
+    Silent,
+    File(String),
+}
+
+/// How reminder notifications sound and stack, read from the settings each time they fire.
+pub(crate) struct NotificationStyle {
+    sound: Sound,
+    group: bool,
+}
+
+impl NotificationStyle {
+    /// Never fails: a setting that can't be read or a missing sound file means the default.
+    pub(crate) async fn load(pool: &SqlitePool) -> Self {
+        let sound = settings::get::<Option<String>>(pool, SOUND_KEY).await.unwrap_or_else(|e| {
+            log::warn!("Failed to read {SOUND_KEY}, using the default sound: {e}");
+            None
+        });
+        let sound = match sound.flatten().as_deref().map(str::trim) {
+            None | Some("" | "default") => Sound::Default,
+            Some("silent") => Sound::Silent,
+            Some(path) if Path::new(path).is_file() => Sound::File(path.to_string()),
+            Some(path) => {
+                log::warn!("Notification sound {path} no longer exists, using the default sound");
+                Sound::Default
+            }
+        };
+        let group = settings::get::<bool>(pool, GROUP_KEY).await.ok().flatten().unwrap_or(false);
+        Self { sound, group }
+    }
+
+    pub(crate) fn apply<R: Runtime>(&self, builder: NotificationBuilder<R>) -> NotificationBuilder<R> {
+        let builder = match &self.sound {
+            Sound::Default => builder,
+            Sound::Silent => builder.silent(),
+            Sound::File(path) => builder.sound(path),
+        };
+        if self.group { builder.group(GROUP_ID) } else { builder }
+    }
+}
+
+/// The `reminderOffsetMinutes` default; never fails, an unreadable setting means 0.
+async fn default_offset(pool: &SqlitePool) -> i64 {
+    match settings::get::<i64>(pool, OFFSET_KEY).await {
+        Ok(minutes) => minutes.unwrap_or(0).clamp(0, i64::from(MAX_OFFSET_MINUTES)),
+        Err(e) => {
+            log::warn!("Failed to read {OFFSET_KEY}, reminding at the due time: {e}");
+            0
+        }
+    }
+}
+
+/// Starts the timer, which fires what's due, then sleeps until the nearest
+/// reminder or until `reschedule` wakes it, and the `fired_reminders` purge.
+pub fn init(app: &AppHandle) {
+    app.manage(ReminderState::default());
+    #[cfg(mobile)]
+    register_actions(app);
+
+    events::on_tasks_changed(app, |handle| reschedule(&handle));
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let next = {
+                let state = handle.state::<AppState>();
+                let _permit = state.job_permit().await;
+                fire_due(&handle).await.unwrap_or_else(|e| {
+                    log::error!("Failed to fire reminders: {e}");
+                    Some(now_ms() + RETRY_AFTER.as_millis() as i64)
+                })
+            };
+            let state = handle.state::<ReminderState>();
+            match next {
+                Some(at) => {
+                    let deadline = Instant::now() + Duration::from_millis(u64::try_from(at - now_ms()).unwrap_or_default());
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(deadline) => {}
+                        _ = state.wake.notified() => {}
+                    }
+                }
+                None => state.wake.notified().await,
+            }
+        }
+    });
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = handle.state::<AppState>();
+            let _permit = state.job_permit().await;
+            if let Err(e) = purge_fired(&state.db()).await {
+                log::error!("Failed to purge fired reminders: {e}");
+            }
+        }
+    });
+}
+
+/// Drops the `fired_reminders` rows no reminder can fire against any more,
+/// returning how many. A row stays while its task is open with that same due
+/// date and the due date is within the retention window; that covers every
+/// reminder still pending, including ones `catch_up` may fire after a sleep.
+pub async fn purge_fired(pool: &SqlitePool) -> Result<u64> {
+    let days = settings::get::<i64>(pool, RETENTION_KEY).await?.unwrap_or(DEFAULT_RETENTION_DAYS).max(1);
+    let cutoff = now_ms() - days * 24 * 60 * 60 * 1000;
+    let purged = sqlx::query(
+        r#"
+        DELETE FROM fired_reminders
+        WHERE due_date < ?
+           OR NOT EXISTS (
+               SELECT 1 FROM tasks t
+               WHERE t.id = fired_reminders.task_id
+                 AND t.due_date = fired_reminders.due_date
+                 AND t.completed = 0
+                 AND t.deleted_at IS NULL
+                 AND t.list_name != 'Trash'
+           )
+        "#,
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if purged > 0 {
+        log::debug!("Purged {purged} fired reminder records");
+    }
+    Ok(purged)
+}
+
+/// Has the timer look for the nearest reminder again. A wake-up requested
+/// while it's busy firing is kept, so none is lost.
+pub fn reschedule(app: &AppHandle) {
+    if let Some(state) = app.try_state::<ReminderState>() {
+        state.wake.notify_one();
+    }
+}
+
+/// Reminders whose time has come, oldest first. Tasks that were completed,
+/// deleted or lost their due date simply aren't found.
+async fn due_now(pool: &SqlitePool, now: i64, offset: i64) -> Result<Vec<Reminder>> {
+    // Everything whose reminder time has come, as long as the task isn't overdue
+    // yet: an offset longer than the time left fires at once rather than never
+    let due: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, due_date, remind_at FROM (
+            SELECT t.id, t.due_date, t.due_date - COALESCE(t.reminder_offset_minutes, ?1) * 60000 AS remind_at
+            FROM tasks t
+            WHERE t.completed = 0
+              AND t.list_name != 'Trash'
+              AND t.deleted_at IS NULL
+              AND t.due_date > ?2
+              AND NOT EXISTS (
+                  SELECT 1 FROM fired_reminders f
+                  WHERE f.task_id = t.id AND f.due_date = t.due_date
+              )
+        )
+        WHERE remind_at <= ?3
+        "#,
+    )
+    .bind(offset)
+    .bind(now - GRACE_MS)
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+
+    // Snoozes fire even if their time passed while the app was closed: the user asked for them
+    let snoozed: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT s.task_id, s.due_date, s.remind_at FROM reminder_snoozes s
+        JOIN tasks t ON t.id = s.task_id AND t.due_date = s.due_date
+        WHERE t.completed = 0 AND t.list_name != 'Trash' AND t.deleted_at IS NULL AND s.remind_at <= ?
+        "#,
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+
+    let scheduled: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT s.task_id, s.remind_at FROM snoozes s
+        JOIN tasks t ON t.id = s.task_id
+        WHERE t.completed = 0 AND t.list_name != 'Trash' AND t.deleted_at IS NULL AND s.remind_at <= ?
+        "#,
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+
+    let mut reminders = Vec::with_capacity(due.len() + snoozed.len() + scheduled.len());
+    for (task_id, due_date, remind_at) in due {
+        reminders.push((remind_at, Reminder { task_id, kind: ReminderKind::Due { due_date } }));
+    }
+    for (task_id, due_date, remind_at) in snoozed {
+        reminders.push((remind_at, Reminder { task_id, kind: ReminderKind::Snoozed { due_date } }));
+    }
+    for (task_id, remind_at) in scheduled {
+        reminders.push((remind_at, Reminder { task_id, kind: ReminderKind::Scheduled { remind_at } }));
+    }
+    reminders.sort_by_key(|(remind_at, _)| *remind_at);
+    Ok(reminders.into_iter().map(|(_, reminder)| reminder).collect())
+}
+
+/// When the nearest reminder after `now` fires, of any kind; `None` when there's none.
+async fn next_at(pool: &SqlitePool, now: i64, offset: i64) -> Result<Option<i64>> {
+    Ok(sqlx::query_scalar(
+        r#"
+        SELECT MIN(remind_at) FROM (
+            SELECT t.due_date - COALESCE(t.reminder_offset_minutes, ?1) * 60000 AS remind_at
+            FROM tasks t
+            WHERE t.completed = 0
+              AND t.list_name != 'Trash'
+              AND t.deleted_at IS NULL
+              AND t.due_date > ?2
+              AND NOT EXISTS (
+                  SELECT 1 FROM fired_reminders f
+                  WHERE f.task_id = t.id AND f.due_date = t.due_date
+              )
+            UNION ALL
+            SELECT s.remind_at FROM reminder_snoozes s
+            JOIN tasks t ON t.id = s.task_id AND t.due_date = s.due_date
+            WHERE t.completed = 0 AND t.list_name != 'Trash' AND t.deleted_at IS NULL
+            UNION ALL
+            SELECT s.remind_at FROM snoozes s
+            JOIN tasks t ON t.id = s.task_id
+            WHERE t.completed = 0 AND t.list_name != 'Trash' AND t.deleted_at IS NULL
+        )
+        WHERE remind_at > ?2
+        "#,
+    )
+    .bind(offset)
+    .bind(now)
+    .fetch_one(pool)
+    .await?)
+}
+
+/// Fires every reminder whose time has come and returns when the next one is due.
+async fn fire_due(app: &AppHandle) -> Result<Option<i64>> {
+    let pool = app.state::<AppState>().db();
+    let now = now_ms();
+    let offset = default_offset(&pool).await;
+    let due = due_now(&pool, now, offset).await?;
+    if !due.is_empty() {
+        let mut claimed = Vec::new();
+        for reminder in due {
+            if let Some(title) = claim(app, &pool, &reminder).await? {
+                claimed.push((reminder.task_id, title));
+            }
+        }
+        let style = NotificationStyle::load(&pool).await;
+        if style.group && claimed.len() > 1 {
+            show_grouped(app, &style, &claimed)?;
+        } else {
+            for (task_id, title) in &claimed {
+                show(app, &style, task_id, title)?;
+            }
+        }
+    }
+    next_at(&pool, now, offset).await
+}
+
+async fn fire(app: &AppHandle, pool: &SqlitePool, reminder: &Reminder) -> Result<()> {
+    if let Some(title) = claim(app, pool, reminder).await? {
+        show(app, &NotificationStyle::load(pool).await, &reminder.task_id, &title)?;
+    }
+    Ok(())
+}
+
+/// Takes a reminder through its gate, returning the task's title if it should
+/// be shown now. Held while the user is away.
+async fn claim(app: &AppHandle, pool: &SqlitePool, reminder: &Reminder) -> Result<Option<String>> {
+    let Reminder { task_id, kind } = reminder;
+    // Re-check the row: it may have been completed or edited since it was found.
+    // A scheduled reminder doesn't care what the due date is.
+    let due_date = match *kind {
+        ReminderKind::Due { due_date } | ReminderKind::Snoozed { due_date } => Some(due_date),
+        ReminderKind::Scheduled { .. } => None,
+    };
+    let title: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT title FROM tasks
+        WHERE id = ?1 AND (?2 IS NULL OR due_date = ?2) AND completed = 0 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(task_id)
+    .bind(due_date)
+    .fetch_optional(pool)
+    .await?;
+    let Some(title) = title else {
+        return Ok(None);
+    };
+
+    // The database write is the single gate against double notifications, both
+    // across timer runs and across restarts: recording the first reminder, or
+    // consuming the snooze.
+    let gate = match *kind {
+        ReminderKind::Due { due_date } => {
+            sqlx::query("INSERT OR IGNORE INTO fired_reminders (task_id, due_date, fired_at) VALUES (?, ?, ?)")
+                .bind(task_id)
+                .bind(due_date)
+                .bind(now_ms())
+                .execute(pool)
+                .await?
+        }
+        ReminderKind::Snoozed { due_date } => {
+            sqlx::query("DELETE FROM reminder_snoozes WHERE task_id = ? AND due_date = ?")
+                .bind(task_id)
+                .bind(due_date)
+                .execute(pool)
+                .await?
+        }
+        ReminderKind::Scheduled { remind_at } => {
+            sqlx::query("DELETE FROM snoozes WHERE task_id = ? AND remind_at = ?")
+                .bind(task_id)
+                .bind(remind_at)
+                .execute(pool)
+                .await?
+        }
+    };
+
+    if gate.rows_affected() == 0 {
+        return Ok(None);
+    }
+    if idle::is_away(app) {
+        idle::hold(pool, task_id).await?;
+        return Ok(None);
+    }
+    Ok(Some(title))
+}
+
+fn show(app: &AppHandle, style: &NotificationStyle, task_id: &str, title: &str) -> Result<()> {
+    log::info!("Firing reminder for task {task_id}");
+    let builder = style.apply(app.notification().builder().title("Tada").body(title).extra("taskId", task_id));
+    #[cfg(mobile)]
+    let builder = builder.action_type_id(ACTION_TYPE_ID);
+    builder.show()?;
+    let _ = app.emit("reminder-fired", ReminderFired { task_id, title });
+    Ok(())
+}
+
+/// One notification for reminders that fired together. The UI still gets
+/// `reminder-fired` for each, to offer their actions.
+fn show_grouped(app: &AppHandle, style: &NotificationStyle, reminders: &[(String, String)]) -> Result<()> {
+    log::info!("Firing {} reminders as one notification", reminders.len());
+    let titles: Vec<&str> = reminders.iter().map(|(_, title)| title.as_str()).collect();
+    let task_ids: Vec<&str> = reminders.iter().map(|(task_id, _)| task_id.as_str()).collect();
+    style
+        .apply(app.notification().builder())
+        .title(format!("Tada: {} reminders", reminders.len()))
+        .body(titles.join("\n"))
+        .extra("taskIds", &task_ids)
+        .group_summary()
+        .show()?;
+    for (task_id, title) in reminders {
+        let _ = app.emit("reminder-fired", ReminderFired { task_id, title });
+    }
+    Ok(())
+}
+
+/// Fires the reminders that came due while the machine was asleep, from `since`
+/// up to where the timer's grace period takes over. A few fire as usual;
+/// a whole night's worth is recorded as fired and summed up in one notification,
+/// with `reminders-missed` listing them for the UI.
+pub async fn catch_up(app: &AppHandle, since: i64) -> Result<()> {
+    let pool = app.state::<AppState>().db();
+    let until = now_ms() - GRACE_MS;
+    if since >= until {
+        return Ok(());
+    }
+    let missed: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT t.id, t.due_date FROM tasks t
+        WHERE t.completed = 0
+          AND t.list_name != 'Trash'
+          AND t.deleted_at IS NULL
+          AND t.due_date IS NOT NULL
+          AND t.due_date - COALESCE(t.reminder_offset_minutes, ?1) * 60000 > ?2
+          AND t.due_date - COALESCE(t.reminder_offset_minutes, ?1) * 60000 <= ?3
+          AND NOT EXISTS (
+              SELECT 1 FROM fired_reminders f
+              WHERE f.task_id = t.id AND f.due_date = t.due_date
+          )
+        ORDER BY t.due_date
+        "#,
+    )
+    .bind(default_offset(&pool).await)
+    .bind(since)
+    .bind(until)
+    .fetch_all(&pool)
+    .await?;
+
+    if missed.len() <= CATCH_UP_INDIVIDUAL {
+        for (task_id, due_date) in missed {
+            fire(app, &pool, &Reminder { task_id, kind: ReminderKind::Due { due_date } }).await?;
+        }
+        return Ok(());
+    }
+
+    let now = now_ms();
+    let mut task_ids = Vec::with_capacity(missed.len());
+    for (task_id, due_date) in missed {
+        let recorded =
+            sqlx::query("INSERT OR IGNORE INTO fired_reminders (task_id, due_date, fired_at) VALUES (?, ?, ?)")
+                .bind(&task_id)
+                .bind(due_date)
+                .bind(now)
+                .execute(&pool)
+                .await?;
+        if recorded.rows_affected() > 0 {
+            task_ids.push(task_id);
+        }
+    }
+    if task_ids.is_empty() {
+        return Ok(());
+    }
+    if idle::is_away(app) {
+        for task_id in &task_ids {
+            idle::hold(&pool, task_id).await?;
+        }
+        return Ok(());
+    }
+    log::info!("Caught up on {} reminders missed while asleep", task_ids.len());
+    NotificationStyle::load(&pool)
+        .await
+        .apply(app.notification().builder())
+        .title("Tada")
+        .body(format!("{} tasks came due while you were away", task_ids.len()))
+        .show()?;
+    let _ = app.emit("reminders-missed", RemindersMissed { task_ids: &task_ids });
+    Ok(())
+}
+
+/// Registers the "Done" / "Snooze 10m" buttons. The plugin's action types can only
+/// be built through serde.
+#[cfg(mobile)]
+fn register_actions(app: &AppHandle) {
+    let action = |id: &str, title: &str| {
+        serde_json::json!({
+            "id": id,
+            "title": title,
+            "requiresAuthentication": false,
+            "foreground": false,
+            "destructive": false,
+            "input": false,
+        })
+    };
+    let action_type = serde_json::from_value(serde_json::json!({
+        "id": ACTION_TYPE_ID,
+        "actions": [action("done", "Done"), action("snooze", "Snooze 10m")],
+        "customDismissAction": false,
+        "allowInCarPlay": false,
+        "hiddenPreviewsShowTitle": false,
+        "hiddenPreviewsShowSubtitle": false,
+    }));
+    match action_type {
+        Ok(action_type) => {
+            if let Err(e) = app.notification().register_action_types(vec![action_type]) {
+                log::error!("Failed to register reminder actions: {e}");
+            }
+        }
+        Err(e) => log::error!("Failed to build reminder actions: {e}"),
+    }
+}
+
+/// Pushes a task's next reminder `minutes` out without touching its due date.
+pub async fn snooze(app: &AppHandle, task_id: &str, minutes: i64) -> Result<()> {
+    let pool = app.state::<AppState>().db();
+    let inserted = sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO reminder_snoozes (task_id, due_date, remind_at)
+        SELECT id, due_date, ? FROM tasks WHERE id = ? AND due_date IS NOT NULL
+        "#,
+    )
+    .bind(now_ms() + minutes * 60_000)
+    .bind(task_id)
+    .execute(&pool)
+    .await?
+    .rows_affected();
+    if inserted == 0 {
+        return Err(Error::NotFound(format!("Task {task_id} with a due date")));
+    }
+    reschedule(app);
+    Ok(())
+}
+
+/// Lets the frontend have the reminders looked at again right after editing a task.
+#[tauri::command]
+pub fn reschedule_reminders(app: AppHandle) {
+    reschedule(&app);
+}
+
+/// Handles a reminder's "done", "snooze" or "open" action, from the notification or the in-app prompt.
+#[tauri::command]
+pub async fn reminder_action(app: AppHandle, task_id: String, action: String) -> Result<()> {
+    match action.as_str() {
+        "done" => {
+            let pool = app.state::<AppState>().db();
+            commands::complete(&app, &pool, &task_id, false).await?;
+            Ok(())
+        }
+        "snooze" => snooze(&app, &task_id, SNOOZE_MINUTES).await,
+        "open" | "tap" => deep_link::navigate_to_task(&app, &task_id).await,
+        other => Err(Error::InvalidInput(format!("Unknown reminder action '{other}'"))),
+    }
+}
+
+/// Snoozes a reminder for a custom number of minutes.
+#[tauri::command]
+pub async fn snooze_reminder(app: AppHandle, task_id: String, minutes: Option<i64>) -> Result<()> {
+    let minutes = minutes.unwrap_or(SNOOZE_MINUTES);
+    if minutes <= 0 {
+        return Err(Error::InvalidInput("Snooze must be at least one minute".into()));
+    }
+    snooze(&app, &task_id, minutes).await
+}
+
+/// Sets how many minutes before its due date a task's reminder fires, or with
+/// `None` makes it follow the `reminderOffsetMinutes` default. The pending
+/// reminder moves right away; one already fired for this due date stays fired.
+#[tauri::command]
+pub async fn set_reminder_offset(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_id: String,
+    minutes: Option<u32>,
+) -> Result<Task> {
+    if minutes.is_some_and(|m| m > MAX_OFFSET_MINUTES) {
+        return Err(Error::InvalidInput(format!(
+            "Reminders can be at most {MAX_OFFSET_MINUTES} minutes before the due date"
+        )));
+    }
+    let mut tx = state.db().begin().await?;
+    let updated =
+        sqlx::query("UPDATE tasks SET reminder_offset_minutes = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+            .bind(minutes)
+            .bind(now_ms())
+            .bind(&task_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    if updated == 0 {
+        return Err(Error::NotFound(format!("Task {task_id}")));
+    }
+    let task = commands::fetch_task(&mut tx, &task_id).await?;
+    tx.commit().await?;
+    log::debug!("Set the reminder offset of task {task_id} to {minutes:?} minutes");
+    reschedule(&app);
+    events::task_updated(&app, &task);
+    Ok(task)
+}
+
+/// A task's reminder offset and when its due-date reminder fires.
+#[tauri::command]
+pub async fn get_reminder_offset(state: State<'_, AppState>, task_id: String) -> Result<ReminderOffset> {
+    let pool = state.db();
+    let task: Option<(Option<i64>, Option<i64>)> =
+        sqlx::query_as("SELECT reminder_offset_minutes, due_date FROM tasks WHERE id = ? AND deleted_at IS NULL")
+            .bind(&task_id)
+            .fetch_optional(&pool)
+            .await?;
+    let (minutes, due_date) = task.ok_or_else(|| Error::NotFound(format!("Task {task_id}")))?;
+    let effective_minutes = match minutes {
+        Some(minutes) => minutes,
+        None => default_offset(&pool).await,
+    };
+    Ok(ReminderOffset {
+        minutes,
+        effective_minutes,
+        remind_at: due_date.map(|due| due - effective_minutes * 60_000),
+    })
+}
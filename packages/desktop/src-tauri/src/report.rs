@@ -0,0 +1,161 @@
+//! Exports stored AI summaries as a Markdown or self-contained HTML report.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use tauri::State;
+
+use crate::ai::period_bounds;
+use crate::dates::local_date;
+use crate::error::Result;
+use crate::markdown;
+use crate::models::{Summary, Task};
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// One summary with the tasks it was written from that still exist.
+struct Section<'a> {
+    summary: &'a Summary,
+    tasks: Vec<&'a Task>,
+}
+
+/// Period keys like `thisWeek` are relative, so they're resolved against the
+/// day the summary was generated.
+fn period_label(summary: &Summary) -> String {
+    let Some((start, end)) = period_bounds(&summary.period_key, summary.created_at) else {
+        return summary.period_key.clone();
+    };
+    let (start, end) = (local_date(start), local_date(end));
+    if start == end {
+        start.format("%Y-%m-%d").to_string()
+    } else {
+        format!("{} – {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"))
+    }
+}
+
+fn list_label(summary: &Summary) -> &str {
+    if summary.list_key == "all" { "All lists" } else { &summary.list_key }
+}
+
+fn render_markdown(sections: &[Section]) -> String {
+    let mut out = String::from("# Tada summary report\n\n");
+    for section in sections {
+        out.push_str(&format!(
+            "## {} · {}\n\n",
+            markdown::escape(&period_label(section.summary)),
+            markdown::escape(list_label(section.summary))
+        ));
+        // The summary is already Markdown from the model
+        out.push_str(section.summary.summary_text.trim());
+        out.push_str("\n\n");
+        if !section.tasks.is_empty() {
+            out.push_str("### Tasks\n\n");
+            for task in &section.tasks {
+                markdown::push_item(&mut out, "", task.completed, &task.title, &task.tags, task.due_date);
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:48rem;margin:2rem auto;padding:0 1rem;\
+color:#1f2937;line-height:1.5}h2{margin-top:2.5rem;border-bottom:1px solid #e5e7eb;padding-bottom:.25rem}\
+.summary{white-space:pre-wrap}ul{list-style:none;padding-left:0}li.done{color:#9ca3af;text-decoration:line-through}\
+.due{color:#6b7280;font-size:.875em;margin-left:.5rem}";
+
+/// No external assets, so the file opens the same anywhere. The summary text keeps its line breaks.
+fn render_html(sections: &[Section]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Tada summary report</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n\
+         <h1>Tada summary report</h1>\n"
+    );
+    for section in sections {
+        out.push_str(&format!(
+            "<section>\n<h2>{} · {}</h2>\n<div class=\"summary\">{}</div>\n",
+            escape_html(&period_label(section.summary)),
+            escape_html(list_label(section.summary)),
+            escape_html(section.summary.summary_text.trim())
+        ));
+        if !section.tasks.is_empty() {
+            out.push_str("<h3>Tasks</h3>\n<ul>\n");
+            for task in &section.tasks {
+                let class = if task.completed { " class=\"done\"" } else { "" };
+                let due = task
+                    .due_date
+                    .map(|d| format!("<span class=\"due\">due {}</span>", local_date(d).format("%Y-%m-%d")))
+                    .unwrap_or_default();
+                out.push_str(&format!("<li{class}>{}{due}</li>\n", escape_html(task.title.trim())));
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Writes the summaries generated between `from` and `to` (epoch millis, inclusive),
+/// oldest first, each followed by its tasks. Task ids that no longer exist are skipped.
+#[tauri::command]
+pub async fn export_summaries_report(
+    state: State<'_, AppState>,
+    path: String,
+    from: i64,
+    to: i64,
+    format: Option<ReportFormat>,
+) -> Result<()> {
+    let summaries: Vec<Summary> =
+        sqlx::query_as("SELECT * FROM summaries WHERE created_at BETWEEN ? AND ? ORDER BY created_at, id")
+            .bind(from)
+            .bind(to)
+            .fetch_all(&state.db())
+            .await?;
+
+    let mut ids: Vec<&str> = summaries.iter().flat_map(|s| s.task_ids.iter().map(String::as_str)).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let tasks: Vec<Task> = sqlx::query_as("SELECT * FROM tasks WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL")
+        .bind(serde_json::to_string(&ids)?)
+        .fetch_all(&state.db())
+        .await?;
+    let by_id: HashMap<&str, &Task> = tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+
+    let sections: Vec<Section> = summaries
+        .iter()
+        .map(|summary| Section {
+            summary,
+            tasks: summary.task_ids.iter().filter_map(|id| by_id.get(id.as_str()).copied()).collect(),
+        })
+        .collect();
+    let report = match format.unwrap_or_default() {
+        ReportFormat::Markdown => render_markdown(&sections),
+        ReportFormat::Html => render_html(&sections),
+    };
+    std::fs::write(&path, report)?;
+    log::info!("Exported {} summaries to {path}", sections.len());
+    Ok(())
+}
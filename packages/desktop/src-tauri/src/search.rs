@@ -0,0 +1,217 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use sqlx::FromRow;
+use tauri::State;
+use tokio::sync::Notify;
+
+use crate::collation::NATURAL;
+use crate::error::Result;
+use crate::AppState;
+
+// Private-use code points that can't collide with real task text; replaced by
+// UTF-16 ranges before results leave Rust.
+const MARK_START: char = '\u{E000}';
+const MARK_END: char = '\u{E001}';
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub text: String,
+    /// `[start, end)` ranges of matched text, in UTF-16 code units so they index JS strings directly.
+    pub highlights: Vec<[usize; 2]>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSearchResult {
+    pub id: String,
+    pub title: Snippet,
+    pub content: Option<Snippet>,
+    pub list_id: Option<String>,
+    pub list_name: String,
+    pub completed: bool,
+    /// Found in `archived_tasks`; restore it with `unarchive`.
+    pub archived: bool,
+    pub rank: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    /// Increases with every search; the UI shows only the highest it has seen.
+    pub generation: u64,
+    /// A newer search started before this one finished, so it was abandoned with no results.
+    pub superseded: bool,
+    pub results: Vec<TaskSearchResult>,
+}
+
+/// Numbers searches so a newer one abandons those still running, and results
+/// typed a keystroke ago never land after the current ones.
+#[derive(Default)]
+pub struct SearchGeneration {
+    current: AtomicU64,
+    changed: Notify,
+}
+
+impl SearchGeneration {
+    /// Starts a search, superseding every earlier one.
+    fn next(&self) -> u64 {
+        let generation = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.changed.notify_waiters();
+        generation
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.current.load(Ordering::SeqCst) == generation
+    }
+
+    /// Resolves once a newer search has started.
+    async fn superseded(&self, generation: u64) {
+        loop {
+            // Created before the check, so a search starting in between still wakes it
+            let changed = self.changed.notified();
+            if !self.is_current(generation) {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct SearchRow {
+    id: String,
+    title_snippet: String,
+    content_snippet: String,
+    list_id: Option<String>,
+    list_name: String,
+    completed: bool,
+    archived: bool,
+    rank: f64,
+}
+
+impl Snippet {
+    fn from_marked(marked: &str) -> Self {
+        let mut text = String::with_capacity(marked.len());
+        let mut highlights = Vec::new();
+        let (mut offset, mut start) = (0, None);
+        for c in marked.chars() {
+            match c {
+                MARK_START => start = Some(offset),
+                MARK_END => {
+                    if let Some(s) = start.take() {
+                        highlights.push([s, offset]);
+                    }
+                }
+                c => {
+                    text.push(c);
+                    offset += c.len_utf16();
+                }
+            }
+        }
+        Self { text, highlights }
+    }
+}
+
+/// Turns free text into an FTS5 query where every word is a quoted prefix term,
+/// so "meet" matches "meeting" and punctuation can't be parsed as query syntax.
+pub fn to_match_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\"*"))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// One side of the search over a task table and its FTS index.
+fn search_select(tasks: &str, fts: &str, archived: bool) -> String {
+    let marks = format!("'{MARK_START}', '{MARK_END}'");
+    format!(
+        r#"
+        SELECT t.id, t.title, t.list_id, t.list_name, t.completed, {archived} AS archived,
+               snippet({fts}, 1, {marks}, '…', 16) AS title_snippet,
+               snippet({fts}, 2, {marks}, '…', 24) AS content_snippet,
+               bm25({fts}, 0.0, 10.0, 1.0) AS rank
+        FROM {fts}
+        JOIN {tasks} t ON t.id = {fts}.task_id
+        WHERE {fts} MATCH ?1 AND (?2 IS NULL OR t.list_id = ?2) {live}
+        "#,
+        archived = i64::from(archived),
+        // `archived_tasks` has no trash
+        live = if archived { "" } else { "AND t.deleted_at IS NULL" },
+    )
+}
+
+/// Ranked full-text search over task titles and content, optionally including the archive.
+///
+/// Meant to run on every keystroke: each call supersedes the ones before it,
+/// which stop waiting for a connection or for their query and come back
+/// `superseded`, so a burst of typing leaves only the last query running.
+#[tauri::command]
+pub async fn search_tasks(
+    state: State<'_, AppState>,
+    query: String,
+    limit: i64,
+    list_id: Option<String>,
+    include_archived: Option<bool>,
+) -> Result<SearchResults> {
+    let searches = &state.searches;
+    let generation = searches.next();
+    let results = tokio::select! {
+        results = run_search(&state, &query, limit, list_id, include_archived.unwrap_or(false)) => Some(results?),
+        _ = searches.superseded(generation) => None,
+    };
+    // A newer search may have started after the query finished
+    Ok(match results.filter(|_| searches.is_current(generation)) {
+        Some(results) => SearchResults { generation, superseded: false, results },
+        None => {
+            log::trace!("Abandoned search {generation}");
+            SearchResults { generation, superseded: true, results: Vec::new() }
+        }
+    })
+}
+
+async fn run_search(
+    state: &AppState,
+    query: &str,
+    limit: i64,
+    list_id: Option<String>,
+    include_archived: bool,
+) -> Result<Vec<TaskSearchResult>> {
+    let Some(match_query) = to_match_query(query) else {
+        return Ok(Vec::new());
+    };
+
+    let mut sql = search_select("tasks", "tasks_fts", false);
+    if include_archived {
+        // Scores from separate indexes aren't strictly comparable, but close enough to interleave
+        sql.push_str(" UNION ALL ");
+        sql.push_str(&search_select("archived_tasks", "archived_tasks_fts", true));
+    }
+    // Equal scores fall back to the order titles have in lists
+    sql.push_str(&format!(" ORDER BY rank, title COLLATE {NATURAL}, id LIMIT ?3"));
+
+    let rows: Vec<SearchRow> = sqlx::query_as(&sql)
+        .bind(&match_query)
+        .bind(&list_id)
+        .bind(limit.clamp(1, 200))
+        .fetch_all(&state.db())
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TaskSearchResult {
+            id: row.id,
+            title: Snippet::from_marked(&row.title_snippet),
+            content: (!row.content_snippet.is_empty()).then(|| Snippet::from_marked(&row.content_snippet)),
+            list_id: row.list_id,
+            list_name: row.list_name,
+            completed: row.completed,
+            archived: row.archived,
+            rank: row.rank,
+        })
+        .collect())
+}
@@ -0,0 +1,167 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::AppState;
+
+const EXPORT_VERSION: u32 = 1;
+/// Field names (lowercased, without `_` or `-`) that hold credentials.
+const SECRET_FIELDS: &[&str] = &["apikey", "password", "passphrase", "secret", "token", "username", "authorization"];
+/// Per-machine state rather than preferences; never exported.
+const LOCAL_KEYS: &[&str] = &["focusSession", "window_state", crate::daily_planning::LAST_FIRED_KEY];
+
+/// A settings file from `export_settings`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsExport {
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: i64,
+    pub settings: Map<String, Value>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsChanged<'a> {
+    keys: &'a [String],
+}
+
+/// Reads a JSON-encoded value from the `settings` table.
+pub async fn get<T: DeserializeOwned>(pool: &SqlitePool, key: &str) -> Result<Option<T>> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    match value {
+        Some(value) => Ok(Some(serde_json::from_str(&value)?)),
+        None => Ok(None),
+    }
+}
+
+/// Writes a value to the `settings` table as JSON, like the frontend does.
+pub async fn set<T: Serialize + ?Sized>(pool: &SqlitePool, key: &str, value: &T) -> Result<()> {
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?, ?, ?)")
+        .bind(key)
+        .bind(serde_json::to_string(value)?)
+        .bind(now_ms())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Whether a setting or field name holds a credential, like `apiKey` or a WebDAV password.
+pub fn is_secret(field: &str) -> bool {
+    let normalized: String = field.chars().filter(|c| *c != '_' && *c != '-').flat_map(char::to_lowercase).collect();
+    SECRET_FIELDS.iter().any(|secret| normalized.contains(secret))
+}
+
+/// Drops credential fields at any depth.
+fn remove_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|field, _| !is_secret(field));
+            map.values_mut().for_each(remove_secrets);
+        }
+        Value::Array(items) => items.iter_mut().for_each(remove_secrets),
+        _ => {}
+    }
+}
+
+/// Overlays `incoming` on `existing`: objects merge field by field, anything else is replaced.
+/// Fields only `existing` has survive, which keeps credentials left out of an export.
+fn merge(existing: &mut Value, incoming: Value) {
+    match (existing, incoming) {
+        (Value::Object(existing), Value::Object(incoming)) => {
+            for (field, value) in incoming {
+                match existing.get_mut(&field) {
+                    Some(current) => merge(current, value),
+                    None => {
+                        existing.insert(field, value);
+                    }
+                }
+            }
+        }
+        (existing, incoming) => *existing = incoming,
+    }
+}
+
+/// Writes the preferences in the `settings` table to a JSON file.
+/// With `exclude_sensitive`, API keys and sync credentials are left out.
+#[tauri::command]
+pub async fn export_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    exclude_sensitive: bool,
+) -> Result<usize> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings ORDER BY key")
+        .fetch_all(&state.db())
+        .await?;
+    let mut settings = Map::new();
+    for (key, raw) in rows {
+        if LOCAL_KEYS.contains(&key.as_str()) || (exclude_sensitive && is_secret(&key)) {
+            continue;
+        }
+        let mut value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+        if exclude_sensitive {
+            remove_secrets(&mut value);
+        }
+        settings.insert(key, value);
+    }
+    let export = SettingsExport {
+        version: EXPORT_VERSION,
+        app_version: app.package_info().version.to_string(),
+        exported_at: now_ms(),
+        settings,
+    };
+    std::fs::write(&path, serde_json::to_vec_pretty(&export)?)?;
+    log::info!("Exported {} settings to {path}", export.settings.len());
+    Ok(export.settings.len())
+}
+
+/// Upserts the settings in a file written by `export_settings`, returning the keys written.
+///
+/// Nothing is deleted: settings missing from the file keep their values, and
+/// object-valued ones merge field by field. Keys this version doesn't use are
+/// stored all the same, so a file from a newer version loses nothing.
+#[tauri::command]
+pub async fn import_settings(app: AppHandle, state: State<'_, AppState>, path: String) -> Result<Vec<String>> {
+    let export: SettingsExport = serde_json::from_slice(&std::fs::read(&path)?)?;
+    if export.version > EXPORT_VERSION {
+        return Err(Error::InvalidInput("This settings file was written by a newer version of Tada".into()));
+    }
+
+    let mut tx = state.db().begin().await?;
+    let mut keys = Vec::with_capacity(export.settings.len());
+    for (key, incoming) in export.settings {
+        if LOCAL_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let current: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+            .bind(&key)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let value = match current.and_then(|raw| serde_json::from_str::<Value>(&raw).ok()) {
+            Some(mut current) => {
+                merge(&mut current, incoming);
+                current
+            }
+            None => incoming,
+        };
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?, ?, ?)")
+            .bind(&key)
+            .bind(serde_json::to_string(&value)?)
+            .bind(now_ms())
+            .execute(&mut *tx)
+            .await?;
+        keys.push(key);
+    }
+    tx.commit().await?;
+    log::info!("Imported {} settings from {path}", keys.len());
+    let _ = app.emit("settings-changed", SettingsChanged { keys: &keys });
+    Ok(keys)
+}
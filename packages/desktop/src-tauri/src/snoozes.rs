@@ -0,0 +1,121 @@
+//! Reminders at a chosen time, and the snooze presets offering times to pick.
+//!
+//! `snooze_task` adds a one-off reminder without touching the due date, kept in
+//! the `snoozes` table until it fires so it survives restarts. Each task has at
+//! most one; snoozing again moves it. The presets come from the `snoozePresets`
+//! setting as a list like `["15m", "1h", "thisEvening", "tomorrowMorning"]` and
+//! are resolved against local time when asked for.
+
+use chrono::{Days, NaiveDate, NaiveTime};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::dates::{local_date, resolve_local};
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::{reminders, settings, AppState};
+
+const PRESETS_KEY: &str = "snoozePresets";
+const DEFAULT_PRESETS: [&str; 4] = ["15m", "1h", "thisEvening", "tomorrowMorning"];
+const EVENING_HOUR: u32 = 18;
+const MORNING_HOUR: u32 = 9;
+
+/// A preset resolved to a time.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnoozeOption {
+    /// The preset as written in the setting.
+    pub preset: String,
+    pub label: String,
+    pub until: i64,
+}
+
+/// `hour`:00 local time on `date`, epoch millis.
+fn local_at(date: NaiveDate, hour: u32) -> Option<i64> {
+    let time = NaiveTime::from_hms_opt(hour, 0, 0)?;
+    resolve_local(date.and_time(time)).map(|at| at.timestamp_millis())
+}
+
+/// `15m`, `2h` or `1d` from now, `thisEvening` at 6pm today, or `tomorrowMorning`
+/// at 9am tomorrow. `None` for anything else.
+fn resolve(preset: &str, now: i64) -> Option<SnoozeOption> {
+    let (label, until) = match preset {
+        "thisEvening" => ("This evening".to_string(), local_at(local_date(now), EVENING_HOUR)?),
+        "tomorrowMorning" => {
+            ("Tomorrow morning".to_string(), local_at(local_date(now).checked_add_days(Days::new(1))?, MORNING_HOUR)?)
+        }
+        _ => {
+            let split = preset.find(|c: char| !c.is_ascii_digit())?;
+            let (amount, unit) = preset.split_at(split);
+            let amount: i64 = amount.parse().ok().filter(|n| *n > 0)?;
+            let (unit_ms, unit_name) = match unit {
+                "m" => (60_000, "minute"),
+                "h" => (3_600_000, "hour"),
+                "d" => (86_400_000, "day"),
+                _ => return None,
+            };
+            let plural = if amount == 1 { "" } else { "s" };
+            (format!("{amount} {unit_name}{plural}"), now + amount * unit_ms)
+        }
+    };
+    Some(SnoozeOption { preset: preset.to_string(), label, until })
+}
+
+/// The configured presets as times from now. Ones already past, like "this
+/// evening" late at night, and ones that don't parse are left out.
+#[tauri::command]
+pub async fn get_snooze_presets(state: State<'_, AppState>) -> Result<Vec<SnoozeOption>> {
+    let presets = settings::get::<Vec<String>>(&state.db(), PRESETS_KEY)
+        .await?
+        .unwrap_or_else(|| DEFAULT_PRESETS.map(String::from).to_vec());
+    let now = now_ms();
+    Ok(presets
+        .iter()
+        .filter_map(|preset| {
+            let option = resolve(preset.trim(), now);
+            if option.is_none() {
+                log::warn!("Ignoring unknown snooze preset '{preset}'");
+            }
+            option
+        })
+        .filter(|option| option.until > now)
+        .collect())
+}
+
+/// Reminds about a task at `until_ms`, on top of any due-date reminder.
+#[tauri::command]
+pub async fn snooze_task(app: AppHandle, state: State<'_, AppState>, task_id: String, until_ms: i64) -> Result<()> {
+    let now = now_ms();
+    if until_ms <= now {
+        return Err(Error::InvalidInput("The reminder time has already passed".into()));
+    }
+    let inserted = sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO snoozes (task_id, remind_at, created_at)
+        SELECT id, ?, ? FROM tasks WHERE id = ? AND completed = 0 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(until_ms)
+    .bind(now)
+    .bind(&task_id)
+    .execute(&state.db())
+    .await?
+    .rows_affected();
+    if inserted == 0 {
+        return Err(Error::NotFound(format!("Open task {task_id}")));
+    }
+    log::debug!("Scheduled a reminder for task {task_id}");
+    reminders::reschedule(&app);
+    Ok(())
+}
+
+/// Drops a task's scheduled reminder, if it has one.
+#[tauri::command]
+pub async fn cancel_snooze(app: AppHandle, state: State<'_, AppState>, task_id: String) -> Result<()> {
+    sqlx::query("DELETE FROM snoozes WHERE task_id = ?")
+        .bind(&task_id)
+        .execute(&state.db())
+        .await?;
+    reminders::reschedule(&app);
+    Ok(())
+}
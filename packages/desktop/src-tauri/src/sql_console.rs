@@ -0,0 +1,157 @@
+//! Ad-hoc read-only SQL for advanced users, off unless `advancedMode` is set.
+//!
+//! Writes are ruled out in layers, so no single check has to be perfect: the
+//! text must be one SELECT (or WITH / VALUES) statement; it runs as a subquery,
+//! which no other statement kind can be; and the connection is opened
+//! read-only with `query_only` on and no room to attach other databases.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{Column, ConnectOptions, Connection, Executor};
+use tauri::{AppHandle, State};
+
+use libsqlite3_sys as ffi;
+
+use crate::db::column_to_json;
+use crate::error::{Error, Result};
+use crate::{paths, settings, workspaces, AppState};
+
+const SETTINGS_KEY: &str = "advancedMode";
+const MAX_ROWS: usize = 1000;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    /// More rows matched than `MAX_ROWS`; only the first ones are returned.
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Skips whitespace and comments at the start of `sql`.
+fn skip_trivia(mut sql: &str) -> &str {
+    loop {
+        sql = sql.trim_start();
+        if let Some(rest) = sql.strip_prefix("--") {
+            sql = rest.split_once('\n').map_or("", |(_, after)| after);
+        } else if let Some(rest) = sql.strip_prefix("/*") {
+            sql = rest.split_once("*/").map_or("", |(_, after)| after);
+        } else {
+            return sql;
+        }
+    }
+}
+
+/// The statement without trailing semicolons, if it's a single query.
+fn validate(sql: &str) -> Result<&str> {
+    let body = skip_trivia(sql);
+    let keyword: String = body
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if !matches!(keyword.as_str(), "select" | "with" | "values") {
+        return Err(Error::InvalidInput("Only SELECT queries can be run".into()));
+    }
+
+    // A semicolon outside quotes and comments must only be followed by more of the same
+    let bytes = body.as_bytes();
+    let mut i = 0;
+    let mut end = body.len();
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+            }
+            b'[' => {
+                while i < bytes.len() && bytes[i] != b']' {
+                    i += 1;
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = body[i + 2..].find("*/").map_or(bytes.len(), |at| i + 2 + at + 1);
+            }
+            b';' => {
+                if !skip_trivia(body[i..].trim_start_matches(';')).is_empty() {
+                    return Err(Error::InvalidInput("Only a single statement can be run".into()));
+                }
+                end = i;
+                break;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(body[..end].trim_end())
+}
+
+async fn open_read_only(app: &AppHandle) -> Result<SqliteConnection> {
+    let path = workspaces::active_db_path(&paths::config_dir(app)?);
+    let mut conn = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .pragma("query_only", "ON")
+        .connect()
+        .await?;
+    let mut handle = conn.lock_handle().await?;
+    // SAFETY: the handle is live for as long as the lock is held
+    unsafe { ffi::sqlite3_limit(handle.as_raw_handle().as_ptr(), ffi::SQLITE_LIMIT_ATTACHED, 0) };
+    drop(handle);
+    Ok(conn)
+}
+
+/// Runs one read-only query, returning at most `MAX_ROWS` rows and giving up after `TIMEOUT`.
+#[tauri::command]
+pub async fn run_readonly_query(app: AppHandle, state: State<'_, AppState>, sql: String) -> Result<QueryResult> {
+    if !settings::get::<bool>(&state.db(), SETTINGS_KEY).await?.unwrap_or(false) {
+        return Err(Error::InvalidInput("Turn on advanced mode to run SQL queries".into()));
+    }
+    let statement = validate(&sql)?;
+    // The newline keeps a trailing `--` comment from swallowing the closing parenthesis
+    let wrapped = format!("SELECT * FROM ({statement}\n) LIMIT {}", MAX_ROWS + 1);
+
+    let mut conn = open_read_only(&app).await?;
+    let columns: Vec<String> = conn
+        .describe(wrapped.as_str())
+        .await?
+        .columns()
+        .iter()
+        .map(|c| c.name().to_string())
+        .collect();
+    let raw = conn.lock_handle().await?.as_raw_handle().as_ptr() as usize;
+
+    let started = Instant::now();
+    let fetched = tokio::select! {
+        rows = sqlx::query(&wrapped).fetch_all(&mut conn) => rows.map_err(Error::from),
+        _ = tokio::time::sleep(TIMEOUT) => {
+            // SAFETY: `conn` is still open; sqlite3_interrupt may be called from any thread
+            unsafe { ffi::sqlite3_interrupt(raw as *mut ffi::sqlite3) };
+            Err(Error::InvalidInput(format!("The query took longer than {} seconds", TIMEOUT.as_secs())))
+        }
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let _ = conn.close().await;
+    let mut rows = fetched?;
+
+    let truncated = rows.len() > MAX_ROWS;
+    rows.truncate(MAX_ROWS);
+    let rows = rows
+        .iter()
+        .map(|row| (0..columns.len()).map(|i| column_to_json(row, i)).collect())
+        .collect();
+    log::info!("Ran a read-only query in {elapsed_ms} ms");
+    Ok(QueryResult { columns, rows, truncated, elapsed_ms })
+}
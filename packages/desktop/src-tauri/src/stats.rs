@@ -0,0 +1,123 @@
+//! Aggregate numbers for the dashboard, computed in SQL.
+
+use chrono::Days;
+use serde::Serialize;
+use sqlx::{QueryBuilder, Sqlite};
+use tauri::State;
+
+use crate::dates::{local_date, start_of_local_day};
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::AppState;
+
+const HISTOGRAM_DAYS: u64 = 7;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    pub total: i64,
+    pub completed: i64,
+    /// Open tasks whose due date has passed.
+    pub overdue: i64,
+    /// `completed / total`, 0 for an empty scope.
+    pub completion_rate: f64,
+    /// Mean of `completed_at - created_at` over completed tasks.
+    pub average_completion_hours: f64,
+    /// Tasks with both an estimate and tracked time, the ones `estimate_accuracy` covers.
+    pub estimated_tasks: i64,
+    /// `actual_minutes / estimated_minutes` summed over `estimated_tasks`: above 1
+    /// means tasks ran long. `None` when no task qualifies.
+    pub estimate_accuracy: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DayCount {
+    /// Local calendar day, `YYYY-MM-DD`.
+    pub date: String,
+    /// Local midnight starting the day, epoch millis.
+    pub start: i64,
+    pub completed: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    #[serde(flatten)]
+    pub totals: Totals,
+    /// Completions per local day, oldest first and ending today.
+    pub histogram: Vec<DayCount>,
+}
+
+/// One list, or every list but the trash. Deleted tasks never count.
+fn push_scope(query: &mut QueryBuilder<'_, Sqlite>, list_id: &Option<String>) {
+    query.push("tasks.deleted_at IS NULL AND ");
+    match list_id {
+        Some(list_id) => {
+            query.push("tasks.list_id = ").push_bind(list_id.clone());
+        }
+        None => {
+            query.push("tasks.list_name != 'Trash'");
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn list_stats(state: State<'_, AppState>, list_id: Option<String>) -> Result<Stats> {
+    let now = now_ms();
+
+    // Empty scopes come back as zeros rather than NULL or a NaN rate
+    let mut query = QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT
+            COUNT(*) AS total,
+            COALESCE(SUM(completed = 1), 0) AS completed,
+            COALESCE(SUM(completed = 0 AND due_date IS NOT NULL AND due_date < "#,
+    );
+    query.push_bind(now).push(
+        r#"), 0) AS overdue,
+            CASE WHEN COUNT(*) = 0 THEN 0.0 ELSE CAST(SUM(completed = 1) AS REAL) / COUNT(*) END AS completion_rate,
+            COALESCE(AVG(CASE WHEN completed = 1 AND completed_at IS NOT NULL
+                THEN completed_at - created_at END) / 3600000.0, 0.0) AS average_completion_hours,
+            COALESCE(SUM(estimated_minutes > 0 AND actual_minutes > 0), 0) AS estimated_tasks,
+            CAST(SUM(CASE WHEN estimated_minutes > 0 AND actual_minutes > 0 THEN actual_minutes END) AS REAL)
+                / SUM(CASE WHEN estimated_minutes > 0 AND actual_minutes > 0 THEN estimated_minutes END)
+                AS estimate_accuracy
+        FROM tasks
+        WHERE "#,
+    );
+    push_scope(&mut query, &list_id);
+    let totals: Totals = query.build_query_as().fetch_one(&state.db()).await?;
+
+    // Day boundaries are cut in Rust so the buckets follow the local zone and its DST shifts
+    let today = local_date(now);
+    let mut query = QueryBuilder::<Sqlite>::new("WITH days(date, day_start, day_end) AS (VALUES ");
+    let mut separated = query.separated(", ");
+    for offset in (0..HISTOGRAM_DAYS).rev() {
+        let Some(date) = today.checked_sub_days(Days::new(offset)) else {
+            continue;
+        };
+        let next = date.succ_opt().unwrap_or(date);
+        separated
+            .push("(")
+            .push_bind_unseparated(date.format("%Y-%m-%d").to_string())
+            .push_unseparated(", ")
+            .push_bind_unseparated(start_of_local_day(date))
+            .push_unseparated(", ")
+            .push_bind_unseparated(start_of_local_day(next))
+            .push_unseparated(")");
+    }
+    query.push(
+        r#")
+        SELECT days.date, days.day_start AS start, COUNT(tasks.id) AS completed
+        FROM days
+        LEFT JOIN tasks ON tasks.completed = 1
+            AND tasks.completed_at >= days.day_start AND tasks.completed_at < days.day_end
+            AND "#,
+    );
+    push_scope(&mut query, &list_id);
+    query.push(" GROUP BY days.date, days.day_start ORDER BY days.day_start");
+    let histogram = query.build_query_as().fetch_all(&state.db()).await?;
+
+    Ok(Stats { totals, histogram })
+}
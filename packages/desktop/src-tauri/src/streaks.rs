@@ -0,0 +1,127 @@
+//! Completion streaks for recurring tasks.
+//!
+//! Each occurrence of a recurring task is its own row, so a streak follows the
+//! series through `task_id`, the occurrence still open. Its key is the id of the
+//! occurrence that started it. Completing an occurrence by the end of its local
+//! due day extends the streak; completing it later starts over. A second
+//! completion on the same local day (finishing tomorrow's occurrence early)
+//! keeps the streak without adding to it.
+
+use serde::Serialize;
+use sqlx::{FromRow, Sqlite, Transaction};
+use tauri::{AppHandle, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::dates::{local_date, local_day_bounds};
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::models::Task;
+use crate::AppState;
+
+/// Streak lengths that get a notification.
+const MILESTONES: [i64; 3] = [7, 30, 100];
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Streak {
+    pub id: String,
+    /// The series' open occurrence, or the last one if the series ended.
+    pub task_id: String,
+    pub title: String,
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    /// Local calendar day as `YYYY-MM-DD`.
+    pub last_completed_day: Option<String>,
+    pub updated_at: i64,
+}
+
+/// Updates the streak of the series `completed` belongs to, moving it on to
+/// `next`. Returns the streak when it has just reached a milestone.
+pub async fn record(tx: &mut Transaction<'_, Sqlite>, completed: &Task, next: Option<&Task>) -> Result<Option<Streak>> {
+    let (Some(_), Some(due_date)) = (&completed.recurrence_rule, completed.due_date) else {
+        return Ok(None);
+    };
+    let now = now_ms();
+    let today = local_date(now).to_string();
+    let on_time = local_date(now) <= local_date(due_date);
+
+    let existing: Option<Streak> = sqlx::query_as("SELECT * FROM streaks WHERE task_id = ?")
+        .bind(&completed.id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    let (id, previous, longest) = match &existing {
+        Some(streak) => (streak.id.clone(), Some(streak), streak.longest_streak),
+        None => (completed.id.clone(), None, 0),
+    };
+    let current = match previous {
+        Some(_) if !on_time => 1,
+        Some(streak) if streak.last_completed_day.as_deref() == Some(today.as_str()) => streak.current_streak,
+        Some(streak) => streak.current_streak + 1,
+        None => 1,
+    };
+    let grew = previous.is_none_or(|streak| current > streak.current_streak);
+
+    let streak = Streak {
+        id,
+        task_id: next.map_or_else(|| completed.id.clone(), |next| next.id.clone()),
+        title: completed.title.clone(),
+        current_streak: current,
+        longest_streak: longest.max(current),
+        last_completed_day: Some(today),
+        updated_at: now,
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO streaks (id, task_id, title, current_streak, longest_streak, last_completed_day, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            task_id = excluded.task_id, title = excluded.title, current_streak = excluded.current_streak,
+            longest_streak = excluded.longest_streak, last_completed_day = excluded.last_completed_day,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&streak.id)
+    .bind(&streak.task_id)
+    .bind(&streak.title)
+    .bind(streak.current_streak)
+    .bind(streak.longest_streak)
+    .bind(&streak.last_completed_day)
+    .bind(streak.updated_at)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok((grew && MILESTONES.contains(&current)).then_some(streak))
+}
+
+/// Shows the milestone notification for a streak `record` returned.
+pub fn celebrate(app: &AppHandle, streak: &Streak) {
+    log::info!("Streak of {} for task {}", streak.current_streak, streak.task_id);
+    let result = app
+        .notification()
+        .builder()
+        .title(format!("{} in a row!", streak.current_streak))
+        .body(&streak.title)
+        .show();
+    if let Err(e) = result {
+        log::error!("Failed to show streak notification: {e}");
+    }
+}
+
+/// Every series' streak, longest running first. A series whose open occurrence
+/// is past its due day has already missed, so it reports a current streak of 0.
+#[tauri::command]
+pub async fn get_streaks(state: State<'_, AppState>) -> Result<Vec<Streak>> {
+    let (today_start, _) = local_day_bounds(now_ms());
+    Ok(sqlx::query_as(
+        r#"
+        SELECT s.id, s.task_id, s.title,
+               CASE WHEN t.completed = 0 AND t.due_date < ? THEN 0 ELSE s.current_streak END AS current_streak,
+               s.longest_streak, s.last_completed_day, s.updated_at
+        FROM streaks s LEFT JOIN tasks t ON t.id = s.task_id
+        ORDER BY current_streak DESC, s.longest_streak DESC, s.title
+        "#,
+    )
+    .bind(today_start)
+    .fetch_all(&state.db())
+    .await?)
+}
@@ -0,0 +1,170 @@
+//! Subtask edits, keeping the parent's `complete_percentage` in step.
+//!
+//! The percentage is the share of completed subtasks, rounded down. A task
+//! without subtasks has nothing to count, so it's 0 or 100 by its own state.
+//! With `autoCompleteOnSubtasks` on, checking off the last open subtask
+//! completes the parent too.
+
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::commands::{announce_completed, complete_in, fetch_task, Completed};
+use crate::db::{new_id, now_ms};
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::{Subtask, Task};
+use crate::{settings, AppState};
+
+const AUTO_COMPLETE_KEY: &str = "autoCompleteOnSubtasks";
+
+/// Recomputes a task's `complete_percentage` from its subtasks.
+pub(crate) async fn update_percentage(tx: &mut Transaction<'_, Sqlite>, parent_id: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE tasks SET complete_percentage = (
+            SELECT CASE WHEN COUNT(*) = 0 THEN tasks.completed * 100
+                        ELSE SUM(s.completed = 1) * 100 / COUNT(*) END
+            FROM subtasks s WHERE s.parent_id = tasks.id
+        )
+        WHERE id = ?
+        "#,
+    )
+    .bind(parent_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// The parent after a subtask change: either just updated, or completed by it.
+enum Rollup {
+    Updated(Task),
+    Completed(Completed),
+}
+
+async fn rollup(tx: &mut Transaction<'_, Sqlite>, parent_id: &str, auto_complete: bool) -> Result<Rollup> {
+    update_percentage(tx, parent_id).await?;
+    let parent = fetch_task(tx, parent_id).await?;
+    // Only a change that leaves subtasks behind can finish the parent; deleting the last one doesn't
+    let all_done = parent.complete_percentage == Some(100) && !parent.completed;
+    if auto_complete && all_done {
+        let has_subtasks: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM subtasks WHERE parent_id = ?)")
+            .bind(parent_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        if has_subtasks {
+            return Ok(Rollup::Completed(complete_in(tx, parent_id).await?));
+        }
+    }
+    Ok(Rollup::Updated(parent))
+}
+
+async fn announce(app: &AppHandle, pool: &SqlitePool, rollup: &Rollup) {
+    match rollup {
+        Rollup::Updated(parent) => events::task_updated(app, parent),
+        Rollup::Completed(completed) => {
+            log::debug!("Completed task {} with its last subtask", completed.task.id);
+            announce_completed(app, pool, completed).await;
+        }
+    }
+}
+
+async fn auto_complete(state: &AppState) -> Result<bool> {
+    Ok(settings::get::<bool>(&state.db(), AUTO_COMPLETE_KEY).await?.unwrap_or(false))
+}
+
+async fn fetch_subtask(tx: &mut Transaction<'_, Sqlite>, id: &str) -> Result<Subtask> {
+    sqlx::query_as("SELECT * FROM subtasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Subtask {id}")))
+}
+
+/// Adds a subtask at the end of a task's checklist.
+#[tauri::command]
+pub async fn create_subtask(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    parent_id: String,
+    title: String,
+    due_date: Option<i64>,
+) -> Result<Subtask> {
+    if title.trim().is_empty() {
+        return Err(Error::InvalidInput("Subtask title cannot be empty".into()));
+    }
+    let auto_complete = auto_complete(&state).await?;
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    fetch_task(&mut tx, &parent_id).await?;
+    let id = new_id();
+    let now = now_ms();
+    sqlx::query(
+        r#"
+        INSERT INTO subtasks (id, parent_id, title, completed, due_date, "order", created_at, updated_at)
+        VALUES (?, ?, ?, 0, ?, (SELECT COALESCE(MAX("order"), -1) + 1 FROM subtasks WHERE parent_id = ?), ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&parent_id)
+    .bind(title.trim())
+    .bind(due_date)
+    .bind(&parent_id)
+    .bind(now)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    let subtask = fetch_subtask(&mut tx, &id).await?;
+    let rollup = rollup(&mut tx, &parent_id, auto_complete).await?;
+    tx.commit().await?;
+    announce(&app, &pool, &rollup).await;
+    Ok(subtask)
+}
+
+/// Checks a subtask off, or back on.
+#[tauri::command]
+pub async fn set_subtask_completed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    completed: bool,
+) -> Result<Subtask> {
+    let auto_complete = auto_complete(&state).await?;
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let now = now_ms();
+    sqlx::query(
+        r#"
+        UPDATE subtasks SET completed = ?1, completed_at = CASE WHEN ?1 THEN ?2 END, updated_at = ?2
+        WHERE id = ?3 AND completed IS NOT ?1
+        "#,
+    )
+    .bind(completed)
+    .bind(now)
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+    let subtask = fetch_subtask(&mut tx, &id).await?;
+    let rollup = rollup(&mut tx, &subtask.parent_id, auto_complete).await?;
+    tx.commit().await?;
+    announce(&app, &pool, &rollup).await;
+    Ok(subtask)
+}
+
+#[tauri::command]
+pub async fn delete_subtask(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<()> {
+    let auto_complete = auto_complete(&state).await?;
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let parent_id: Option<String> = sqlx::query_scalar("DELETE FROM subtasks WHERE id = ? RETURNING parent_id")
+        .bind(&id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(parent_id) = parent_id else {
+        return Err(Error::NotFound(format!("Subtask {id}")));
+    };
+    // Deleting the one open subtask leaves the rest all done
+    let rollup = rollup(&mut tx, &parent_id, auto_complete).await?;
+    tx.commit().await?;
+    announce(&app, &pool, &rollup).await;
+    Ok(())
+}
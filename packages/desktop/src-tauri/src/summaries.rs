@@ -0,0 +1,65 @@
+//! Lookup and housekeeping for stored AI summaries.
+//!
+//! Every generated summary is kept, so the table only grows. `prune_summaries`
+//! caps it per `list_key`, sparing whatever the UI has open.
+
+use tauri::State;
+
+use crate::error::{Error, Result};
+use crate::models::Summary;
+use crate::AppState;
+
+/// The newest summary for a period and list, if one was generated.
+#[tauri::command]
+pub async fn get_latest_summary(
+    state: State<'_, AppState>,
+    period_key: String,
+    list_key: String,
+) -> Result<Option<Summary>> {
+    Ok(sqlx::query_as(
+        "SELECT * FROM summaries WHERE period_key = ? AND list_key = ? ORDER BY created_at DESC, id DESC LIMIT 1",
+    )
+    .bind(&period_key)
+    .bind(&list_key)
+    .fetch_optional(&state.db())
+    .await?)
+}
+
+/// Keeps the `keep_per_list` newest summaries of each list and deletes the
+/// rest, returning how many went. Summaries in `protect` are never deleted,
+/// though they still count towards the ones kept.
+#[tauri::command]
+pub async fn prune_summaries(
+    state: State<'_, AppState>,
+    keep_per_list: usize,
+    protect: Option<Vec<String>>,
+) -> Result<u64> {
+    if keep_per_list == 0 {
+        return Err(Error::InvalidInput("At least one summary per list must be kept".into()));
+    }
+    let protect = protect.unwrap_or_default();
+    let mut tx = state.db().begin().await?;
+    let pruned = sqlx::query(
+        r#"
+        DELETE FROM summaries
+        WHERE id IN (
+            SELECT id FROM (
+                SELECT id, ROW_NUMBER() OVER (PARTITION BY list_key ORDER BY created_at DESC, id DESC) AS rank
+                FROM summaries
+            )
+            WHERE rank > ?
+        )
+        AND id NOT IN (SELECT value FROM json_each(?))
+        "#,
+    )
+    .bind(i64::try_from(keep_per_list).unwrap_or(i64::MAX))
+    .bind(serde_json::to_string(&protect)?)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+    if pruned > 0 {
+        log::info!("Pruned {pruned} old summaries, keeping {keep_per_list} per list");
+    }
+    Ok(pruned)
+}
@@ -0,0 +1,352 @@
+//! Multi-device sync through a single JSON snapshot on a WebDAV server.
+//!
+//! Rows merge last-write-wins on `updated_at`; deletions travel as tombstones.
+//! Settings are device-local (window geometry, API keys, these credentials) and never leave the machine.
+
+use std::collections::{BTreeMap, HashMap};
+
+use reqwest::header::{ETAG, IF_MATCH};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use crate::backup::{self, DatabaseExport, ImportMode};
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::power;
+use crate::settings;
+use crate::AppState;
+
+/// Tables that sync, parents before children. Must match the tombstone triggers in migration 7.
+const SYNC_TABLES: &[&str] = &["lists", "tasks", "subtasks", "summaries", "echo_reports"];
+const SNAPSHOT_FILE: &str = "tada-sync.json";
+
+/// The `sync` settings blob. Not `Debug`, so the password can't end up in logs.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncConfig {
+    url: String,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    password: String,
+    #[serde(default)]
+    enabled: bool,
+}
+
+impl SyncConfig {
+    /// The URL may name the snapshot file itself or the folder to keep it in.
+    fn snapshot_url(&self) -> String {
+        let url = self.url.trim().trim_end_matches('/');
+        if url.ends_with(".json") { url.to_string() } else { format!("{url}/{SNAPSHOT_FILE}") }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncState {
+    last_sync_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+struct Tombstone {
+    table_name: String,
+    row_id: String,
+    deleted_at: i64,
+}
+
+/// What lives on the server: the regular export format plus the tombstones.
+#[derive(Debug, Serialize, Deserialize)]
+struct SyncSnapshot {
+    #[serde(flatten)]
+    export: DatabaseExport,
+    #[serde(default)]
+    tombstones: Vec<Tombstone>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// Rows and deletions the server didn't have yet.
+    pub pushed: u64,
+    /// Rows and deletions taken from the server.
+    pub pulled: u64,
+    /// Rows changed on both sides since the last sync, settled by last-write-wins.
+    pub conflicted: u64,
+}
+
+type Key = (String, String);
+
+fn row_id(row: &Map<String, Value>) -> Option<&str> {
+    row.get("id").and_then(Value::as_str)
+}
+
+/// Rows without `updated_at` (echo reports) never change after creation.
+fn row_version(row: &Map<String, Value>) -> i64 {
+    row.get("updated_at")
+        .or_else(|| row.get("created_at"))
+        .and_then(Value::as_i64)
+        .unwrap_or_default()
+}
+
+fn versions(export: &DatabaseExport) -> HashMap<Key, i64> {
+    let mut versions = HashMap::new();
+    for (table, rows) in &export.tables {
+        for row in rows {
+            if let Some(id) = row_id(row) {
+                versions.insert((table.clone(), id.to_string()), row_version(row));
+            }
+        }
+    }
+    versions
+}
+
+fn tombstone_map(tombstones: &[Tombstone]) -> HashMap<Key, i64> {
+    tombstones
+        .iter()
+        .filter(|t| SYNC_TABLES.contains(&t.table_name.as_str()))
+        .map(|t| ((t.table_name.clone(), t.row_id.clone()), t.deleted_at))
+        .collect()
+}
+
+async fn local_snapshot(pool: &SqlitePool) -> Result<SyncSnapshot> {
+    let mut export = backup::snapshot(pool).await?;
+    export.tables.retain(|table, _| SYNC_TABLES.contains(&table.as_str()));
+    let tombstones = sqlx::query_as("SELECT table_name, row_id, deleted_at FROM tombstones")
+        .fetch_all(pool)
+        .await?;
+    Ok(SyncSnapshot { export, tombstones })
+}
+
+/// Downloads the server snapshot with its ETag; `None` when nothing has been uploaded yet.
+async fn download(
+    client: &reqwest::Client,
+    config: &SyncConfig,
+) -> Result<Option<(SyncSnapshot, Option<String>)>> {
+    let response = client
+        .get(config.snapshot_url())
+        .basic_auth(&config.username, Some(&config.password))
+        .send()
+        .await?;
+    match response.status() {
+        StatusCode::NOT_FOUND => Ok(None),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Err(Error::Http("the WebDAV server rejected the credentials".into()))
+        }
+        status if !status.is_success() => Err(Error::Http(format!("WebDAV server returned {status}"))),
+        _ => {
+            let etag = response.headers().get(ETAG).and_then(|v| v.to_str().ok()).map(str::to_string);
+            let snapshot = serde_json::from_slice(&response.bytes().await?)?;
+            Ok(Some((snapshot, etag)))
+        }
+    }
+}
+
+async fn upload(
+    client: &reqwest::Client,
+    config: &SyncConfig,
+    snapshot: &SyncSnapshot,
+    etag: Option<&str>,
+) -> Result<()> {
+    let mut request = client
+        .put(config.snapshot_url())
+        .basic_auth(&config.username, Some(&config.password))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(snapshot)?);
+    // Don't overwrite a snapshot another device uploaded while we were merging
+    if let Some(etag) = etag {
+        request = request.header(IF_MATCH, etag);
+    }
+    let response = request.send().await?;
+    match response.status() {
+        StatusCode::PRECONDITION_FAILED => Err(Error::Http(
+            "another device synced at the same time, please sync again".into(),
+        )),
+        status if !status.is_success() => Err(Error::Http(format!("WebDAV upload returned {status}"))),
+        _ => Ok(()),
+    }
+}
+
+/// Merges the server snapshot into the database, counting pulled and conflicted rows.
+async fn merge(
+    pool: &SqlitePool,
+    local: &SyncSnapshot,
+    remote: &SyncSnapshot,
+    last_sync_at: i64,
+    report: &mut SyncReport,
+) -> Result<BTreeMap<String, u64>> {
+    let local_rows = versions(&local.export);
+    let local_tombstones = tombstone_map(&local.tombstones);
+    let changed_locally = |version: i64| version > last_sync_at;
+
+    let mut tx = pool.begin().await?;
+
+    // Remote deletions win unless the row was edited here after it was deleted there
+    for ((table, id), deleted_at) in tombstone_map(&remote.tombstones) {
+        match local_rows.get(&(table.clone(), id.clone())) {
+            Some(&version) if version > deleted_at => {
+                if changed_locally(version) {
+                    report.conflicted += 1;
+                }
+                continue;
+            }
+            Some(&version) => {
+                if changed_locally(version) {
+                    report.conflicted += 1;
+                }
+                // tombstone_map only yields names from SYNC_TABLES, so this is safe to interpolate
+                sqlx::query(&format!("DELETE FROM \"{table}\" WHERE id = ?"))
+                    .bind(&id)
+                    .execute(&mut *tx)
+                    .await?;
+                report.pulled += 1;
+            }
+            None if local_tombstones.get(&(table.clone(), id.clone())) >= Some(&deleted_at) => continue,
+            None => {}
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO tombstones (table_name, row_id, deleted_at) VALUES (?, ?, ?)
+            ON CONFLICT(table_name, row_id) DO UPDATE SET deleted_at = excluded.deleted_at
+            "#,
+        )
+        .bind(&table)
+        .bind(&id)
+        .bind(deleted_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Drop rows deleted here after the server's copy was written; deletion wins ties
+    let mut tables = remote.export.tables.clone();
+    tables.retain(|table, _| SYNC_TABLES.contains(&table.as_str()));
+    for (table, rows) in tables.iter_mut() {
+        rows.retain(|row| {
+            let Some(id) = row_id(row) else {
+                return false;
+            };
+            let key = (table.clone(), id.to_string());
+            let version = row_version(row);
+            if let Some(&deleted_at) = local_tombstones.get(&key) {
+                if deleted_at >= version {
+                    if changed_locally(version) {
+                        report.conflicted += 1;
+                    }
+                    return false;
+                }
+            }
+            if let Some(&local_version) = local_rows.get(&key) {
+                if local_version != version && changed_locally(local_version) && changed_locally(version) {
+                    report.conflicted += 1;
+                }
+            }
+            true
+        });
+    }
+
+    let incoming = DatabaseExport {
+        schema_version: remote.export.schema_version,
+        exported_at: remote.export.exported_at,
+        tables,
+    };
+    let written = backup::apply(&mut tx, &incoming, ImportMode::Merge).await?;
+    report.pulled += written.values().sum::<u64>();
+    tx.commit().await?;
+    Ok(written)
+}
+
+/// Counts what the merged local state has that the server snapshot lacks.
+fn count_pushed(local: &SyncSnapshot, remote: Option<&SyncSnapshot>) -> u64 {
+    let (remote_rows, remote_tombstones) = match remote {
+        Some(remote) => (versions(&remote.export), tombstone_map(&remote.tombstones)),
+        None => Default::default(),
+    };
+    let rows = versions(&local.export)
+        .into_iter()
+        .filter(|(key, version)| remote_rows.get(key).is_none_or(|remote| version > remote))
+        .count();
+    let tombstones = tombstone_map(&local.tombstones)
+        .into_iter()
+        .filter(|(key, deleted_at)| remote_tombstones.get(key).is_none_or(|remote| deleted_at > remote))
+        .count();
+    (rows + tombstones) as u64
+}
+
+async fn run(app: &AppHandle) -> Result<SyncReport> {
+    let state = app.state::<AppState>();
+    let pool = &state.db();
+    let config: SyncConfig = settings::get(pool, "sync")
+        .await?
+        .filter(|c: &SyncConfig| !c.url.trim().is_empty())
+        .ok_or_else(|| Error::InvalidInput("Sync is not set up. Add a WebDAV URL first.".into()))?;
+    let sync_state: SyncState = settings::get(pool, "syncState").await?.unwrap_or_default();
+    let started_at = now_ms();
+
+    let client = reqwest::Client::new();
+    let mut report = SyncReport::default();
+    let (remote, etag) = match download(&client, &config).await? {
+        Some((remote, etag)) => (Some(remote), etag),
+        None => (None, None),
+    };
+    let mut lists_changed = false;
+    if let Some(remote) = &remote {
+        let local = local_snapshot(pool).await?;
+        let written = merge(pool, &local, remote, sync_state.last_sync_at, &mut report).await?;
+        lists_changed = written.get("lists").is_some_and(|n| *n > 0);
+    }
+
+    let merged = local_snapshot(pool).await?;
+    report.pushed = count_pushed(&merged, remote.as_ref());
+    if report.pushed > 0 || remote.is_none() {
+        upload(&client, &config, &merged, etag.as_deref()).await?;
+    }
+
+    settings::set(pool, "syncState", &SyncState { last_sync_at: started_at }).await?;
+    // One batched event rather than one per pulled row
+    if report.pulled > 0 {
+        events::tasks_changed(app);
+    }
+    if lists_changed {
+        events::list_updated(app, None);
+    }
+    log::info!("Sync finished: {report:?}");
+    Ok(report)
+}
+
+/// Pulls and merges once at startup when sync is enabled, or later once
+/// plugged in if `power` holds it back.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = handle.state::<AppState>();
+        match settings::get::<SyncConfig>(&state.db(), "sync").await {
+            Ok(Some(config)) if config.enabled => {
+                if !power::defer(&handle, power::Job::Sync).await {
+                    run_in_background(&handle).await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to read sync settings: {e}"),
+        }
+    });
+}
+
+/// A sync nobody is waiting on, so failures are only logged.
+pub async fn run_in_background(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let _permit = state.job_permit().await;
+    if let Err(e) = run(app).await {
+        log::error!("Background sync failed: {e}");
+    }
+}
+
+/// Syncs with the configured WebDAV server right away, on battery too.
+#[tauri::command]
+pub async fn sync_now(app: AppHandle) -> Result<SyncReport> {
+    let report = run(&app).await?;
+    power::settle(&app, power::Job::Sync);
+    Ok(report)
+}
@@ -0,0 +1,109 @@
+//! Tags across all tasks: usage counts, and renaming or merging them everywhere.
+//!
+//! Tags live in each task's `tags` JSON array, so a rename rewrites every task
+//! that has the tag. Trashed tasks are rewritten too, so restoring one doesn't
+//! bring an old name back. A task that already has the new name keeps it once.
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::parse_tags;
+use crate::AppState;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUsage {
+    pub name: String,
+    /// Open and completed tasks outside the trash carrying the tag.
+    pub count: i64,
+}
+
+/// Every tag in use, most used first.
+#[tauri::command]
+pub async fn list_tags(state: State<'_, AppState>) -> Result<Vec<TagUsage>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT j.value, COUNT(DISTINCT t.id) FROM tasks t,
+            json_each(CASE WHEN json_valid(t.tags) THEN t.tags ELSE '[]' END) j
+        WHERE t.deleted_at IS NULL AND j.type = 'text'
+        GROUP BY j.value
+        ORDER BY COUNT(DISTINCT t.id) DESC, j.value
+        "#,
+    )
+    .fetch_all(&state.db())
+    .await?;
+    Ok(rows.into_iter().map(|(name, count)| TagUsage { name, count }).collect())
+}
+
+/// `tags` with every tag in `from` replaced by `into`, first occurrence kept.
+fn replace(tags: Vec<String>, from: &[String], into: &str) -> Vec<String> {
+    let mut replaced: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = if from.contains(&tag) { into.to_string() } else { tag };
+        if !replaced.contains(&tag) {
+            replaced.push(tag);
+        }
+    }
+    replaced
+}
+
+/// Replaces the `from` tags with `into` on every task in one transaction,
+/// returning how many tasks changed.
+async fn retag(app: &AppHandle, state: &AppState, from: Vec<String>, into: String) -> Result<u64> {
+    let into = into.trim().to_string();
+    if into.is_empty() {
+        return Err(Error::InvalidInput("Tag name cannot be empty".into()));
+    }
+    let from: Vec<String> = from.into_iter().filter(|tag| *tag != into).collect();
+    if from.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tx = state.db().begin().await?;
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT id, tags FROM tasks
+        WHERE EXISTS (
+            SELECT 1 FROM json_each(CASE WHEN json_valid(tags) THEN tags ELSE '[]' END)
+            WHERE value IN (SELECT value FROM json_each(?))
+        )
+        "#,
+    )
+    .bind(serde_json::to_string(&from)?)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let now = now_ms();
+    for (id, tags) in &rows {
+        let tags = replace(parse_tags(tags.clone()), &from, &into);
+        sqlx::query("UPDATE tasks SET tags = ?, updated_at = ? WHERE id = ?")
+            .bind(serde_json::to_string(&tags)?)
+            .bind(now)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    let changed = rows.len() as u64;
+    if changed > 0 {
+        log::info!("Retagged {changed} tasks from {from:?} to '{into}'");
+        events::tasks_changed(app);
+    }
+    Ok(changed)
+}
+
+/// Renames a tag on every task. Tasks that already had the new name keep one copy.
+#[tauri::command]
+pub async fn rename_tag(app: AppHandle, state: State<'_, AppState>, old: String, new: String) -> Result<u64> {
+    retag(&app, &state, vec![old], new).await
+}
+
+/// Folds several tags into one on every task.
+#[tauri::command]
+pub async fn merge_tags(app: AppHandle, state: State<'_, AppState>, from: Vec<String>, into: String) -> Result<u64> {
+    retag(&app, &state, from, into).await
+}
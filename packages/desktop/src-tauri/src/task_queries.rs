@@ -0,0 +1,58 @@
+//! The hottest read paths, kept as fixed SQL so their prepared statements are reused.
+//!
+//! sqlx caches prepared statements per connection, keyed by the exact SQL text.
+//! These strings never change and every filter value is a bound parameter, so a
+//! different list or date range reuses the same statement, while a query with
+//! different SQL (like each filter shape of `query_tasks`) gets its own entry.
+
+use sqlx::SqlitePool;
+use tauri::State;
+
+use crate::error::Result;
+use crate::models::Task;
+use crate::AppState;
+
+pub const TASKS_IN_LIST_SQL: &str = r#"SELECT * FROM tasks WHERE list_id = ? AND deleted_at IS NULL ORDER BY completed, "order", id"#;
+pub const COUNT_DUE_BETWEEN_SQL: &str = r#"
+    SELECT COUNT(*) FROM tasks
+    WHERE completed = 0 AND list_name != 'Trash' AND deleted_at IS NULL AND due_date >= ? AND due_date < ?
+"#;
+
+pub const DUE_BETWEEN_SQL: &str = r#"
+    SELECT * FROM tasks
+    WHERE completed = 0 AND list_name != 'Trash' AND deleted_at IS NULL AND due_date >= ? AND due_date < ?
+    ORDER BY due_date, COALESCE(priority, 4), "order", id
+    LIMIT ?
+"#;
+
+/// Every task of a list, open ones first in their manual order.
+pub async fn tasks_in_list(pool: &SqlitePool, list_id: &str) -> Result<Vec<Task>> {
+    Ok(sqlx::query_as(TASKS_IN_LIST_SQL)
+        .bind(list_id)
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Open tasks due in `[start, end)`, outside the trash.
+pub async fn count_due_between(pool: &SqlitePool, start: i64, end: i64) -> Result<i64> {
+    Ok(sqlx::query_scalar(COUNT_DUE_BETWEEN_SQL)
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
+        .await?)
+}
+
+/// The first `limit` open tasks due in `[start, end)`, soonest and most urgent first.
+pub async fn due_between(pool: &SqlitePool, start: i64, end: i64, limit: i64) -> Result<Vec<Task>> {
+    Ok(sqlx::query_as(DUE_BETWEEN_SQL)
+        .bind(start)
+        .bind(end)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?)
+}
+
+#[tauri::command]
+pub async fn list_tasks(state: State<'_, AppState>, list_id: String) -> Result<Vec<Task>> {
+    tasks_in_list(&state.db(), &list_id).await
+}
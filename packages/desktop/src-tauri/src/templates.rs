@@ -0,0 +1,278 @@
+//! Task templates: a saved set of tasks and subtasks to create again in one go.
+//!
+//! Due dates are stored relative to the day the template was saved by default,
+//! so a "Monday planning" set saved on a Monday lands on the Monday it's used.
+//! Instantiating always creates fresh ids and timestamps.
+
+use chrono::{Days, Local, NaiveTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Sqlite, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::commands::{self, TaskInput};
+use crate::dates::{local_date, resolve_local, start_of_local_day};
+use crate::db::{new_id, now_ms};
+use crate::error::{Error, Result};
+use crate::models::{Subtask, Task};
+use crate::{subtasks, AppState};
+
+const MAX_NAME_LEN: usize = 100;
+
+/// A due date as saved in a template.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum TemplateDue {
+    Absolute { at: i64 },
+    /// Calendar days from the anchor day, at a wall-clock time or as a date-only due date.
+    #[serde(rename_all = "camelCase")]
+    Relative { days: i64, minute_of_day: Option<u32> },
+}
+
+impl TemplateDue {
+    fn capture(due: i64, anchor: i64, relative: bool) -> Self {
+        if !relative {
+            return Self::Absolute { at: due };
+        }
+        let Some(local) = Local.timestamp_millis_opt(due).single() else {
+            return Self::Absolute { at: due };
+        };
+        let days = (local.date_naive() - local_date(anchor)).num_days();
+        let minute = local.hour() * 60 + local.minute();
+        // Midnight is how date-only due dates are stored
+        Self::Relative { days, minute_of_day: (minute != 0).then_some(minute) }
+    }
+
+    fn resolve(self, anchor: i64) -> Option<i64> {
+        match self {
+            Self::Absolute { at } => Some(at),
+            Self::Relative { days, minute_of_day } => {
+                let base = local_date(anchor);
+                let date = if days >= 0 {
+                    base.checked_add_days(Days::new(days as u64))?
+                } else {
+                    base.checked_sub_days(Days::new(days.unsigned_abs()))?
+                };
+                match minute_of_day {
+                    Some(minute) => {
+                        let time = NaiveTime::from_hms_opt(minute / 60, minute % 60, 0)?;
+                        Some(resolve_local(date.and_time(time))?.timestamp_millis())
+                    }
+                    None => Some(start_of_local_day(date)),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplateSubtask {
+    title: String,
+    #[serde(default)]
+    due: Option<TemplateDue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplateTask {
+    title: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    priority: Option<i64>,
+    #[serde(default)]
+    recurrence_rule: Option<String>,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    due: Option<TemplateDue>,
+    #[serde(default)]
+    subtasks: Vec<TemplateSubtask>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplatePayload {
+    tasks: Vec<TemplateTask>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub task_count: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Saves the given tasks and their subtasks, in that order, as a template.
+///
+/// `relative_dates` (default on) stores due dates as days from today instead
+/// of fixed instants.
+#[tauri::command]
+pub async fn save_template(
+    state: State<'_, AppState>,
+    name: String,
+    task_ids: Vec<String>,
+    relative_dates: Option<bool>,
+) -> Result<TemplateSummary> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(Error::InvalidInput(format!("Template names must be 1 to {MAX_NAME_LEN} characters")));
+    }
+    if task_ids.is_empty() {
+        return Err(Error::InvalidInput("A template needs at least one task".into()));
+    }
+    let relative = relative_dates.unwrap_or(true);
+    let now = now_ms();
+
+    let pool = state.db();
+    let mut payload = TemplatePayload::default();
+    for id in &task_ids {
+        let task: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Task {id}")))?;
+        let subtasks: Vec<Subtask> =
+            sqlx::query_as(r#"SELECT * FROM subtasks WHERE parent_id = ? ORDER BY "order", created_at"#)
+                .bind(id)
+                .fetch_all(&pool)
+                .await?;
+        payload.tasks.push(TemplateTask {
+            title: task.title,
+            content: task.content,
+            tags: task.tags,
+            priority: task.priority,
+            recurrence_rule: task.recurrence_rule,
+            color: task.color,
+            due: task.due_date.map(|due| TemplateDue::capture(due, now, relative)),
+            subtasks: subtasks
+                .into_iter()
+                .map(|s| TemplateSubtask {
+                    title: s.title,
+                    due: s.due_date.map(|due| TemplateDue::capture(due, now, relative)),
+                })
+                .collect(),
+        });
+    }
+
+    let summary = TemplateSummary {
+        id: new_id(),
+        name,
+        task_count: payload.tasks.len() as i64,
+        created_at: now,
+        updated_at: now,
+    };
+    sqlx::query("INSERT INTO templates (id, name, payload, created_at, updated_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&summary.id)
+        .bind(&summary.name)
+        .bind(serde_json::to_string(&payload)?)
+        .bind(now)
+        .bind(now)
+        .execute(&pool)
+        .await?;
+    log::info!("Saved template '{}' with {} tasks", summary.name, summary.task_count);
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn list_templates(state: State<'_, AppState>) -> Result<Vec<TemplateSummary>> {
+    Ok(sqlx::query_as(
+        r#"
+        SELECT id, name, COALESCE(json_array_length(payload, '$.tasks'), 0) AS task_count, created_at, updated_at
+        FROM templates ORDER BY name COLLATE NOCASE
+        "#,
+    )
+    .fetch_all(&state.db())
+    .await?)
+}
+
+#[tauri::command]
+pub async fn delete_template(state: State<'_, AppState>, id: String) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM templates WHERE id = ?")
+        .bind(&id)
+        .execute(&state.db())
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("Template {id}")));
+    }
+    Ok(())
+}
+
+async fn insert_subtask(
+    tx: &mut Transaction<'_, Sqlite>,
+    parent_id: &str,
+    title: &str,
+    due_date: Option<i64>,
+    order: i64,
+) -> Result<()> {
+    let now = now_ms();
+    sqlx::query(
+        r#"
+        INSERT INTO subtasks (id, parent_id, title, completed, due_date, "order", created_at, updated_at)
+        VALUES (?, ?, ?, 0, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(new_id())
+    .bind(parent_id)
+    .bind(title)
+    .bind(due_date)
+    .bind(order)
+    .bind(now)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Creates the template's tasks in a list, with relative due dates counted from
+/// `anchor` (default: now). Returns the new tasks in template order.
+#[tauri::command]
+pub async fn instantiate_template(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    template_id: String,
+    list_id: String,
+    anchor: Option<i64>,
+) -> Result<Vec<Task>> {
+    let anchor = anchor.unwrap_or_else(now_ms);
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    let payload: String = sqlx::query_scalar("SELECT payload FROM templates WHERE id = ?")
+        .bind(&template_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Template {template_id}")))?;
+    let payload: TemplatePayload = serde_json::from_str(&payload)?;
+
+    let mut created = Vec::with_capacity(payload.tasks.len());
+    for template in payload.tasks {
+        let input = TaskInput {
+            title: template.title,
+            content: template.content,
+            // Explicit, so the list's defaults don't override what the template says
+            due_date: Some(template.due.and_then(|due| due.resolve(anchor))),
+            list_id: Some(list_id.clone()),
+            tags: template.tags,
+            priority: Some(template.priority),
+            recurrence_rule: template.recurrence_rule,
+            color: template.color,
+            start_date: None,
+        };
+        let task = commands::insert_task(&mut tx, &input).await?;
+        for (order, subtask) in template.subtasks.iter().enumerate() {
+            let due = subtask.due.and_then(|due| due.resolve(anchor));
+            insert_subtask(&mut tx, &task.id, &subtask.title, due, order as i64).await?;
+        }
+        subtasks::update_percentage(&mut tx, &task.id).await?;
+        created.push(commands::fetch_task(&mut tx, &task.id).await?);
+    }
+    tx.commit().await?;
+    log::info!("Created {} tasks from template {template_id}", created.len());
+    commands::announce_created(&app, &pool, &created).await;
+    Ok(created)
+}
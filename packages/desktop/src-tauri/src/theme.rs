@@ -0,0 +1,141 @@
+//! Window chrome that follows the `darkMode` appearance setting.
+//!
+//! With `"system"` the windows follow the OS theme, and a change at runtime
+//! arrives as `WindowEvent::ThemeChanged`, which is passed on to that window's
+//! webview as `system-theme-changed` for the UI to switch as well. `"light"`
+//! and `"dark"` pin the chrome. On Windows the title bar gets the immersive
+//! dark mode attribute to match, as it doesn't follow the theme on its own.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager, State, Theme, WebviewWindow, Window};
+
+use crate::error::Result;
+use crate::settings;
+use crate::AppState;
+
+const APPEARANCE_KEY: &str = "appearance";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DarkMode {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// The `darkMode` in effect, kept for window events, which can't wait on the database.
+#[derive(Default)]
+pub struct ThemeState(Mutex<DarkMode>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemThemeChanged {
+    dark: bool,
+}
+
+async fn load(app: &AppHandle) -> DarkMode {
+    let pool = app.state::<AppState>().db();
+    match settings::get::<Map<String, Value>>(&pool, APPEARANCE_KEY).await {
+        Ok(appearance) => appearance
+            .and_then(|mut appearance| appearance.remove("darkMode"))
+            .and_then(|mode| serde_json::from_value(mode).ok())
+            .unwrap_or_default(),
+        Err(e) => {
+            log::error!("Failed to read the appearance setting: {e}");
+            DarkMode::default()
+        }
+    }
+}
+
+/// Sets the immersive dark mode attribute, which only Windows 10 20H1 and later know.
+#[cfg(windows)]
+fn set_dark_titlebar(window: &Window, dark: bool) {
+    use windows_sys::Win32::Graphics::Dwm::{DwmSetWindowAttribute, DWMWA_USE_IMMERSIVE_DARK_MODE};
+
+    let Ok(hwnd) = window.hwnd() else {
+        return;
+    };
+    let value = i32::from(dark);
+    // SAFETY: `hwnd` belongs to a live window and `value` outlives the call
+    let result = unsafe {
+        DwmSetWindowAttribute(
+            hwnd.0 as _,
+            DWMWA_USE_IMMERSIVE_DARK_MODE as u32,
+            (&value as *const i32).cast(),
+            size_of::<i32>() as u32,
+        )
+    };
+    if result != 0 {
+        log::debug!("Failed to set the dark title bar on {}: {result:#x}", window.label());
+    }
+}
+
+#[cfg(not(windows))]
+fn set_dark_titlebar(_window: &Window, _dark: bool) {}
+
+fn apply_to(window: &Window, mode: DarkMode) {
+    let theme = match mode {
+        DarkMode::System => None,
+        DarkMode::Light => Some(Theme::Light),
+        DarkMode::Dark => Some(Theme::Dark),
+    };
+    if let Err(e) = window.set_theme(theme) {
+        log::warn!("Failed to set the theme of {}: {e}", window.label());
+    }
+    // Following the system, this is the OS theme
+    let dark = matches!(window.theme(), Ok(Theme::Dark));
+    set_dark_titlebar(window, dark);
+}
+
+fn apply_all(app: &AppHandle, mode: DarkMode) {
+    for window in app.webview_windows().into_values() {
+        apply_to(&window.as_ref().window(), mode);
+    }
+}
+
+/// Applies the stored `darkMode` to the windows open at launch.
+pub fn init(app: &AppHandle) {
+    let mode = tauri::async_runtime::block_on(load(app));
+    app.manage(ThemeState(Mutex::new(mode)));
+    apply_all(app, mode);
+}
+
+/// Gives a window opened after launch, like a list window, the current chrome.
+pub fn apply_to_new(window: &WebviewWindow) {
+    if let Some(state) = window.try_state::<ThemeState>() {
+        let mode = *state.0.lock().unwrap();
+        apply_to(&window.as_ref().window(), mode);
+    }
+}
+
+/// Handles the OS switching between light and dark while the app runs.
+pub fn on_theme_changed(window: &Window, theme: Theme) {
+    let mode = window.try_state::<ThemeState>().map(|state| *state.0.lock().unwrap()).unwrap_or_default();
+    let dark = theme == Theme::Dark;
+    if mode == DarkMode::System {
+        set_dark_titlebar(window, dark);
+    }
+    log::debug!("System theme changed to {}", if dark { "dark" } else { "light" });
+    let _ = window.emit_to(window.label(), "system-theme-changed", SystemThemeChanged { dark });
+}
+
+/// Stores `darkMode` in the appearance setting and applies it to every window.
+#[tauri::command]
+pub async fn set_dark_mode(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    theme: State<'_, ThemeState>,
+    mode: DarkMode,
+) -> Result<()> {
+    let pool = state.db();
+    let mut appearance = settings::get::<Map<String, Value>>(&pool, APPEARANCE_KEY).await?.unwrap_or_default();
+    appearance.insert("darkMode".into(), serde_json::to_value(mode)?);
+    settings::set(&pool, APPEARANCE_KEY, &appearance).await?;
+    *theme.0.lock().unwrap() = mode;
+    apply_all(&app, mode);
+    Ok(())
+}
@@ -0,0 +1,257 @@
+//! The trash: deleted tasks and lists keep their rows, with `deleted_at` set,
+//! until the trash is emptied.
+//!
+//! A list deleted together with its tasks stamps them with its own `deleted_at`,
+//! which is how restoring the list knows which tasks to bring back; tasks trashed
+//! on their own before that stay in the trash. A task restored while its list is
+//! still trashed (or gone) comes back in the Inbox, and says so.
+
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row, Sqlite, Transaction};
+use tauri::{AppHandle, State};
+
+use crate::attachments;
+use crate::commands::{fetch_task, next_order, resolve_list};
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::Task;
+use crate::AppState;
+
+const DAY_MS: i64 = 86_400_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedTask {
+    #[serde(flatten)]
+    pub task: Task,
+    pub deleted_at: i64,
+    /// The task's list is in the trash too, or gone.
+    pub list_deleted: bool,
+}
+
+impl<'r> FromRow<'r, SqliteRow> for TrashedTask {
+    fn from_row(row: &'r SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            task: Task::from_row(row)?,
+            deleted_at: row.try_get("deleted_at")?,
+            list_deleted: row.try_get("list_deleted")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedList {
+    pub id: String,
+    pub name: String,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    pub deleted_at: i64,
+    /// Tasks that went to the trash with the list and come back with it.
+    pub task_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trash {
+    pub lists: Vec<TrashedList>,
+    pub tasks: Vec<TrashedTask>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredTask {
+    #[serde(flatten)]
+    pub task: Task,
+    /// Name of the list the task was in when that list couldn't take it back,
+    /// so it was restored to the Inbox instead.
+    pub moved_from_list: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Restored {
+    /// Set when `id` was a list.
+    pub list_id: Option<String>,
+    pub tasks: Vec<RestoredTask>,
+}
+
+#[tauri::command]
+pub async fn query_trash(state: State<'_, AppState>) -> Result<Trash> {
+    let pool = state.db();
+    let lists: Vec<TrashedList> = sqlx::query_as(
+        r#"
+        SELECT l.id, l.name, l.icon, l.color, l.deleted_at,
+               (SELECT COUNT(*) FROM tasks t WHERE t.list_id = l.id AND t.deleted_at = l.deleted_at) AS task_count
+        FROM lists l
+        WHERE l.deleted_at IS NOT NULL
+        ORDER BY l.deleted_at DESC
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    // A list that's gone altogether counts as deleted too: the task can't go back to it
+    let tasks = sqlx::query_as(
+        r#"
+        SELECT t.*, (l.id IS NULL OR l.deleted_at IS NOT NULL) AS list_deleted
+        FROM tasks t LEFT JOIN lists l ON l.id = t.list_id
+        WHERE t.deleted_at IS NOT NULL
+        ORDER BY t.deleted_at DESC, t."order"
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(Trash { lists, tasks })
+}
+
+/// Takes one task out of the trash, into the Inbox if its list can't have it back.
+pub(crate) async fn restore_task(tx: &mut Transaction<'_, Sqlite>, id: &str) -> Result<RestoredTask> {
+    let (list_id, list_name): (Option<String>, String) =
+        sqlx::query_as("SELECT list_id, list_name FROM tasks WHERE id = ? AND deleted_at IS NOT NULL")
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Task {id} in the trash")))?;
+    let list_live = match &list_id {
+        Some(list_id) => resolve_list(tx, Some(list_id)).await.is_ok(),
+        None => false,
+    };
+
+    let now = now_ms();
+    let moved_from_list = if list_live {
+        sqlx::query("UPDATE tasks SET deleted_at = NULL, updated_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        None
+    } else {
+        let (inbox_id, inbox_name) = resolve_list(tx, None).await?;
+        let order = next_order(tx, &inbox_id).await?;
+        sqlx::query(
+            r#"UPDATE tasks SET deleted_at = NULL, list_id = ?, list_name = ?, "order" = ?, updated_at = ? WHERE id = ?"#,
+        )
+        .bind(&inbox_id)
+        .bind(&inbox_name)
+        .bind(order)
+        .bind(now)
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+        Some(list_name)
+    };
+    Ok(RestoredTask { task: fetch_task(tx, id).await?, moved_from_list })
+}
+
+/// Restores a list, with the tasks trashed along with it.
+async fn restore_list(tx: &mut Transaction<'_, Sqlite>, id: &str, deleted_at: i64) -> Result<Vec<RestoredTask>> {
+    sqlx::query("UPDATE lists SET deleted_at = NULL, updated_at = ? WHERE id = ?")
+        .bind(now_ms())
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    let ids: Vec<String> = sqlx::query_scalar(r#"SELECT id FROM tasks WHERE list_id = ? AND deleted_at = ? ORDER BY "order""#)
+        .bind(id)
+        .bind(deleted_at)
+        .fetch_all(&mut **tx)
+        .await?;
+    let mut tasks = Vec::with_capacity(ids.len());
+    for task_id in &ids {
+        tasks.push(restore_task(tx, task_id).await?);
+    }
+    Ok(tasks)
+}
+
+/// Takes a task or a list out of the trash.
+#[tauri::command]
+pub async fn restore(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<Restored> {
+    let mut tx = state.db().begin().await?;
+    let list_deleted_at: Option<i64> =
+        sqlx::query_scalar("SELECT deleted_at FROM lists WHERE id = ? AND deleted_at IS NOT NULL")
+            .bind(&id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let restored = match list_deleted_at {
+        Some(deleted_at) => Restored { tasks: restore_list(&mut tx, &id, deleted_at).await?, list_id: Some(id) },
+        None => Restored { list_id: None, tasks: vec![restore_task(&mut tx, &id).await?] },
+    };
+    tx.commit().await?;
+
+    for restored_task in &restored.tasks {
+        if let Some(from) = &restored_task.moved_from_list {
+            log::info!("Restored task {} to the Inbox; its list '{from}' is deleted", restored_task.task.id);
+        }
+    }
+    match &restored.list_id {
+        Some(list_id) => {
+            log::debug!("Restored list {list_id} with {} tasks", restored.tasks.len());
+            events::list_updated(&app, Some(list_id));
+            events::tasks_changed(&app);
+        }
+        None => events::task_created(&app, &restored.tasks[0].task),
+    }
+    Ok(restored)
+}
+
+/// Deletes a trashed task for good, with its subtasks and attachments. Returns
+/// the attachment files to remove once the transaction commits.
+async fn purge_task(tx: &mut Transaction<'_, Sqlite>, id: &str) -> Result<Vec<String>> {
+    let files = attachments::stored_paths_for_task(&mut **tx, id).await?;
+    // Children are removed explicitly in case foreign key enforcement is off for this connection
+    sqlx::query("DELETE FROM subtasks WHERE parent_id = ?")
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query("DELETE FROM attachments WHERE task_id = ?")
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query("DELETE FROM tasks WHERE id = ?").bind(id).execute(&mut **tx).await?;
+    Ok(files)
+}
+
+/// Permanently deletes what has been in the trash for more than
+/// `older_than_days`, or everything in it when that's left out. Returns how
+/// many tasks and lists went.
+#[tauri::command]
+pub async fn empty_trash(app: AppHandle, state: State<'_, AppState>, older_than_days: Option<i64>) -> Result<u64> {
+    if older_than_days.is_some_and(|days| days < 0) {
+        return Err(Error::InvalidInput("older_than_days can't be negative".into()));
+    }
+    let cutoff = older_than_days.map_or(i64::MAX, |days| now_ms() - days.saturating_mul(DAY_MS));
+    let mut tx = state.db().begin().await?;
+    let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at <= ?")
+        .bind(cutoff)
+        .fetch_all(&mut *tx)
+        .await?;
+    let mut files = Vec::new();
+    for id in &ids {
+        files.extend(purge_task(&mut tx, id).await?);
+    }
+    // Trashed tasks newer than their list lose it, and restore into the Inbox. Set
+    // explicitly in case foreign key enforcement is off for this connection
+    sqlx::query(
+        "UPDATE tasks SET list_id = NULL WHERE list_id IN (SELECT id FROM lists WHERE deleted_at IS NOT NULL AND deleted_at <= ?)",
+    )
+    .bind(cutoff)
+    .execute(&mut *tx)
+    .await?;
+    let lists = sqlx::query("DELETE FROM lists WHERE deleted_at IS NOT NULL AND deleted_at <= ?")
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+    // Files only go once the delete is committed
+    attachments::remove_files(&app, &files);
+
+    let purged = ids.len() as u64 + lists;
+    if purged > 0 {
+        log::info!("Emptied the trash: {} tasks, {lists} lists", ids.len());
+        events::tasks_changed(&app);
+    }
+    Ok(purged)
+}
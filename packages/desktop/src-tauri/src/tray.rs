@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::image::Image;
+use tauri::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::close_behavior;
+use crate::commands;
+use crate::dates::local_day_bounds;
+use crate::db::now_ms;
+use crate::error::Result;
+use crate::events;
+use crate::models::Task;
+use crate::reminders;
+use crate::task_queries;
+use crate::workspaces;
+use crate::{show_main_window, AppState};
+
+const TRAY_ID: &str = "tray";
+/// Also rolls the count over shortly after local midnight.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Tasks listed in the menu, at most.
+const MENU_TASKS: i64 = 5;
+/// Longer titles are cut off with an ellipsis.
+const MAX_TITLE_CHARS: usize = 40;
+const SNOOZE_CHOICES: [(i64, &str); 3] = [(15, "Snooze 15 minutes"), (60, "Snooze 1 hour"), (180, "Snooze 3 hours")];
+
+/// What a task entry in the menu does when clicked.
+#[derive(Debug, Clone)]
+enum TrayAction {
+    Complete(String),
+    Snooze(String, i64),
+}
+
+/// Actions of the task entries in the current menu, by menu item id.
+#[derive(Default)]
+pub struct TrayState {
+    actions: Mutex<HashMap<String, TrayAction>>,
+}
+
+/// `Call the plumber about the leaking…`; `&` doubled so Windows doesn't read it as a mnemonic.
+fn menu_title(title: &str) -> String {
+    let mut short: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    if title.chars().count() > MAX_TITLE_CHARS {
+        short = format!("{}…", short.trim_end());
+    }
+    short.replace('&', "&&")
+}
+
+/// The whole menu: today's count, up to `MENU_TASKS` tasks, then Show and Quit.
+fn build_menu(app: &AppHandle, summary: &str, tasks: &[Task]) -> tauri::Result<Menu<Wry>> {
+    let mut actions = HashMap::new();
+    let mut items: Vec<Box<dyn IsMenuItem<Wry>>> = vec![
+        Box::new(MenuItem::with_id(app, "today", summary, false, None::<&str>)?),
+        Box::new(PredefinedMenuItem::separator(app)?),
+    ];
+
+    for task in tasks {
+        // Ids name the task, so a click racing a rebuild can't land on whatever took its slot
+        let complete_id = format!("task-{}-complete", task.id);
+        let complete = MenuItem::with_id(app, &complete_id, "Complete", true, None::<&str>)?;
+        actions.insert(complete_id, TrayAction::Complete(task.id.clone()));
+        let mut entries: Vec<Box<dyn IsMenuItem<Wry>>> =
+            vec![Box::new(complete), Box::new(PredefinedMenuItem::separator(app)?)];
+        for (minutes, label) in SNOOZE_CHOICES {
+            let id = format!("task-{}-snooze-{minutes}", task.id);
+            entries.push(Box::new(MenuItem::with_id(app, &id, label, true, None::<&str>)?));
+            actions.insert(id, TrayAction::Snooze(task.id.clone(), minutes));
+        }
+        let entries: Vec<&dyn IsMenuItem<Wry>> = entries.iter().map(|item| item.as_ref()).collect();
+        items.push(Box::new(Submenu::with_items(app, menu_title(&task.title), true, &entries)?));
+    }
+    if !tasks.is_empty() {
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    }
+
+    items.push(Box::new(MenuItem::with_id(app, "show", "Show Tada", true, None::<&str>)?));
+    items.push(Box::new(MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?));
+    let items: Vec<&dyn IsMenuItem<Wry>> = items.iter().map(|item| item.as_ref()).collect();
+    let menu = Menu::with_items(app, &items)?;
+    *app.state::<TrayState>().actions.lock().unwrap() = actions;
+    Ok(menu)
+}
+
+fn run_action(app: &AppHandle, action: TrayAction) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match &action {
+            TrayAction::Complete(task_id) => {
+                let pool = handle.state::<AppState>().db();
+                commands::complete(&handle, &pool, task_id, false).await.map(|_| ())
+            }
+            TrayAction::Snooze(task_id, minutes) => reminders::snooze(&handle, task_id, *minutes).await,
+        };
+        if let Err(e) = result {
+            log::error!("Tray action {action:?} failed: {e}");
+        }
+        // Right away rather than waiting for the change event, so the entry doesn't linger
+        if let Err(e) = refresh(&handle).await {
+            log::error!("Failed to refresh tray: {e}");
+        }
+    });
+}
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    app.manage(TrayState::default());
+    let menu = build_menu(app, "No tasks due today", &[])?;
+
+    let icon_bytes = include_bytes!("../icons/tray-icon.png");
+    let icon = Image::from_bytes(icon_bytes).expect("Failed to load tray icon");
+
+    // Build the tray icon
+    let tray_builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .icon(icon)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "quit" => {
+                // User clicked the exit button of the tray
+                close_behavior::quit(app);
+            }
+            "show" => {
+                // User clicked "Display"
+                show_main_window(app);
+            }
+            id => {
+                let action = app.state::<TrayState>().actions.lock().unwrap().get(id).cloned();
+                if let Some(action) = action {
+                    run_action(app, action);
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| match event {
+            // Left-click the tray icon on Windows/Linux to display the window
+            TrayIconEvent::Click {
+                button: MouseButton::Left,
+                ..
+            } => {
+                show_main_window(tray.app_handle());
+            }
+            _ => {}
+        });
+
+    #[cfg(target_os = "macos")]
+    let tray_builder = tray_builder.icon_as_template(true);
+
+    tray_builder.build(app)?;
+
+    events::on_tasks_changed(app, |handle| {
+        tauri::async_runtime::spawn(async move {
+            let state = handle.state::<AppState>();
+            let _permit = state.job_permit().await;
+            if let Err(e) = refresh(&handle).await {
+                log::error!("Failed to refresh tray: {e}");
+            }
+        });
+    });
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = handle.state::<AppState>();
+            let _permit = state.job_permit().await;
+            if let Err(e) = refresh(&handle).await {
+                log::error!("Failed to refresh tray: {e}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Recounts today's incomplete tasks and rebuilds the menu and tooltip.
+pub async fn refresh(app: &AppHandle) -> Result<()> {
+    let pool = app.state::<AppState>().db();
+    let (start, end) = local_day_bounds(now_ms());
+    let count = task_queries::count_due_between(&pool, start, end).await?;
+    let tasks = task_queries::due_between(&pool, start, end, MENU_TASKS).await?;
+
+    let text = match count {
+        0 => "No tasks due today".to_string(),
+        1 => "1 task due today".to_string(),
+        n => format!("{n} tasks due today"),
+    };
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_menu(Some(build_menu(app, &text, &tasks)?))?;
+        let tooltip = match workspaces::label(app) {
+            Some(workspace) => format!("{workspace}: {text}"),
+            None => text,
+        };
+        tray.set_tooltip(Some(&tooltip))?;
+    }
+    Ok(())
+}
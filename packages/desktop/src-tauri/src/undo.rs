@@ -0,0 +1,268 @@
+//! Undo and redo for the last few completions, deletions and moves.
+//!
+//! The history is kept in memory only and cleared on a workspace switch, so an
+//! entry is never replayed against a database other than its own. Undoing a
+//! completion puts the task back as it was and deletes the occurrence a
+//! recurring task spawned; the streak keeps the completion. Undoing a delete
+//! takes the task out of the trash the way `restore` does.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use sqlx::{Sqlite, Transaction};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::{announce_completed, complete_in, delete_in, fetch_task, move_in, Completed};
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::events;
+use crate::models::Task;
+use crate::trash::restore_task;
+use crate::AppState;
+
+/// Mutations kept to undo; the oldest falls off past this.
+const LIMIT: usize = 50;
+
+/// A completion to take back: the task before it, and the occurrence it spawned.
+#[derive(Debug, Clone)]
+pub(crate) struct Completion {
+    previous: Task,
+    next_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum UndoEntry {
+    Complete(Vec<Completion>),
+    Delete(Vec<String>),
+    /// A task moved between two `(list id, index)` positions.
+    Move { task_id: String, from: (String, usize), to: (String, usize) },
+}
+
+impl UndoEntry {
+    /// The completions worth undoing; completing a done task again changed nothing.
+    fn completed(completed: &[Completed]) -> Option<Self> {
+        let completions: Vec<Completion> = completed
+            .iter()
+            .filter(|c| !c.previous.completed)
+            .map(|c| Completion { previous: c.previous.clone(), next_id: c.next.as_ref().map(|next| next.id.clone()) })
+            .collect();
+        (!completions.is_empty()).then_some(Self::Complete(completions))
+    }
+
+    fn summary(&self) -> Undone {
+        let (kind, task_ids) = match self {
+            Self::Complete(completions) => {
+                (UndoKind::Complete, completions.iter().map(|c| c.previous.id.clone()).collect())
+            }
+            Self::Delete(ids) => (UndoKind::Delete, ids.clone()),
+            Self::Move { task_id, .. } => (UndoKind::Move, vec![task_id.clone()]),
+        };
+        Undone { kind, task_ids }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    undo: VecDeque<UndoEntry>,
+    redo: VecDeque<UndoEntry>,
+}
+
+fn push(stack: &mut VecDeque<UndoEntry>, entry: UndoEntry) {
+    if stack.len() == LIMIT {
+        stack.pop_front();
+    }
+    stack.push_back(entry);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UndoKind {
+    Complete,
+    Delete,
+    Move,
+}
+
+/// What `undo` or `redo` just replayed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Undone {
+    pub kind: UndoKind,
+    pub task_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+/// Records a committed mutation. Anything that could be redone is forgotten.
+pub(crate) fn record(app: &AppHandle, entry: UndoEntry) {
+    let state = app.state::<AppState>();
+    let mut history = state.history.lock().unwrap();
+    history.redo.clear();
+    push(&mut history.undo, entry);
+}
+
+/// Records committed completions, if any of them changed a task.
+pub(crate) fn record_completed(app: &AppHandle, completed: &[Completed]) {
+    if let Some(entry) = UndoEntry::completed(completed) {
+        record(app, entry);
+    }
+}
+
+/// Forgets the history, when the database it refers to is swapped out.
+pub(crate) fn clear(state: &AppState) {
+    *state.history.lock().unwrap() = History::default();
+}
+
+/// Puts tasks back as they were before completing, returning each with the
+/// deleted next occurrence and its list.
+async fn uncomplete(
+    tx: &mut Transaction<'_, Sqlite>,
+    completions: &[Completion],
+) -> Result<Vec<(Task, Option<(String, Option<String>)>)>> {
+    let now = now_ms();
+    let mut tasks = Vec::with_capacity(completions.len());
+    for Completion { previous, next_id } in completions {
+        sqlx::query(
+            r#"
+            UPDATE tasks
+            SET completed = ?, completed_at = ?, complete_percentage = ?, group_category = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(previous.completed)
+        .bind(previous.completed_at)
+        .bind(previous.complete_percentage)
+        .bind(&previous.group_category)
+        .bind(now)
+        .bind(&previous.id)
+        .execute(&mut **tx)
+        .await?;
+        let mut removed = None;
+        if let Some(next_id) = next_id {
+            let list_id: Option<Option<String>> = sqlx::query_scalar("DELETE FROM tasks WHERE id = ? RETURNING list_id")
+                .bind(next_id)
+                .fetch_optional(&mut **tx)
+                .await?;
+            removed = list_id.map(|list_id| (next_id.clone(), list_id));
+        }
+        tasks.push((fetch_task(tx, &previous.id).await?, removed));
+    }
+    Ok(tasks)
+}
+
+/// Replays an entry one way in a transaction and emits the events for it.
+/// Returns the entry to put on the other stack, if there's anything to replay.
+async fn apply(app: &AppHandle, state: &AppState, entry: UndoEntry, direction: Direction) -> Result<Option<UndoEntry>> {
+    let pool = state.db();
+    let mut tx = pool.begin().await?;
+    match (entry, direction) {
+        (UndoEntry::Complete(completions), Direction::Undo) => {
+            let tasks = uncomplete(&mut tx, &completions).await?;
+            tx.commit().await?;
+            for (task, removed) in &tasks {
+                events::task_updated(app, task);
+                if let Some((next_id, list_id)) = removed {
+                    events::task_deleted(app, next_id, list_id.as_deref());
+                }
+            }
+            Ok(Some(UndoEntry::Complete(completions)))
+        }
+        (UndoEntry::Complete(completions), Direction::Redo) => {
+            let mut completed = Vec::with_capacity(completions.len());
+            for completion in &completions {
+                completed.push(complete_in(&mut tx, &completion.previous.id).await?);
+            }
+            tx.commit().await?;
+            for completed in &completed {
+                announce_completed(app, &pool, completed).await;
+            }
+            Ok(UndoEntry::completed(&completed))
+        }
+        (UndoEntry::Delete(ids), Direction::Undo) => {
+            let mut restored = Vec::with_capacity(ids.len());
+            for id in &ids {
+                // Already restored, or emptied out of the trash since
+                match restore_task(&mut tx, id).await {
+                    Ok(task) => restored.push(task),
+                    Err(Error::NotFound(_)) => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            tx.commit().await?;
+            for restored_task in &restored {
+                if let Some(from) = &restored_task.moved_from_list {
+                    log::info!("Restored task {} to the Inbox; its list '{from}' is deleted", restored_task.task.id);
+                }
+                events::task_created(app, &restored_task.task);
+            }
+            let ids: Vec<String> = restored.into_iter().map(|restored| restored.task.id).collect();
+            Ok((!ids.is_empty()).then_some(UndoEntry::Delete(ids)))
+        }
+        (UndoEntry::Delete(ids), Direction::Redo) => {
+            let mut deleted = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(list_id) = delete_in(&mut tx, &id).await? {
+                    deleted.push((id, list_id));
+                }
+            }
+            tx.commit().await?;
+            for (id, list_id) in &deleted {
+                events::task_deleted(app, id, list_id.as_deref());
+            }
+            let ids: Vec<String> = deleted.into_iter().map(|(id, _)| id).collect();
+            Ok((!ids.is_empty()).then_some(UndoEntry::Delete(ids)))
+        }
+        (UndoEntry::Move { task_id, from, to }, direction) => {
+            let (list_id, index) = match direction {
+                Direction::Undo => &from,
+                Direction::Redo => &to,
+            };
+            let changed = move_in(&mut tx, &task_id, list_id, *index).await?;
+            tx.commit().await?;
+            if !changed.is_empty() {
+                events::tasks_changed(app);
+            }
+            Ok(Some(UndoEntry::Move { task_id, from, to }))
+        }
+    }
+}
+
+/// Pops the latest entry off one stack, replays it and moves it to the other.
+/// An entry that fails is dropped, as it would only fail again.
+async fn step(app: &AppHandle, state: &AppState, direction: Direction) -> Result<Option<Undone>> {
+    let entry = {
+        let mut history = state.history.lock().unwrap();
+        match direction {
+            Direction::Undo => history.undo.pop_back(),
+            Direction::Redo => history.redo.pop_back(),
+        }
+    };
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+    let undone = entry.summary();
+    if let Some(entry) = apply(app, state, entry, direction).await? {
+        let mut history = state.history.lock().unwrap();
+        match direction {
+            Direction::Undo => push(&mut history.redo, entry),
+            Direction::Redo => push(&mut history.undo, entry),
+        }
+    }
+    log::debug!("{direction:?} of {:?} for {} tasks", undone.kind, undone.task_ids.len());
+    Ok(Some(undone))
+}
+
+/// Reverses the latest completion, delete or move. `None` when there's nothing to undo.
+#[tauri::command]
+pub async fn undo(app: AppHandle, state: State<'_, AppState>) -> Result<Option<Undone>> {
+    step(&app, &state, Direction::Undo).await
+}
+
+/// Replays the latest undone change. `None` when there's nothing to redo.
+#[tauri::command]
+pub async fn redo(app: AppHandle, state: State<'_, AppState>) -> Result<Option<Undone>> {
+    step(&app, &state, Direction::Redo).await
+}
@@ -0,0 +1,143 @@
+//! In-app updates through the updater plugin. Checking never installs; the user
+//! decides when to call `install_update`.
+//!
+//! Releases are fetched from the endpoint under `plugins.updater` in
+//! `tauri.conf.json` and verified against its `pubkey`, which must match the key
+//! the release artifacts are signed with. Until a build ships that section (and
+//! `bundle.createUpdaterArtifacts`), the plugin isn't registered and both
+//! commands say updates aren't set up.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::{Updater, UpdaterExt};
+
+use crate::error::{Error, Result};
+use crate::settings;
+use crate::AppState;
+
+const AUTO_CHECK_KEY: &str = "autoCheckUpdates";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateStatus {
+    UpToDate,
+    Available,
+    /// Offline or the release server is down; not worth an error dialog.
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub status: UpdateStatus,
+    pub current_version: String,
+    pub version: Option<String>,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateProgress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// Whether this build has an updater section in `tauri.conf.json`; the plugin
+/// fails to start without one, so it's only registered when this holds.
+pub fn configured(app: &AppHandle) -> bool {
+    app.config().plugins.0.contains_key("updater")
+}
+
+fn updater(app: &AppHandle) -> Result<Updater> {
+    if !configured(app) {
+        return Err(Error::InvalidInput("Updates aren't set up in this build".into()));
+    }
+    Ok(app.updater()?)
+}
+
+/// Failures that mean the release server couldn't be reached. Everything else,
+/// like a bad endpoint or a malformed release manifest, is a real error.
+fn is_unreachable(e: &tauri_plugin_updater::Error) -> bool {
+    use tauri_plugin_updater::Error as E;
+    matches!(e, E::Reqwest(_) | E::Network(_) | E::ReleaseNotFound)
+}
+
+async fn check(app: &AppHandle) -> Result<UpdateInfo> {
+    let current_version = app.package_info().version.to_string();
+    match updater(app)?.check().await {
+        Ok(Some(update)) => Ok(UpdateInfo {
+            status: UpdateStatus::Available,
+            current_version,
+            version: Some(update.version.clone()),
+            notes: update.body.clone(),
+            date: update.date.map(|d| d.to_string()),
+        }),
+        Ok(None) => Ok(UpdateInfo {
+            status: UpdateStatus::UpToDate,
+            current_version,
+            version: None,
+            notes: None,
+            date: None,
+        }),
+        Err(e) if is_unreachable(&e) => {
+            log::warn!("Update check failed: {e}");
+            Ok(UpdateInfo {
+                status: UpdateStatus::Unreachable,
+                current_version,
+                version: None,
+                notes: None,
+                date: None,
+            })
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Checks once at launch when `autoCheckUpdates` is on, announcing a new version with `update-available`.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = handle.state::<AppState>();
+        match settings::get::<bool>(&state.db(), AUTO_CHECK_KEY).await {
+            Ok(Some(true)) => match check(&handle).await {
+                Ok(info @ UpdateInfo { status: UpdateStatus::Available, .. }) => {
+                    let _ = handle.emit("update-available", info);
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("Update check failed: {e}"),
+            },
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to read update setting: {e}"),
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo> {
+    check(&app).await
+}
+
+/// Downloads and installs the latest version, emitting `update-progress` along the way, then restarts.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<()> {
+    let update = updater(&app)?
+        .check()
+        .await?
+        .ok_or_else(|| Error::InvalidInput("Tada is already up to date".into()))?;
+    log::info!("Installing update {}", update.version);
+
+    let mut downloaded = 0u64;
+    update
+        .download_and_install(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = app.emit("update-progress", UpdateProgress { downloaded, total });
+            },
+            || {
+                let _ = app.emit("update-downloaded", ());
+            },
+        )
+        .await?;
+    app.restart()
+}
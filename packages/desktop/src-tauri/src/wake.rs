@@ -0,0 +1,69 @@
+//! Notices when the machine wakes from sleep (or the clock is changed) and
+//! catches up on what went stale meanwhile: missed reminders, the reminder
+//! schedule and the date groups. Then `clock-rolled` tells the UI to refresh.
+//!
+//! There is no portable resume notification, so this watches the clocks
+//! instead. A periodic check compares how far the wall clock moved against the
+//! monotonic clock, which stops during sleep on macOS and Linux; where it keeps
+//! running (Windows), the check itself overslept by hours, which gives it away.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::now_ms;
+use crate::{events, grouping, reminders, AppState};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Jumps smaller than this are scheduling jitter or NTP adjustments.
+const JUMP_THRESHOLD_MS: i64 = 3 * 60_000;
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClockRolled {
+    /// Wall-clock time of the last check before the jump, epoch millis.
+    since: i64,
+    now: i64,
+}
+
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_wall = now_ms();
+        let mut last_mono = Instant::now();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let wall = now_ms();
+            let mono = Instant::now();
+            let wall_elapsed = wall - last_wall;
+            let mono_elapsed = i64::try_from(mono.duration_since(last_mono).as_millis()).unwrap_or(i64::MAX);
+            let expected = CHECK_INTERVAL.as_millis() as i64;
+
+            let drifted = (wall_elapsed - mono_elapsed).abs() > JUMP_THRESHOLD_MS;
+            let overslept = wall_elapsed > expected + JUMP_THRESHOLD_MS;
+            if drifted || overslept {
+                log::info!("Wall clock moved {wall_elapsed} ms in {mono_elapsed} ms of uptime; catching up");
+                roll(&handle, last_wall, wall).await;
+            }
+            last_wall = wall;
+            last_mono = mono;
+        }
+    });
+}
+
+async fn roll(app: &AppHandle, since: i64, now: i64) {
+    let state = app.state::<AppState>();
+    let _permit = state.job_permit().await;
+    // The reminder timer only looks a minute back; everything older in the gap is caught up here
+    if let Err(e) = reminders::catch_up(app, since).await {
+        log::error!("Failed to catch up on reminders: {e}");
+    }
+    reminders::reschedule(app);
+    match grouping::recompute(&state.db()).await {
+        Ok(0) => {}
+        Ok(_) => events::tasks_changed(app),
+        Err(e) => log::error!("Failed to recompute task groups: {e}"),
+    }
+    let _ = app.emit("clock-rolled", ClockRolled { since, now });
+}
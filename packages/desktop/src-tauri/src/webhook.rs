@@ -0,0 +1,196 @@
+//! Outbound webhook: a small JSON POST to `outboundWebhookUrl` when a task is
+//! created, completed or trashed, for home automation and the like.
+//!
+//! Events are resolved when they're queued, right after the change commits: the
+//! URL, the list's `webhook_enabled` flag and the task's title and list are read
+//! then, from the database that changed, so a later workspace switch or trash
+//! purge doesn't change what gets sent. The queue is bounded and never waits,
+//! and a single background worker does the sending, so a slow or unreachable
+//! endpoint can't hold up a task operation. A failed delivery is retried with
+//! backoff, then dropped; so is an event arriving while the queue is full.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::mpsc;
+
+use crate::db::now_ms;
+use crate::error::{Error, Result};
+use crate::{events, settings, AppState};
+
+const URL_KEY: &str = "outboundWebhookUrl";
+/// Events waiting to be sent; past this, new ones are dropped.
+const QUEUE_LIMIT: usize = 256;
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled for each one after.
+const BACKOFF: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Event {
+    Created,
+    Completed,
+    Deleted,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Payload {
+    event: Event,
+    task_id: String,
+    title: String,
+    list_id: Option<String>,
+    list_name: String,
+    /// When the change happened, epoch millis; delivery may be later.
+    occurred_at: i64,
+}
+
+struct Queued {
+    url: String,
+    payload: Payload,
+}
+
+pub struct Webhooks(mpsc::Sender<Queued>);
+
+/// The configured URL, `None` while the webhook is off.
+async fn url(pool: &SqlitePool) -> Result<Option<String>> {
+    Ok(settings::get::<Option<String>>(pool, URL_KEY)
+        .await?
+        .flatten()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty()))
+}
+
+/// The payloads for `event` on each task, `None` while the webhook is off.
+/// Tasks that are gone or whose list has the webhook off are left out.
+async fn resolve<'a>(
+    pool: &SqlitePool,
+    event: Event,
+    task_ids: impl IntoIterator<Item = &'a str>,
+) -> Result<Option<(String, Vec<Payload>)>> {
+    let Some(url) = url(pool).await? else {
+        return Ok(None);
+    };
+    let occurred_at = now_ms();
+    let mut payloads = Vec::new();
+    for task_id in task_ids {
+        // Trashed tasks are still there, so deletions are found too
+        let row: Option<(String, Option<String>, String, bool)> = sqlx::query_as(
+            r#"
+            SELECT t.title, t.list_id, t.list_name, COALESCE(l.webhook_enabled, 1)
+            FROM tasks t LEFT JOIN lists l ON l.id = t.list_id
+            WHERE t.id = ?
+            "#,
+        )
+        .bind(task_id)
+        .fetch_optional(pool)
+        .await?;
+        if let Some((title, list_id, list_name, true)) = row {
+            payloads.push(Payload { event, task_id: task_id.to_string(), title, list_id, list_name, occurred_at });
+        }
+    }
+    Ok(Some((url, payloads)))
+}
+
+/// Queues `event` for each task, read from `pool` now. Call it once the change
+/// has committed; it doesn't wait for delivery and never fails the caller.
+pub async fn queue<'a>(app: &AppHandle, pool: &SqlitePool, event: Event, task_ids: impl IntoIterator<Item = &'a str>) {
+    let Some(webhooks) = app.try_state::<Webhooks>() else {
+        return;
+    };
+    let (url, payloads) = match resolve(pool, event, task_ids).await {
+        Ok(Some(resolved)) => resolved,
+        Ok(None) => return,
+        Err(e) => {
+            log::error!("Failed to prepare a webhook: {e}");
+            return;
+        }
+    };
+    for payload in payloads {
+        let task_id = payload.task_id.clone();
+        if webhooks.0.try_send(Queued { url: url.clone(), payload }).is_err() {
+            log::warn!("Webhook queue is full; dropping {event:?} for task {task_id}");
+        }
+    }
+}
+
+/// Sends `event` for one task and waits for it, for the CLI, which has no worker.
+pub async fn send(pool: &SqlitePool, event: Event, task_id: &str) -> Result<()> {
+    let Some((url, payloads)) = resolve(pool, event, [task_id]).await? else {
+        return Ok(());
+    };
+    let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+    for payload in &payloads {
+        deliver(&client, &url, payload).await;
+    }
+    Ok(())
+}
+
+/// One POST; `Ok(false)` for a failure worth retrying.
+async fn post(client: &reqwest::Client, url: &str, payload: &Payload) -> Result<bool> {
+    let response = match client.post(url).json(payload).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            log::warn!("Webhook request to {url} failed: {e}");
+            return Ok(false);
+        }
+    };
+    let status = response.status();
+    if status.is_success() {
+        return Ok(true);
+    }
+    log::warn!("Webhook endpoint {url} returned {status}");
+    // Other client errors won't go away by sending the same request again
+    if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::Http(format!("webhook endpoint returned {status}")));
+    }
+    Ok(false)
+}
+
+async fn deliver(client: &reqwest::Client, url: &str, payload: &Payload) {
+    for attempt in 1..=MAX_ATTEMPTS {
+        match post(client, url, payload).await {
+            Ok(true) => return,
+            Ok(false) if attempt < MAX_ATTEMPTS => tokio::time::sleep(BACKOFF * 2u32.pow(attempt - 1)).await,
+            Ok(false) | Err(_) => break,
+        }
+    }
+    log::warn!("Dropping webhook {:?} for task {}", payload.event, payload.task_id);
+}
+
+/// Starts the worker that sends queued events in order.
+pub fn init(app: &AppHandle) {
+    let (sender, mut receiver) = mpsc::channel::<Queued>(QUEUE_LIMIT);
+    app.manage(Webhooks(sender));
+    tauri::async_runtime::spawn(async move {
+        let client = match reqwest::Client::builder().timeout(TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Failed to set up the webhook client: {e}");
+                return;
+            }
+        };
+        while let Some(Queued { url, payload }) = receiver.recv().await {
+            deliver(&client, &url, &payload).await;
+        }
+    });
+}
+
+/// Turns the outbound webhook on or off for one list's tasks.
+#[tauri::command]
+pub async fn set_list_webhook(app: AppHandle, state: State<'_, AppState>, list_id: String, enabled: bool) -> Result<()> {
+    let updated = sqlx::query("UPDATE lists SET webhook_enabled = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(enabled)
+        .bind(now_ms())
+        .bind(&list_id)
+        .execute(&state.db())
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("List {list_id}")));
+    }
+    events::list_updated(&app, Some(&list_id));
+    Ok(())
+}
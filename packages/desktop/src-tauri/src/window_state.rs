@@ -0,0 +1,194 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, LogicalSize, Manager, PhysicalPosition, PhysicalSize, State, WebviewWindow, Window};
+
+use crate::error::{Error, Result};
+use crate::settings;
+use crate::AppState;
+
+const SETTINGS_KEY: &str = "window_state";
+const ALWAYS_ON_TOP_KEY: &str = "alwaysOnTop";
+/// The narrow floating checklist of compact mode, in logical pixels.
+const COMPACT_SIZE: LogicalSize<f64> = LogicalSize::new(340.0, 560.0);
+/// `minWidth` / `minHeight` of the main window in `tauri.conf.json`, lifted while compact.
+const MIN_SIZE: LogicalSize<f64> = LogicalSize::new(800.0, 600.0);
+/// Moving or resizing emits a stream of events; only the settled geometry is written.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+#[derive(Default)]
+pub struct WindowStateTracker {
+    generation: AtomicU64,
+    /// Last geometry seen while not maximized, so un-maximizing after a restart
+    /// returns to the size the user chose.
+    last_normal: Mutex<Option<WindowGeometry>>,
+    /// The geometry compact mode took over from; `Some` while compact.
+    before_compact: Mutex<Option<WindowGeometry>>,
+}
+
+/// Applies the saved geometry to the main window, falling back to centering
+/// on the primary monitor when the saved position isn't visible on any monitor.
+pub fn restore(app: &AppHandle) {
+    app.manage(WindowStateTracker::default());
+
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let pool = app.state::<AppState>().db();
+    restore_always_on_top(&window, &pool);
+    let saved = match tauri::async_runtime::block_on(settings::get::<WindowGeometry>(&pool, SETTINGS_KEY)) {
+        Ok(Some(saved)) => saved,
+        Ok(None) => return,
+        Err(e) => {
+            log::error!("Failed to read window state: {e}");
+            return;
+        }
+    };
+    *app.state::<WindowStateTracker>().last_normal.lock().unwrap() = Some(saved);
+
+    let size = PhysicalSize::new(saved.width, saved.height);
+    let _ = window.set_size(size);
+
+    let monitors = window.available_monitors().unwrap_or_default();
+    let visible = monitors.iter().any(|m| {
+        let (pos, area) = (m.position(), m.size());
+        // Require the title bar area to land on the monitor, not just a sliver of the window
+        saved.x + 100 > pos.x
+            && saved.x < pos.x + area.width as i32 - 100
+            && saved.y >= pos.y
+            && saved.y < pos.y + area.height as i32 - 50
+    });
+
+    if visible {
+        let _ = window.set_position(PhysicalPosition::new(saved.x, saved.y));
+    } else if let Ok(Some(primary)) = window.primary_monitor() {
+        let (pos, area) = (primary.position(), primary.size());
+        let width = saved.width.min(area.width);
+        let height = saved.height.min(area.height);
+        let _ = window.set_size(PhysicalSize::new(width, height));
+        let _ = window.set_position(PhysicalPosition::new(
+            pos.x + (area.width - width) as i32 / 2,
+            pos.y + (area.height - height) as i32 / 2,
+        ));
+    }
+
+    if saved.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Re-pins the main window if it was left always on top.
+fn restore_always_on_top(window: &WebviewWindow, pool: &sqlx::SqlitePool) {
+    match tauri::async_runtime::block_on(settings::get::<bool>(pool, ALWAYS_ON_TOP_KEY)) {
+        Ok(Some(true)) => {
+            if let Err(e) = window.set_always_on_top(true) {
+                log::error!("Failed to pin window on top: {e}");
+            }
+        }
+        Ok(_) => {}
+        Err(e) => log::error!("Failed to read always-on-top setting: {e}"),
+    }
+}
+
+/// Records the main window's geometry after a move or resize, debounced.
+pub fn track(window: &Window) {
+    let handle = window.app_handle();
+    let Some(tracker) = handle.try_state::<WindowStateTracker>() else {
+        return;
+    };
+    // Minimized windows report bogus positions (-32000 on Windows), and the
+    // compact strip isn't the size to come back to
+    if window.is_minimized().unwrap_or(false) || tracker.before_compact.lock().unwrap().is_some() {
+        return;
+    }
+
+    let maximized = window.is_maximized().unwrap_or(false);
+    let geometry = {
+        let mut last = tracker.last_normal.lock().unwrap();
+        if !maximized {
+            if let (Ok(pos), Ok(size)) = (window.outer_position(), window.inner_size()) {
+                if size.width > 0 && size.height > 0 {
+                    *last = Some(WindowGeometry {
+                        x: pos.x,
+                        y: pos.y,
+                        width: size.width,
+                        height: size.height,
+                        maximized: false,
+                    });
+                }
+            }
+        }
+        match *last {
+            Some(geometry) => WindowGeometry { maximized, ..geometry },
+            None => return,
+        }
+    };
+
+    let generation = tracker.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(DEBOUNCE).await;
+        if app.state::<WindowStateTracker>().generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let pool = app.state::<AppState>().db();
+        if let Err(e) = settings::set(&pool, SETTINGS_KEY, &geometry).await {
+            log::error!("Failed to save window state: {e}");
+        }
+    });
+}
+
+fn main_window(app: &AppHandle) -> Result<WebviewWindow> {
+    app.get_webview_window("main").ok_or_else(|| Error::NotFound("Main window".into()))
+}
+
+/// Pins the main window above other windows, or releases it, and remembers the choice.
+#[tauri::command]
+pub async fn set_always_on_top(app: AppHandle, state: State<'_, AppState>, enabled: bool) -> Result<()> {
+    main_window(&app)?.set_always_on_top(enabled)?;
+    settings::set(&state.db(), ALWAYS_ON_TOP_KEY, &enabled).await
+}
+
+/// Shrinks the main window to an undecorated strip, or puts back the size and
+/// position it had before. Not kept across restarts.
+#[tauri::command]
+pub fn set_compact_mode(app: AppHandle, tracker: State<'_, WindowStateTracker>, enabled: bool) -> Result<()> {
+    let window = main_window(&app)?;
+    let mut before = tracker.before_compact.lock().unwrap();
+    if enabled == before.is_some() {
+        return Ok(());
+    }
+
+    if enabled {
+        let (pos, size) = (window.outer_position()?, window.inner_size()?);
+        let maximized = window.is_maximized()?;
+        *before = Some(WindowGeometry { x: pos.x, y: pos.y, width: size.width, height: size.height, maximized });
+        if maximized {
+            window.unmaximize()?;
+        }
+        window.set_decorations(false)?;
+        window.set_min_size(None::<LogicalSize<f64>>)?;
+        window.set_size(COMPACT_SIZE)?;
+    } else if let Some(previous) = before.take() {
+        window.set_decorations(true)?;
+        window.set_min_size(Some(MIN_SIZE))?;
+        window.set_size(PhysicalSize::new(previous.width, previous.height))?;
+        window.set_position(PhysicalPosition::new(previous.x, previous.y))?;
+        if previous.maximized {
+            window.maximize()?;
+        }
+    }
+    let _ = app.emit("compact-mode-changed", enabled);
+    Ok(())
+}
@@ -0,0 +1,262 @@
+//! Named workspaces, each a separate database file in the app config dir.
+//!
+//! `workspaces.json` lists them and remembers the active one. The default
+//! workspace is the original `tada.db`, so existing installs keep their data.
+//! The webview opens the same file through the SQL plugin; `workspace-changed`
+//! carries the `sqlite:` URL for it to load.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::{self, DB_FILE};
+use crate::error::{Error, Result};
+use crate::events;
+use crate::{focus, list_windows, migrations, paths, reminders, tray, undo, AppState};
+
+const WORKSPACES_FILE: &str = "workspaces.json";
+pub const DEFAULT_WORKSPACE: &str = "Default";
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub name: String,
+    /// File name inside the app config dir.
+    pub file: String,
+}
+
+impl Workspace {
+    /// The connection string the SQL plugin knows this database by.
+    pub fn sql_url(&self) -> String {
+        paths::sql_url(&self.file)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspacesFile {
+    active: String,
+    workspaces: Vec<Workspace>,
+}
+
+impl Default for WorkspacesFile {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_WORKSPACE.into(),
+            workspaces: vec![Workspace { name: DEFAULT_WORKSPACE.into(), file: DB_FILE.into() }],
+        }
+    }
+}
+
+impl WorkspacesFile {
+    fn find(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.name.eq_ignore_ascii_case(name))
+    }
+
+    fn active(&self) -> &Workspace {
+        self.find(&self.active).unwrap_or(&self.workspaces[0])
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceInfo {
+    pub name: String,
+    pub active: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceChanged<'a> {
+    name: &'a str,
+    db_url: String,
+}
+
+/// Name of the active workspace, for the tray and window title.
+#[derive(Default)]
+pub struct ActiveWorkspace(Mutex<String>);
+
+/// A missing or unreadable file means the single default workspace.
+fn load(config_dir: &Path) -> WorkspacesFile {
+    let path = config_dir.join(WORKSPACES_FILE);
+    let Ok(bytes) = std::fs::read(&path) else {
+        return WorkspacesFile::default();
+    };
+    match serde_json::from_slice::<WorkspacesFile>(&bytes) {
+        Ok(file) if !file.workspaces.is_empty() => file,
+        Ok(_) => WorkspacesFile::default(),
+        Err(e) => {
+            log::warn!("Ignoring unreadable {WORKSPACES_FILE}: {e}");
+            WorkspacesFile::default()
+        }
+    }
+}
+
+/// Written to a temp file and renamed, so a crash can't leave half a file behind.
+fn save(config_dir: &Path, file: &WorkspacesFile) -> Result<()> {
+    std::fs::create_dir_all(config_dir)?;
+    let tmp = config_dir.join(format!("{WORKSPACES_FILE}.tmp"));
+    std::fs::write(&tmp, serde_json::to_vec_pretty(file)?)?;
+    std::fs::rename(tmp, config_dir.join(WORKSPACES_FILE))?;
+    Ok(())
+}
+
+/// Path of the active workspace's database.
+pub fn active_db_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(&load(config_dir).active().file)
+}
+
+/// The `sqlite:` URL of the active workspace's database.
+pub fn active_sql_url(config_dir: &Path) -> String {
+    load(config_dir).active().sql_url()
+}
+
+/// Every workspace's `sqlite:` URL, so the SQL plugin migrates whichever one the webview opens.
+pub fn sql_urls(config_dir: &Path) -> Vec<String> {
+    load(config_dir).workspaces.iter().map(Workspace::sql_url).collect()
+}
+
+/// Folder the active workspace's attachments and backups go in, under
+/// `attachments/` and `backups/`: its database file's stem, `tada` for the default.
+pub fn active_folder(config_dir: &Path) -> String {
+    let file = load(config_dir).active().file.clone();
+    Path::new(&file).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or(file)
+}
+
+/// Whether there's more than the one workspace.
+pub fn has_several(config_dir: &Path) -> bool {
+    load(config_dir).workspaces.len() > 1
+}
+
+pub fn active_name(app: &AppHandle) -> String {
+    app.state::<ActiveWorkspace>().0.lock().unwrap().clone()
+}
+
+/// `Work Stuff` → `workspace-work-stuff.db`, numbered if that file is taken.
+fn file_name_for(name: &str, existing: &[Workspace]) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() { "workspace".to_string() } else { slug };
+    let taken = |file: &str| existing.iter().any(|w| w.file == file);
+    let mut file = format!("workspace-{slug}.db");
+    let mut n = 2;
+    while taken(&file) {
+        file = format!("workspace-{slug}-{n}.db");
+        n += 1;
+    }
+    file
+}
+
+/// The workspace to show in the title bar and tray; the default one stays plain "Tada".
+pub fn label(app: &AppHandle) -> Option<String> {
+    Some(active_name(app)).filter(|name| name != DEFAULT_WORKSPACE)
+}
+
+fn apply_title(app: &AppHandle, name: &str) {
+    let title = if name == DEFAULT_WORKSPACE { "Tada".to_string() } else { format!("Tada — {name}") };
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_title(&title);
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<()> {
+    let file = load(&paths::config_dir(app)?);
+    let name = file.active().name.clone();
+    apply_title(app, &name);
+    app.manage(ActiveWorkspace(Mutex::new(name)));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_workspaces(app: AppHandle) -> Result<Vec<WorkspaceInfo>> {
+    let file = load(&paths::config_dir(&app)?);
+    let active = file.active().name.clone();
+    Ok(file
+        .workspaces
+        .into_iter()
+        .map(|w| WorkspaceInfo { active: w.name == active, name: w.name })
+        .collect())
+}
+
+/// Adds a workspace with a fresh, migrated database. It doesn't become active until switched to.
+#[tauri::command]
+pub async fn create_workspace(app: AppHandle, name: String) -> Result<WorkspaceInfo> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(Error::InvalidInput(format!("Workspace names must be 1 to {MAX_NAME_LEN} characters")));
+    }
+    let config_dir = paths::config_dir(&app)?;
+    let mut file = load(&config_dir);
+    if file.find(&name).is_some() {
+        return Err(Error::InvalidInput(format!("A workspace named '{name}' already exists")));
+    }
+
+    let workspace = Workspace { file: file_name_for(&name, &file.workspaces), name };
+    std::fs::create_dir_all(&config_dir)?;
+    let pool = db::open(&config_dir.join(&workspace.file)).await?;
+    migrations::run(&pool).await?;
+    migrations::sync_user_version(&pool).await?;
+    pool.close().await;
+
+    file.workspaces.push(workspace.clone());
+    save(&config_dir, &file)?;
+    log::info!("Created workspace '{}' in {}", workspace.name, workspace.file);
+    Ok(WorkspaceInfo { name: workspace.name, active: false })
+}
+
+/// Points the Rust side at another workspace's database and tells the UI to reload.
+///
+/// Background jobs read the pool from `AppState` on every run, so swapping it is
+/// enough to move them over; the reminder queue and focus timer are rebuilt from
+/// the new database right away instead of waiting for their next tick.
+#[tauri::command]
+pub async fn switch_workspace(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<()> {
+    let config_dir = paths::config_dir(&app)?;
+    let mut file = load(&config_dir);
+    let workspace = file.find(&name).cloned().ok_or_else(|| Error::NotFound(format!("Workspace '{name}'")))?;
+    if workspace.name == active_name(&app) {
+        return Ok(());
+    }
+
+    // Migrated before the swap, so nothing ever sees a half-upgraded schema
+    let pool = db::open(&config_dir.join(&workspace.file)).await?;
+    if let Err(e) = migrations::ensure_supported(&mut *pool.acquire().await?).await {
+        pool.close().await;
+        return Err(e);
+    }
+    migrations::run(&pool).await?;
+    migrations::sync_user_version(&pool).await?;
+
+    focus::stop(&app);
+    // Their lists belong to the workspace being left
+    list_windows::close_all(&app);
+    let old = state.replace_db(pool);
+    undo::clear(&state);
+    file.active = workspace.name.clone();
+    save(&config_dir, &file)?;
+    *app.state::<ActiveWorkspace>().0.lock().unwrap() = workspace.name.clone();
+    log::info!("Switched to workspace '{}'", workspace.name);
+
+    reminders::reschedule(&app);
+    focus::resume(&app);
+    apply_title(&app, &workspace.name);
+    if let Err(e) = tray::refresh(&app).await {
+        log::error!("Failed to refresh tray: {e}");
+    }
+    let _ = app.emit("workspace-changed", WorkspaceChanged { name: &workspace.name, db_url: workspace.sql_url() });
+    events::tasks_changed(&app);
+    events::list_updated(&app, None);
+
+    // Jobs that grabbed the old pool just before the swap finish on it first
+    tauri::async_runtime::spawn(async move { old.close().await });
+    Ok(())
+}
@@ -1,4 +1,5 @@
 import Database from '@tauri-apps/plugin-sql';
+import { invoke } from '@tauri-apps/api/core';
 import { IStorageService } from '@tada/core/services/storageInterface';
 import {
     AISettings,
@@ -119,7 +120,9 @@ export class SqliteStorageService implements IStorageService {
 
     async initialize(): Promise<void> {
         try {
-            this.db = await Database.load('sqlite:tada.db');
+            // Resolved by the backend, which knows the active workspace and portable mode
+            const { dbUrl } = await invoke<{ dbUrl: string }>('get_data_dir');
+            this.db = await Database.load(dbUrl);
             console.log('Database connected successfully');
 
             await this.ensureIndexes();